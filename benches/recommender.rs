@@ -0,0 +1,67 @@
+//! Recommender benchmarks over synthetic training histories
+//!
+//! Run with `cargo bench --bench recommender`.
+
+use chrono::{Duration, Utc};
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+
+use majowuji::db::Training;
+use majowuji::exercises::get_all_exercises;
+use majowuji::ml::{GoalCalculator, Recommender};
+
+/// History sizes to measure
+const SIZES: [usize; 3] = [100, 1_000, 10_000];
+
+/// `n` trainings cycling through the catalog, one every 20 minutes back from now
+fn synthetic_history(n: usize) -> Vec<Training> {
+    let exercises = get_all_exercises();
+    let now = Utc::now();
+    (0..n)
+        .map(|i| {
+            let exercise = exercises[i % exercises.len()];
+            Training {
+                id: Some(i as i64),
+                date: now - Duration::minutes(20 * i as i64),
+                exercise: exercise.name.to_string(),
+                sets: 1,
+                reps: 10 + (i % 15) as i32,
+                duration_secs: Some(30 + (i % 60) as i32),
+                pulse_before: Some(70 + (i % 10) as i32),
+                pulse_after: Some(120 + (i % 30) as i32),
+                rpe: None,
+                notes: None,
+                user_id: Some(1),
+                context: None,
+                category: Some(exercise.category),
+                is_timed: Some(exercise.is_timed),
+            }
+        })
+        .collect()
+}
+
+fn bench_recommendation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("get_recommendation");
+    for size in SIZES {
+        let history = synthetic_history(size);
+        // Includes Recommender::new, which builds the MuscleTracker
+        group.bench_with_input(BenchmarkId::from_parameter(size), &history, |b, history| {
+            b.iter(|| Recommender::new(black_box(history.clone())).get_recommendation())
+        });
+    }
+    group.finish();
+}
+
+fn bench_goal_calculator(c: &mut Criterion) {
+    let mut group = c.benchmark_group("goal_calculate");
+    let exercise = get_all_exercises()[0].name;
+    for size in SIZES {
+        let history = synthetic_history(size);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &history, |b, history| {
+            b.iter(|| GoalCalculator::calculate(black_box(history), exercise))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_recommendation, bench_goal_calculator);
+criterion_main!(benches);
@@ -1,26 +1,31 @@
 //! Telegram bot module - Remote training logging with hourly reminders
 
-use std::collections::HashSet;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use std::time::Duration;
-use chrono::{DateTime, FixedOffset, Utc};
+use chrono::{DateTime, Datelike, FixedOffset, Timelike, Utc, Weekday};
 use teloxide::{
     prelude::*,
-    types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup},
+    types::{ChatId, InlineKeyboardButton, InlineKeyboardMarkup, InputFile},
     utils::command::BotCommands,
     dispatching::dialogue::{InMemStorage, Dialogue},
 };
 use tokio::sync::Mutex;
-use tracing::{info, error};
-
-use crate::db::{Database, Training, User};
-use crate::exercises::{get_base_exercises, find_exercise, find_exercise_by_name, EXTRA_EXERCISES};
-use crate::ml::{Recommender, ProgressPredictor, GoalCalculator, Recommendation};
+use tokio::task::JoinHandle;
+use tracing::{info, error, warn};
+
+use crate::db::{day_bounds_utc, user_tz, DailyTipSubscription, Database, Goal, Training, User, UserSettings, BODYWEIGHT_RANGE_KG, DEFAULT_TZ_OFFSET_MINUTES, TRASH_RETENTION_HOURS, TZ_OFFSET_RANGE_MINUTES, WEEKLY_TARGET_RANGE};
+use crate::exercises::{Category, Equipment, Exercise, MuscleGroup, WeekdayTemplate, get_base_exercises, find_exercise, find_exercise_by_alias, find_exercise_by_name, progression, recommended_rest, validate_reps, DIFFICULTY_RANGE, EXTRA_EXERCISES};
+use crate::ml::predictor::MIN_DATA_POINTS;
+use crate::ml::{render_progress_png, sessions_remaining, zone, resting_pulse_elevated, RESTING_PULSE_DRIFT_BPM_PER_DAY, MuscleTracker, PlanItem, max_hr, pulse_trend, streak_days_with_rest, Analytics, Recommender, ProgressPredictor, GoalCalculator, Recommendation, PeriodComparison};
+use crate::lang::{command_description_en, t, Key, Lang};
 use crate::tips;
 
 /// Bot configuration
 pub struct BotConfig {
     pub max_users: usize,
+    /// Longest believable set, seconds; longer timers look abandoned
+    pub max_set_duration_secs: i32,
 }
 
 impl Default for BotConfig {
@@ -30,23 +35,75 @@ impl Default for BotConfig {
                 .ok()
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(10),
+            max_set_duration_secs: std::env::var("MAX_SET_DURATION_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .filter(|secs| *secs > 0)
+                .unwrap_or(DEFAULT_MAX_SET_DURATION_SECS),
         }
     }
 }
 
 type MyDialogue = Dialogue<State, InMemStorage<State>>;
 type HandlerResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
-type Subscribers = Arc<Mutex<HashSet<ChatId>>>;
+/// Reminder subscribers with the time each one's next reminder is due
+type Subscribers = Arc<Mutex<HashMap<ChatId, DateTime<Utc>>>>;
+type Runtime = Arc<Mutex<ChatRuntime>>;
+
+/// Per-chat runtime state that lives outside the dialogue
+#[derive(Default)]
+pub struct ChatRuntime {
+    /// Pending session-end timers, rescheduled on every logged set
+    session_timers: HashMap<ChatId, JoinHandle<()>>,
+    /// Running /rest timers, at most one per chat
+    rest_timers: HashMap<ChatId, JoinHandle<()>>,
+    /// Indices of the last tips shown, oldest first
+    recent_tips: HashMap<ChatId, VecDeque<usize>>,
+}
+
+/// How many recent tips /tip avoids repeating
+const RECENT_TIPS: usize = 3;
+
+/// Default cap on a measured set duration (30 minutes)
+const DEFAULT_MAX_SET_DURATION_SECS: i32 = 1800;
+
+/// Seconds between tapping an exercise and actually starting the set
+const SET_PREP_SECS: i32 = 5;
 
 /// Reminder interval (1 hour = 3600 seconds)
 const REMINDER_INTERVAL_SECS: u64 = 3600;
 
-/// Moscow timezone offset (UTC+3)
-const MOSCOW_OFFSET_SECS: i32 = 3 * 3600;
+/// Local hour of the daily tip when /dailytip on has no hour
+const DEFAULT_DAILY_TIP_HOUR: u32 = 8;
+
+/// How often the reminder task checks for due subscribers
+const REMINDER_TICK_SECS: u64 = 60;
+
+/// Snooze delay offered on reminder messages (minutes)
+const SNOOZE_MINUTES: i64 = 30;
+
+/// Rest between sets when /rest has no argument: 90 s, the strength interval from the tips
+const DEFAULT_REST_SECS: u64 = 90;
+
+/// Longest accepted /rest timer (30 minutes)
+const MAX_REST_SECS: u64 = 30 * 60;
+
+/// Inactivity after the last activity that ends a session (30 minutes)
+const SESSION_IDLE_SECS: i64 = 30 * 60;
+
+/// Days of history loaded for /stats: the 30-day window plus margin
+/// so the 28-day load ratio still sees older trainings
+const STATS_WINDOW_DAYS: i64 = 35;
+
+/// Pause before the single retry of a failed DB open (e.g. a transient lock)
+const DB_OPEN_RETRY_MS: u64 = 500;
+
+/// Moscow timezone offset (UTC+3), the default user zone
+const MOSCOW_OFFSET_SECS: i32 = DEFAULT_TZ_OFFSET_MINUTES * 60;
 
 /// Get Moscow timezone for consistent date handling
 fn moscow_tz() -> FixedOffset {
-    FixedOffset::east_opt(MOSCOW_OFFSET_SECS).unwrap()
+    user_tz(DEFAULT_TZ_OFFSET_MINUTES)
 }
 
 /// Format duration in seconds to human-readable string
@@ -60,12 +117,39 @@ fn format_duration(secs: i32) -> String {
     }
 }
 
+/// Measured set duration: elapsed time minus preparation, at most `cap`.
+/// The flag is set when the value was clamped
+fn finalize_duration(elapsed_secs: i64, prep_secs: i32, cap_secs: i32) -> (i32, bool) {
+    let secs = (elapsed_secs - i64::from(prep_secs)).max(0);
+    if secs > i64::from(cap_secs) {
+        (cap_secs, true)
+    } else {
+        (secs as i32, false)
+    }
+}
+
 #[derive(Clone, Default)]
 pub enum State {
     #[default]
     Start,
     /// Waiting for message to forward to owner (limit reached)
     WaitingForOwnerMessage,
+    /// Setup wizard step 1: choosing timezone
+    SetupTimezone {
+        user_id: i64,
+    },
+    /// Setup wizard step 2: toggling base exercises
+    SetupBase {
+        user_id: i64,
+        tz_offset_minutes: i32,
+        base_exercises: Vec<String>,
+    },
+    /// Setup wizard step 3: reminder preference
+    SetupReminders {
+        user_id: i64,
+        tz_offset_minutes: i32,
+        base_exercises: Vec<String>,
+    },
     /// Waiting for pulse before exercise
     WaitingForPulseBefore {
         exercise_id: String,
@@ -89,6 +173,41 @@ pub enum State {
         duration_secs: i32,
         user_id: i64,
     },
+    /// Waiting for an optional note ("-" or the button skips)
+    WaitingForNotes {
+        exercise_id: String,
+        exercise_name: String,
+        pulse_before: i32,
+        reps: i32,
+        duration_secs: i32,
+        pulse_after: i32,
+        user_id: i64,
+    },
+    /// Waiting for perceived exertion (optional, "-" skips)
+    WaitingForRpe {
+        exercise_id: String,
+        exercise_name: String,
+        pulse_before: i32,
+        reps: i32,
+        duration_secs: i32,
+        pulse_after: i32,
+        notes: Option<String>,
+        user_id: i64,
+    },
+}
+
+impl State {
+    /// Whether this is a step of logging a set (the only flow the session timer may cut short)
+    fn is_logging(&self) -> bool {
+        matches!(
+            self,
+            State::WaitingForPulseBefore { .. }
+                | State::WaitingForReps { .. }
+                | State::WaitingForPulseAfter { .. }
+                | State::WaitingForNotes { .. }
+                | State::WaitingForRpe { .. }
+        )
+    }
 }
 
 #[derive(BotCommands, Clone)]
@@ -102,27 +221,320 @@ pub enum Command {
     Train,
     #[command(description = "Сегодняшние тренировки")]
     Today,
-    #[command(description = "Статистика")]
-    Stats,
+    #[command(description = "Статистика (или по упражнению: /stats пловец)")]
+    Stats(String),
+    #[command(description = "Статистика одной строкой")]
+    S,
+    #[command(description = "Серия дней подряд и рекорд")]
+    Streak,
+    #[command(description = "Личные рекорды по упражнениям")]
+    Records,
     #[command(description = "Баланс нагрузки по группам мышц")]
     Balance,
+    #[command(rename = "balance_trend", description = "Баланс по неделям")]
+    BalanceTrend,
+    #[command(description = "Объём на группу мышц по неделям: /muscle спина")]
+    Muscle(String),
+    #[command(description = "Когда дойду до цели: /goal отжимания 50")]
+    Goal(String),
+    #[command(description = "То же, что /goal: /goalreps отжимания 30")]
+    GoalReps(String),
+    #[command(rename = "setgoal", description = "Поставить цель: /setgoal планка 3м или /setgoal пловец 30")]
+    SetGoal(String),
+    #[command(description = "Мои цели и прогресс")]
+    Goals,
+    #[command(rename = "delgoal", description = "Удалить цель: /delgoal 3")]
+    DelGoal(String),
+    #[command(description = "График повторов с трендом: /graph пловец")]
+    Graph(String),
+    #[command(description = "Прирост пульса по подходам: /pulse пловец")]
+    Pulse(String),
+    #[command(description = "Тренировки дня файлом JSON: /day 2026-01-05")]
+    Day(String),
+    #[command(description = "Карта нагрузки по телу за сегодня")]
+    Body,
+    #[command(description = "Эта неделя против прошлой")]
+    Compare,
+    #[command(description = "Рейтинг по подходам за 7 дней")]
+    Leaderboard,
+    #[command(description = "Скрыть себя из рейтинга или вернуть")]
+    Private,
+    #[command(description = "Возраст для зон пульса: /age 35")]
+    Age(String),
+    #[command(description = "Язык интерфейса: /lang en или /lang ru")]
+    Lang(String),
+    #[command(description = "Часовой пояс в минутах от UTC: /tz +180")]
+    Tz(String),
+    #[command(description = "Записать вес: /weight 72.5")]
+    Weight(String),
     #[command(description = "Включить напоминания раз в час")]
     Remind,
     #[command(description = "Выключить напоминания")]
     Stop,
+    #[command(description = "Тихие часы без напоминаний: /quiet 22 7 или /quiet off")]
+    Quiet(String),
+    #[command(description = "Выключить все фоновые уведомления")]
+    StopAll,
+    #[command(description = "Отметить больную группу мышц: /sore спина")]
+    Sore(String),
+    #[command(description = "Фокус по дням недели: /template пн жим")]
+    Template(String),
+    #[command(description = "Где тренируюсь: /where дома")]
+    Where(String),
+    #[command(description = "Инвентарь под рукой: /gear ручки ступенька")]
+    Gear(String),
+    #[command(description = "Порог бонуса в % базы: /bonusrule 80")]
+    BonusRule(String),
+    #[command(description = "Потолок сложности бонуса 1-5: /level 2 (/level off - без ограничений)")]
+    Level(String),
+    #[command(description = "Цель: тренировок в неделю: /weekly 4 (/weekly off - без цели)")]
+    Weekly(String),
+    #[command(description = "Таймер отдыха между подходами: /rest 60 (по умолчанию 90с)")]
+    Rest(String),
+    #[command(description = "Отметить день отдыха")]
+    RestDay,
+    #[command(description = "Удалить последний подход")]
+    Undo,
+    #[command(description = "Отменить текущий подход или настройку")]
+    Cancel,
+    #[command(description = "Повторить тренировку дня: /repeat 2026-01-05")]
+    Repeat(String),
+    #[command(description = "План на сегодня с отметками")]
+    Checklist,
     #[command(description = "Совет из книги")]
     Tip,
+    #[command(description = "Совет дня каждое утро: /dailytip on 8 или /dailytip off")]
+    DailyTip(String),
+    #[command(description = "Открыть доступ по запросу (владелец): /approve 123456")]
+    Approve(String),
+    #[command(description = "Отклонить запрос на доступ (владелец): /deny 123456")]
+    Deny(String),
+    #[command(description = "Пользователи и их активность (владелец)")]
+    Users,
+    #[command(description = "Перенести тренировки в другой аккаунт (владелец): /merge 111 222")]
+    Merge(String),
+}
+
+/// Sections used to group commands in /help
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HelpSection {
+    Training,
+    Stats,
+    Settings,
+}
+
+impl HelpSection {
+    const ALL: [HelpSection; 3] = [HelpSection::Training, HelpSection::Stats, HelpSection::Settings];
+
+    fn title(&self, lang: Lang) -> &'static str {
+        let key = match self {
+            HelpSection::Training => Key::SectionTraining,
+            HelpSection::Stats => Key::SectionStats,
+            HelpSection::Settings => Key::SectionSettings,
+        };
+        t(key, lang)
+    }
+}
+
+impl Command {
+    /// Help section and usage example for the command.
+    /// Exhaustive match: a new variant won't compile until it is placed in /help.
+    fn help_info(&self) -> (HelpSection, &'static str) {
+        match self {
+            Command::Start => (HelpSection::Settings, "/start → регистрация и список команд"),
+            Command::Help => (HelpSection::Settings, "/help → это сообщение"),
+            Command::Train => (HelpSection::Training, "/train → ✓ упражнение → пульс 80 → 15 повт. → пульс 120"),
+            Command::Today => (HelpSection::Stats, "/today → • пловец - 1x20"),
+            Command::Stats(_) => (HelpSection::Stats, "/stats → подходы и время за день/неделю/месяц"),
+            Command::Records => (HelpSection::Stats, "/records → • отжимания на кулаках — 22 повт. / • стойка на локтях — 1м 30с"),
+            Command::Streak => (HelpSection::Stats, "/streak → 🔥 Серия: 4 дня / 🏆 Рекорд: 9 дней"),
+            Command::S => (HelpSection::Stats, "/s → Сегодня: 3 подх. / Неделя: 12 / Стрик: 4 дня / Баланс: 68%"),
+            Command::Balance => (HelpSection::Stats, "/balance → [++..] спина: 40 повторов"),
+            Command::BalanceTrend => (HelpSection::Stats, "/balance_trend → 12.01 [######....] 62%"),
+            Command::Muscle(_) => (HelpSection::Stats, "/muscle спина → 12.01 [######....] 60 📈"),
+            Command::Goal(_) => (HelpSection::Stats, "/goal пловец 30 → 🎯 30 повт. через ~18 дней (к 05.02)"),
+            Command::GoalReps(_) => (HelpSection::Stats, "/goalreps пловец 30 → то же, что /goal"),
+            Command::SetGoal(_) => (HelpSection::Training, "/setgoal планка 3м → 🎯 поздравление, когда простоишь 3 минуты"),
+            Command::Goals => (HelpSection::Stats, "/goals → #1 стойка на локтях — 3м 0с (лучшее: 1м 30с, 50%)"),
+            Command::DelGoal(_) => (HelpSection::Training, "/delgoal 1 → 🗑 Цель удалена"),
+            Command::Graph(_) => (HelpSection::Stats, "/graph пловец → 🖼 точки подходов и красная линия тренда"),
+            Command::Pulse(_) => (HelpSection::Stats, "/pulse пловец → 🫀 █▆▅▃▂▁ 48 → 30 уд/мин"),
+            Command::Day(_) => (HelpSection::Stats, "/day 2026-01-05 → 📄 trainings-2026-01-05.json"),
+            Command::Body => (HelpSection::Stats, "/body → 💪 [++++] верх, жим: 45"),
+            Command::Compare => (HelpSection::Stats, "/compare → Тренировок: 3 📈 (было 2)"),
+            Command::Leaderboard => (HelpSection::Stats, "/leaderboard → 🥇 Анна — 42 подх."),
+            Command::Private => (HelpSection::Settings, "/private → 🙈 Ты скрыт из рейтинга"),
+            Command::Age(_) => (HelpSection::Settings, "/age 35 → макс. пульс ≈ 185, зона в каждом подходе"),
+            Command::Lang(_) => (HelpSection::Settings, "/lang en → /help и /stats на английском"),
+            Command::Tz(_) => (HelpSection::Settings, "/tz +600 → «сегодня» считается по UTC+10"),
+            Command::Weight(_) => (HelpSection::Stats, "/weight 72.5 → ⚖️ 72.5 кг (-0.7 кг)"),
+            Command::Remind => (HelpSection::Settings, "/remind → ⏰ Время размяться! (раз в час)"),
+            Command::Stop => (HelpSection::Settings, "/stop → 🔕 Напоминания выключены"),
+            Command::StopAll => (HelpSection::Settings, "/stopall → 🔕 Выключено: • напоминания раз в час"),
+            Command::Sore(_) => (HelpSection::Training, "/sore спина → /train пару дней не даёт грузить спину"),
+            Command::Template(_) => (HelpSection::Settings, "/template чт жим → по четвергам /train предлагает жим"),
+            Command::Where(_) => (HelpSection::Settings, "/where зал → следующие подходы помечены «зал» (/where - сброс)"),
+            Command::Gear(_) => (HelpSection::Settings, "/gear нет → в поездке /train не предложит упражнения с инвентарём"),
+            Command::Quiet(_) => (HelpSection::Settings, "/quiet 22 7 → без напоминаний с 22:00 до 07:00"),
+            Command::BonusRule(_) => (HelpSection::Settings, "/bonusrule 80 → бонус открывается после 80% базы"),
+            Command::Level(_) => (HelpSection::Settings, "/level 2 → бонус только из упражнений сложностью 1-2"),
+            Command::Weekly(_) => (HelpSection::Settings, "/weekly 4 → 🎯 Цель недели: 1/4, осталось 3 трен."),
+            Command::Rest(_) => (HelpSection::Training, "/rest 60 → ⏰ Отдых окончен! через минуту"),
+            Command::RestDay => (HelpSection::Training, "/restday → 😴 День отдыха, стрик не прервётся"),
+            Command::Undo => (HelpSection::Training, "/undo → 🗑 Удалено: пловец - 1x15 (вернуть можно 24 ч)"),
+            Command::Cancel => (HelpSection::Training, "/cancel → ❌ Отменено, ничего не сохранено (или кнопка «❌ Отмена»)"),
+            Command::Repeat(_) => (HelpSection::Training, "/repeat → вчерашние упражнения по порядку с теми же целями"),
+            Command::Checklist => (HelpSection::Training, "/checklist → ✅ пловец ⬜ стойка на локтях"),
+            Command::Tip => (HelpSection::Training, "/tip → 📖 совет из книги"),
+            Command::DailyTip(_) => (HelpSection::Settings, "/dailytip on 8 → 📖 совет дня каждое утро в 08:00"),
+            Command::Approve(_) => (HelpSection::Settings, "/approve 123456 → ✅ Доступ открыт, пользователь получит уведомление"),
+            Command::Deny(_) => (HelpSection::Settings, "/deny 123456 → 🚫 Запрос отклонён"),
+            Command::Users => (HelpSection::Settings, "/users → @ivan — 42 подх."),
+            Command::Merge(_) => (HelpSection::Settings, "/merge 111 222 → 🔀 Перенесено 42 подх."),
+        }
+    }
+}
+
+/// /stats summary: totals per day/week/month plus today's sets by exercise
+fn format_stats_summary(trainings: &[Training], total: usize, now: DateTime<Utc>, tz: FixedOffset, lang: Lang) -> String {
+    let today = now.with_timezone(&tz).date_naive();
+    let week_ago = today - chrono::Duration::days(7);
+    let month_ago = today - chrono::Duration::days(30);
+    let local_date = |tr: &Training| tr.date.with_timezone(&tz).date_naive();
+
+    let today_trainings: Vec<_> = trainings.iter().filter(|tr| local_date(tr) == today).collect();
+    let week_trainings: Vec<_> = trainings.iter().filter(|tr| local_date(tr) > week_ago).collect();
+    let month_trainings: Vec<_> = trainings.iter().filter(|tr| local_date(tr) > month_ago).collect();
+    let total_time = |set: &[&Training]| -> i32 { set.iter().filter_map(|tr| tr.duration_secs).sum() };
+
+    let mut text = format!(
+        "{}\n\n{}: {} {}\n{}: {} ({})\n{}: {} ({})\n{}: {} ({})\n",
+        t(Key::StatsTitle, lang),
+        t(Key::StatsTotal, lang), total, t(Key::Sets, lang),
+        t(Key::StatsToday, lang), today_trainings.len(), format_duration(total_time(&today_trainings)),
+        t(Key::StatsWeek, lang), week_trainings.len(), format_duration(total_time(&week_trainings)),
+        t(Key::StatsMonth, lang), month_trainings.len(), format_duration(total_time(&month_trainings))
+    );
+    let analytics = Analytics::new(trainings.to_vec());
+    if let Some(warning) = acwr_warning(analytics.acwr()) {
+        text.push_str(&format!("\n{}\n", warning));
+    }
+    if let Some(warning) = resting_pulse_warning(analytics.resting_pulse_trend_at(now, tz)) {
+        text.push_str(&format!("\n{}\n", warning));
+    }
+
+    // Group today's trainings by exercise: (sets, total_reps, total_time, max_time, is_timed)
+    if !today_trainings.is_empty() {
+        text.push_str(&format!("\n{}\n", t(Key::StatsTodayBreakdown, lang)));
+        let mut exercise_stats: HashMap<&str, (usize, i32, i32, i32, bool)> = HashMap::new();
+        for tr in &today_trainings {
+            let duration = tr.duration_secs.unwrap_or(0);
+            let entry = exercise_stats.entry(&tr.exercise)
+                .or_insert((0, 0, 0, 0, tr.exercise_is_timed().unwrap_or(false)));
+            entry.0 += 1;
+            entry.1 += tr.reps;
+            entry.2 += duration;
+            entry.3 = entry.3.max(duration);
+        }
+        for (exercise, (sets, reps, total_time, max_time, is_timed)) in exercise_stats {
+            if is_timed {
+                // Timed exercises: max hold and total time
+                text.push_str(&format!(
+                    "• {} - {} {}, {} {}{}, {} {}\n",
+                    exercise, sets, t(Key::Sets, lang),
+                    t(Key::Max, lang), max_time, t(Key::Seconds, lang),
+                    t(Key::Total, lang), format_duration(total_time)
+                ));
+            } else {
+                text.push_str(&format!(
+                    "• {} - {} {}, {} {}, {}\n",
+                    exercise, sets, t(Key::Sets, lang), reps, t(Key::Reps, lang), format_duration(total_time)
+                ));
+            }
+        }
+    }
+
+    text
+}
+
+/// Build /help text: commands grouped by section with usage examples
+/// (examples are Russian-only, so English help lists descriptions alone)
+fn format_help(lang: Lang) -> String {
+    let mut text = format!("{}\n", t(Key::HelpTitle, lang));
+
+    for section in HelpSection::ALL {
+        text.push_str(&format!("\n{}\n", section.title(lang)));
+        for bot_command in Command::bot_commands() {
+            let Ok(cmd) = Command::parse(&bot_command.command, "") else {
+                continue;
+            };
+            let (cmd_section, example) = cmd.help_info();
+            if cmd_section != section {
+                continue;
+            }
+            match lang {
+                Lang::Ru => text.push_str(&format!(
+                    "{} — {}\n   {}\n",
+                    bot_command.command, bot_command.description, example
+                )),
+                Lang::En => text.push_str(&format!(
+                    "{} — {}\n",
+                    bot_command.command,
+                    command_description_en(&bot_command.command).unwrap_or(&bot_command.description)
+                )),
+            }
+        }
+    }
+
+    text
+}
+
+/// Base exercises enabled for a user (empty selection = full base program)
+fn enabled_base_exercises(enabled: &[String]) -> Vec<&'static Exercise> {
+    get_base_exercises()
+        .iter()
+        .filter(|ex| enabled.is_empty() || enabled.iter().any(|id| id == ex.id))
+        .collect()
+}
+
+/// Create inline keyboard with base exercises (`enabled` as in user settings)
+fn make_exercises_keyboard(enabled: &[String]) -> InlineKeyboardMarkup {
+    make_exercises_keyboard_with_targets(enabled, &HashMap::new())
 }
 
-/// Create inline keyboard with base exercises
-fn make_exercises_keyboard() -> InlineKeyboardMarkup {
-    let exercises = get_base_exercises();
+/// Telegram truncates long button labels; keep ours well within that
+const MAX_BUTTON_LABEL_CHARS: usize = 40;
+
+/// Button label for an exercise, with its target when known ("💪 пловец ~20")
+fn exercise_button_label(exercise: &Exercise, target: Option<i32>) -> String {
+    let suffix = match target {
+        Some(value) if exercise.is_timed => format!(" ~{}с", value),
+        Some(value) => format!(" ~{}", value),
+        None => String::new(),
+    };
+    let name_budget = MAX_BUTTON_LABEL_CHARS
+        .saturating_sub(exercise.category.emoji().chars().count() + 1 + suffix.chars().count());
+    let name: String = if exercise.name.chars().count() > name_budget {
+        let cut: String = exercise.name.chars().take(name_budget.saturating_sub(1)).collect();
+        format!("{}…", cut)
+    } else {
+        exercise.name.to_string()
+    };
+    format!("{} {}{}", exercise.category.emoji(), name, suffix)
+}
+
+/// Exercises keyboard with per-exercise targets (exercise id -> target value)
+fn make_exercises_keyboard_with_targets(
+    enabled: &[String],
+    targets: &HashMap<&str, i32>,
+) -> InlineKeyboardMarkup {
+    let exercises = enabled_base_exercises(enabled);
 
     let mut buttons: Vec<Vec<InlineKeyboardButton>> = exercises
         .chunks(2)
         .map(|chunk| {
             chunk.iter().map(|ex| {
-                let label = format!("{} {}", ex.category.emoji(), ex.name);
+                let label = exercise_button_label(ex, targets.get(ex.id).copied());
                 InlineKeyboardButton::callback(label, format!("ex:{}", ex.id))
             }).collect()
         })
@@ -150,9 +562,114 @@ fn make_commands_keyboard() -> InlineKeyboardMarkup {
     ])
 }
 
+/// Keyboard under a tip: another tip from a chosen category, then the usual commands
+fn make_tip_keyboard() -> InlineKeyboardMarkup {
+    let mut rows: Vec<Vec<InlineKeyboardButton>> = tips::TipCategory::all()
+        .chunks(2)
+        .map(|chunk| {
+            chunk.iter().map(|category| {
+                let label = format!("{} {}", category.emoji(), category.name());
+                InlineKeyboardButton::callback(label, format!("tipcat:{}", category.id()))
+            }).collect()
+        })
+        .collect();
+    rows.extend(make_commands_keyboard().inline_keyboard);
+    InlineKeyboardMarkup::new(rows)
+}
+
+/// Tip message as sent by /tip
+fn format_book_tip(tip: &tips::Tip) -> String {
+    format!("📖 Совет из книги\n\"You Are Your Own Gym\"\n\n{}", tips::format_tip(tip))
+}
+
+/// Keyboard under a saved set: another set of the same exercise or finish
+fn make_after_set_keyboard(exercise_id: &str, pulse_before: i32) -> InlineKeyboardMarkup {
+    let mut first_row = vec![
+        InlineKeyboardButton::callback("➕ Ещё подход", format!("more:{}:{}", exercise_id, pulse_before)),
+        InlineKeyboardButton::callback("✅ Готово", "set_done"),
+    ];
+    if let Some(exercise) = find_exercise(exercise_id) {
+        let (min_secs, _) = recommended_rest(exercise.category);
+        first_row.push(InlineKeyboardButton::callback(
+            format!("⏱ {}", format_duration(min_secs as i32)),
+            format!("rest:{}", min_secs),
+        ));
+    }
+    let mut rows = vec![first_row];
+    rows.extend(make_commands_keyboard().inline_keyboard);
+    InlineKeyboardMarkup::new(rows)
+}
+
+/// Rest window line for the post-set message, e.g. "Отдых: 1м 30с – 2м 0с"
+fn format_rest_window(exercise: &Exercise) -> String {
+    let (min_secs, max_secs) = recommended_rest(exercise.category);
+    format!(
+        "Отдых: {} – {}",
+        format_duration(min_secs as i32),
+        format_duration(max_secs as i32)
+    )
+}
+
+/// Question asked before saving a set
+const RPE_PROMPT: &str = "Насколько тяжело было (1-10)? «-» — пропустить";
+
+/// Button under /undo that takes the set back out of the trash
+fn make_restore_keyboard(training_id: i64) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback("↩️ Вернуть", format!("restore:{}", training_id)),
+    ]])
+}
+
+/// Note step buttons: skip in one tap or cancel the set
+fn make_skip_note_keyboard() -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback("⏭ Без заметки", "skip_note"),
+        InlineKeyboardButton::callback("❌ Отмена", "cancel"),
+    ]])
+}
+
+/// Note typed after a set: trimmed, empty or "-" means none
+fn parse_note(text: &str) -> Option<String> {
+    match text.trim() {
+        "" | "-" => None,
+        note => Some(note.to_string()),
+    }
+}
+
+/// One /today line, with the note underneath when present
+fn format_today_entry(t: &Training) -> String {
+    let mut line = format!("• {} - {}x{}\n", t.exercise, t.sets, t.reps);
+    if let Some(note) = &t.notes {
+        line.push_str(&format!("   📝 {}\n", note));
+    }
+    line
+}
+
+/// Single "❌ Отмена" button for dialogue prompts
+fn make_cancel_keyboard() -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback("❌ Отмена", "cancel")]])
+}
+
+/// Reply to /cancel for the dialogue state being dropped
+fn cancel_reply(state: Option<&State>) -> &'static str {
+    match state {
+        None | Some(State::Start) => "Нечего отменять",
+        Some(State::SetupTimezone { .. } | State::SetupBase { .. } | State::SetupReminders { .. }) => {
+            "❌ Настройка отменена, ничего не сохранено. Начать заново: /start"
+        }
+        Some(_) => "❌ Отменено, подход не сохранён",
+    }
+}
+
+/// Parse "more:<exercise_id>:<pulse_before>" callback data
+fn parse_more_set(data: &str) -> Option<(&str, i32)> {
+    let (exercise_id, pulse) = data.strip_prefix("more:")?.rsplit_once(':')?;
+    Some((exercise_id, pulse.parse().ok()?))
+}
+
 /// Format bonus recommendation for display
-fn format_bonus_recommendation(rec: &Recommendation, trainings: &[Training]) -> String {
-    let goal_info = GoalCalculator::calculate(trainings, rec.exercise.name)
+fn format_bonus_recommendation(rec: &Recommendation, trainings: &[Training], tz: FixedOffset) -> String {
+    let goal_info = GoalCalculator::calculate_in(trainings, rec.exercise.name, tz)
         .map(|g| format!("\n\n📊 {}", g.format_short()))
         .unwrap_or_default();
 
@@ -227,124 +744,1401 @@ fn make_extra_exercises_keyboard() -> InlineKeyboardMarkup {
     InlineKeyboardMarkup::new(buttons)
 }
 
-/// Background task that sends reminders every hour
-async fn reminder_task(bot: Bot, subscribers: Subscribers) {
-    info!("Reminder task started (interval: {} seconds)", REMINDER_INTERVAL_SECS);
+/// Timezones offered by the setup wizard: (UTC offset in hours, label)
+const SETUP_TIMEZONES: [(i32, &str); 9] = [
+    (2, "Калининград"),
+    (3, "Москва"),
+    (4, "Самара"),
+    (5, "Екатеринбург"),
+    (6, "Омск"),
+    (7, "Новосибирск"),
+    (8, "Иркутск"),
+    (9, "Якутск"),
+    (10, "Владивосток"),
+];
+
+/// Setup wizard step 1: timezone keyboard
+fn make_setup_tz_keyboard() -> InlineKeyboardMarkup {
+    let buttons: Vec<Vec<InlineKeyboardButton>> = SETUP_TIMEZONES
+        .chunks(3)
+        .map(|chunk| {
+            chunk.iter().map(|(hours, city)| {
+                InlineKeyboardButton::callback(
+                    format!("UTC+{} {}", hours, city),
+                    format!("setup_tz:{}", hours * 60),
+                )
+            }).collect()
+        })
+        .collect();
+    InlineKeyboardMarkup::new(buttons)
+}
 
-    loop {
-        tokio::time::sleep(Duration::from_secs(REMINDER_INTERVAL_SECS)).await;
+/// Setup wizard step 2: base exercise toggles
+fn make_setup_base_keyboard(selected: &[String]) -> InlineKeyboardMarkup {
+    let mut buttons: Vec<Vec<InlineKeyboardButton>> = get_base_exercises()
+        .iter()
+        .map(|ex| {
+            let mark = if selected.iter().any(|id| id == ex.id) { "✅" } else { "⬜" };
+            vec![InlineKeyboardButton::callback(
+                format!("{} {}", mark, ex.name),
+                format!("setup_base:{}", ex.id),
+            )]
+        })
+        .collect();
+    buttons.push(vec![
+        InlineKeyboardButton::callback("Готово ➡️", "setup_base_done")
+    ]);
+    InlineKeyboardMarkup::new(buttons)
+}
 
-        let subs = subscribers.lock().await;
-        if subs.is_empty() {
-            continue;
-        }
+/// Setup wizard step 3: reminder preference keyboard
+fn make_setup_reminders_keyboard() -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback("⏰ Раз в час", "setup_remind:on"),
+        InlineKeyboardButton::callback("🔕 Не надо", "setup_remind:off"),
+    ]])
+}
 
-        info!("Sending reminders to {} subscribers", subs.len());
-        let keyboard = make_exercises_keyboard();
+/// Add or remove an exercise id from the wizard selection
+fn toggle_base_exercise(selected: &mut Vec<String>, exercise_id: &str) {
+    if let Some(pos) = selected.iter().position(|id| id == exercise_id) {
+        selected.remove(pos);
+    } else {
+        selected.push(exercise_id.to_string());
+    }
+}
 
-        for chat_id in subs.iter() {
-            let result = bot
-                .send_message(*chat_id, "⏰ Время размяться!\n\nВыбери упражнение:")
-                .reply_markup(keyboard.clone())
-                .await;
+/// Parse a /tz offset in minutes east of UTC ("+180", "-300"), a multiple of 15
+fn parse_tz_offset(text: &str) -> Option<i32> {
+    let minutes: i32 = text.trim().parse().ok()?;
+    (TZ_OFFSET_RANGE_MINUTES.contains(&minutes) && minutes % 15 == 0).then_some(minutes)
+}
 
-            if let Err(e) = result {
-                error!("Failed to send reminder to {}: {}", chat_id, e);
-            }
-        }
+/// Format an offset in minutes as "UTC+3", "UTC+5:30" or "UTC-5"
+fn format_tz_offset(minutes: i32) -> String {
+    let sign = if minutes < 0 { '-' } else { '+' };
+    let (hours, mins) = (minutes.abs() / 60, minutes.abs() % 60);
+    if mins == 0 {
+        format!("UTC{}{}", sign, hours)
+    } else {
+        format!("UTC{}{}:{:02}", sign, hours, mins)
     }
 }
 
-/// User access check result
-enum AccessResult {
-    Allowed(User),
-    NewUser(User),
-    LimitReached,
+/// Format the settings confirmation shown at the end of the wizard
+fn format_settings_summary(settings: &UserSettings) -> String {
+    let base: Vec<_> = enabled_base_exercises(&settings.base_exercises)
+        .iter()
+        .map(|ex| ex.name)
+        .collect();
+    format!(
+        "✅ Настройка завершена!\n\n\
+        Часовой пояс: UTC{:+}\n\
+        База ({}): {}\n\
+        Напоминания: {}",
+        settings.tz_offset_minutes / 60,
+        base.len(),
+        base.join(", "),
+        if settings.reminders_enabled { "раз в час" } else { "выключены" }
+    )
 }
 
-/// Check user access and register if allowed
-fn check_user_access(
-    db: &Database,
-    chat_id: i64,
-    username: Option<&str>,
-    first_name: Option<&str>,
-    config: &BotConfig,
-) -> anyhow::Result<AccessResult> {
-    // Check if user already exists
-    if let Some(user) = db.get_user_by_chat_id(chat_id)? {
-        return Ok(AccessResult::Allowed(user));
-    }
+/// Start the setup wizard (owner's first run)
+async fn start_setup_wizard(bot: &Bot, chat_id: ChatId, dialogue: &MyDialogue, user_id: i64) -> HandlerResult {
+    dialogue.update(State::SetupTimezone { user_id }).await?;
+    bot.send_message(chat_id, "⚙️ Настройка (1/3)\n\nВыбери часовой пояс:")
+        .reply_markup(make_setup_tz_keyboard())
+        .await?;
+    Ok(())
+}
 
-    // Check user limit
-    let user_count = db.count_users()?;
-    if user_count >= config.max_users {
-        return Ok(AccessResult::LimitReached);
+/// User's UTC offset in seconds (Moscow until the wizard is completed)
+fn user_tz_offset_secs(db: &Database, user_id: i64) -> anyhow::Result<i32> {
+    Ok(db.user_tz_offset_minutes(user_id)? * 60)
+}
+
+/// User's enabled base exercises (empty = all)
+fn user_base_exercises(db: &Database, user_id: i64) -> anyhow::Result<Vec<String>> {
+    Ok(db.get_settings(user_id)?
+        .map(|s| s.base_exercises)
+        .unwrap_or_default())
+}
+
+/// Start of the current week (Monday 00:00 local) as UTC
+fn week_start_utc(now: DateTime<Utc>, tz_offset_secs: i32) -> DateTime<Utc> {
+    let tz = FixedOffset::east_opt(tz_offset_secs).unwrap_or_else(moscow_tz);
+    let (today_start, _) = day_bounds_utc(now, tz_offset_secs);
+    let days_since_monday = now.with_timezone(&tz).weekday().num_days_from_monday();
+    today_start - chrono::Duration::days(days_since_monday as i64)
+}
+
+/// Sessions (training days) so far this local week
+fn weekly_sessions_done(db: &Database, user_id: i64, now: DateTime<Utc>) -> anyhow::Result<usize> {
+    let tz_offset_secs = user_tz_offset_secs(db, user_id)?;
+    let tz = FixedOffset::east_opt(tz_offset_secs).unwrap_or_else(moscow_tz);
+    let week_start = week_start_utc(now, tz_offset_secs);
+    let trainings = db.get_trainings_in_range(user_id, week_start, week_start + chrono::Duration::days(7))?;
+    Ok(Analytics::new(trainings).sessions_this_week_at(now.with_timezone(&tz).date_naive(), tz))
+}
+
+/// Nudge toward the /weekly target, more insistent from Friday on; None once it's met
+fn format_weekly_nudge(done: usize, target: u8, weekday: Weekday) -> Option<String> {
+    let remaining = sessions_remaining(target, done);
+    if remaining == 0 {
+        return None;
     }
+    // Days left including today
+    let days_left = 7 - weekday.num_days_from_monday() as usize;
+    let text = if weekday.num_days_from_monday() < Weekday::Fri.num_days_from_monday() {
+        format!("🎯 Цель недели: {}/{}, осталось {} трен.", done, target, remaining)
+    } else if remaining > days_left {
+        format!(
+            "⚠️ Цель недели {}/{}: осталось {} трен., а дней — {}. Сделай сколько успеешь!",
+            done, target, remaining, days_left
+        )
+    } else {
+        format!(
+            "🔥 Неделя на исходе: до цели {} трен. за {} дн. ({}/{}) — не откладывай!",
+            remaining, days_left, done, target
+        )
+    };
+    Some(text)
+}
 
-    // Register new user (first user becomes owner)
-    let user = db.get_or_create_user(chat_id, username, first_name)?;
+/// /weekly reply: progress toward the target, or a congratulation once it's met
+fn format_weekly_status(done: usize, target: u8, weekday: Weekday) -> String {
+    format_weekly_nudge(done, target, weekday)
+        .unwrap_or_else(|| format!("✅ Цель недели выполнена: {}/{}", done, target))
+}
 
-    // Migrate existing trainings to owner if this is the first user
-    if user.is_owner {
-        let migrated = db.migrate_trainings_to_owner()?;
-        if migrated > 0 {
-            info!("Migrated {} trainings to owner", migrated);
-        }
+/// Trend indicator for a value compared to its previous value
+fn trend_arrow(current: i64, previous: i64) -> &'static str {
+    match current.cmp(&previous) {
+        std::cmp::Ordering::Greater => "📈",
+        std::cmp::Ordering::Less => "📉",
+        std::cmp::Ordering::Equal => "➡️",
     }
+}
 
-    Ok(AccessResult::NewUser(user))
+/// Format /compare: this week so far vs the same span of last week
+fn format_week_comparison(cmp: &PeriodComparison) -> String {
+    let (cur, prev) = (&cmp.current, &cmp.previous);
+    let line = |label: &str, c: i64, p: i64| format!("{}: {} {} (было {})\n", label, c, trend_arrow(c, p), p);
+
+    let mut text = String::from("📊 Неделя к неделе\n\n");
+    text.push_str(&line("Тренировок", cur.sessions as i64, prev.sessions as i64));
+    text.push_str(&line("Подходов", cur.sets as i64, prev.sets as i64));
+    text.push_str(&line("Объём", cur.volume as i64, prev.volume as i64));
+    text.push_str(&format!(
+        "Топ: {} (было {})",
+        cur.top_exercise.as_deref().unwrap_or("—"),
+        prev.top_exercise.as_deref().unwrap_or("—")
+    ));
+    text
 }
 
-/// Start the Telegram bot with reminders
-pub async fn run_bot(token: String, db_path: &str) -> anyhow::Result<()> {
-    let bot = Bot::new(token);
-    let db = Arc::new(Mutex::new(Database::open(db_path)?));
-    let config = Arc::new(BotConfig::default());
-    let subscribers: Subscribers = Arc::new(Mutex::new(HashSet::new()));
+/// User's local date today
+fn user_today(db: &Database, user_id: i64) -> anyhow::Result<chrono::NaiveDate> {
+    let tz = FixedOffset::east_opt(user_tz_offset_secs(db, user_id)?).unwrap_or_else(moscow_tz);
+    Ok(Utc::now().with_timezone(&tz).date_naive())
+}
 
-    info!("Bot started with max_users={}", config.max_users);
+/// Whether the user marked their local today as a rest day
+fn is_rest_day_today(db: &Database, user_id: i64) -> anyhow::Result<bool> {
+    Ok(db.get_rest_days(user_id)?.contains(&user_today(db, user_id)?))
+}
 
-    // Start reminder background task
-    let reminder_bot = bot.clone();
-    let reminder_subs = subscribers.clone();
-    tokio::spawn(async move {
-        reminder_task(reminder_bot, reminder_subs).await;
-    });
+/// Whether a local hour falls in the quiet window `start..end`.
+/// Windows may wrap midnight (22..7); `start == end` means no quiet hours.
+fn is_quiet(now_hour: u32, start: u32, end: u32) -> bool {
+    if start <= end {
+        (start..end).contains(&now_hour)
+    } else {
+        now_hour >= start || now_hour < end
+    }
+}
 
-    let handler = dptree::entry()
-        .enter_dialogue::<Update, InMemStorage<State>, State>()
-        .branch(
-            Update::filter_message()
-                .filter_command::<Command>()
-                .endpoint(handle_command),
-        )
-        .branch(
-            Update::filter_message()
-                .endpoint(handle_message),
-        )
-        .branch(
-            Update::filter_callback_query()
-                .endpoint(handle_callback),
-        );
+/// Whether a subscriber's quiet hours cover their current local hour
+fn in_quiet_hours(db: &Database, chat_id: ChatId, now: DateTime<Utc>) -> anyhow::Result<bool> {
+    let Some((start, end)) = db.get_quiet_hours(chat_id.0)? else {
+        return Ok(false);
+    };
+    let tz_offset_secs = match db.get_user_by_chat_id(chat_id.0)? {
+        Some(user) => user_tz_offset_secs(db, user.id)?,
+        None => MOSCOW_OFFSET_SECS,
+    };
+    let tz = FixedOffset::east_opt(tz_offset_secs).unwrap_or_else(moscow_tz);
+    Ok(is_quiet(now.with_timezone(&tz).hour(), start, end))
+}
 
-    Dispatcher::builder(bot, handler)
-        .dependencies(dptree::deps![InMemStorage::<State>::new(), db, config, subscribers])
-        .enable_ctrlc_handler()
-        .build()
-        .dispatch()
-        .await;
+/// Parse a /quiet argument: `off` clears, `22 7` sets start and end hours
+fn parse_quiet_hours(arg: &str) -> Option<Option<(u32, u32)>> {
+    let arg = arg.trim();
+    if arg.eq_ignore_ascii_case("off") || arg == "выкл" {
+        return Some(None);
+    }
+    let mut hours = arg.split_whitespace().map(|h| h.parse::<u32>().ok().filter(|h| *h < 24));
+    match (hours.next(), hours.next(), hours.next()) {
+        (Some(Some(start)), Some(Some(end)), None) if start != end => Some(Some((start, end))),
+        _ => None,
+    }
+}
 
-    Ok(())
+/// Parse a /dailytip argument: `off` stops, `on [hour]` or just `hour` sets the local hour
+fn parse_daily_tip(arg: &str) -> Option<Option<u32>> {
+    let mut words = arg.split_whitespace();
+    let (first, second) = (words.next()?, words.next());
+    if words.next().is_some() {
+        return None;
+    }
+    let hour = |h: &str| h.parse::<u32>().ok().filter(|h| *h < 24);
+    match (first.to_lowercase().as_str(), second) {
+        ("off" | "выкл", None) => Some(None),
+        ("on" | "вкл", None) => Some(Some(DEFAULT_DAILY_TIP_HOUR)),
+        ("on" | "вкл", Some(h)) => hour(h).map(Some),
+        (h, None) => hour(h).map(Some),
+        _ => None,
+    }
 }
 
-async fn handle_command(
-    bot: Bot,
-    msg: Message,
-    cmd: Command,
-    dialogue: MyDialogue,
+/// Local date a daily tip is due for, if one is due at `now`.
+/// Sent once the local hour is reached; one per local date.
+fn daily_tip_due(subscription: &DailyTipSubscription, now: DateTime<Utc>) -> Option<chrono::NaiveDate> {
+    let local = now.with_timezone(&user_tz(subscription.tz_offset_minutes));
+    let today = local.date_naive();
+    (local.hour() >= subscription.hour && subscription.last_sent != Some(today)).then_some(today)
+}
+
+/// Muscle groups reported sore today or yesterday (soreness expires after a day)
+fn active_sore_groups(db: &Database, user_id: i64) -> anyhow::Result<Vec<MuscleGroup>> {
+    let since = user_today(db, user_id)? - chrono::Duration::days(1);
+    Ok(db.get_sore_groups(user_id, since)?
+        .iter()
+        .filter_map(|name| MuscleGroup::from_ru(name))
+        .collect())
+}
+
+/// Parse a Russian weekday abbreviation ("пн".."вс")
+fn parse_weekday_ru(s: &str) -> Option<Weekday> {
+    WEEKDAYS_RU.iter()
+        .find(|(_, name)| *name == s.trim().to_lowercase())
+        .map(|(day, _)| *day)
+}
+
+/// Weekday abbreviations in display order
+const WEEKDAYS_RU: [(Weekday, &str); 7] = [
+    (Weekday::Mon, "пн"),
+    (Weekday::Tue, "вт"),
+    (Weekday::Wed, "ср"),
+    (Weekday::Thu, "чт"),
+    (Weekday::Fri, "пт"),
+    (Weekday::Sat, "сб"),
+    (Weekday::Sun, "вс"),
+];
+
+/// Format a weekday template for /template
+fn format_weekday_template(template: &WeekdayTemplate) -> String {
+    let mut text = String::from("📅 Шаблон недели\n\n");
+    for (day, name) in WEEKDAYS_RU {
+        let focus = template.focus(day)
+            .map(|c| format!("{} {}", c.emoji(), c.name_ru()))
+            .unwrap_or_else(|| "—".to_string());
+        text.push_str(&format!("{}: {}\n", name, focus));
+    }
+    let categories: Vec<_> = Category::all().iter().map(|c| c.name_ru()).collect();
+    text.push_str(&format!(
+        "\nИзменить: /template пн жим\nУбрать день: /template пн -\nКатегории: {}",
+        categories.join(", ")
+    ));
+    text
+}
+
+/// Apply "/template <день> <категория|->" to a template
+fn apply_template_arg(template: &mut WeekdayTemplate, arg: &str) -> Result<(), String> {
+    let mut parts = arg.split_whitespace();
+    let (Some(day), Some(category), None) = (parts.next(), parts.next(), parts.next()) else {
+        return Err("Формат: /template пн жим".to_string());
+    };
+    let day = parse_weekday_ru(day)
+        .ok_or_else(|| "День недели: пн, вт, ср, чт, пт, сб, вс".to_string())?;
+    let category = match category {
+        "-" => None,
+        name => Some(Category::from_ru(name)
+            .ok_or_else(|| format!("Неизвестная категория: {}", name))?),
+    };
+    template.set(day, category);
+    Ok(())
+}
+
+/// User's trainings and a recommender with soreness and weekday focus applied
+fn training_recommender(db: &Database, user: &User) -> anyhow::Result<(Vec<Training>, Recommender)> {
+    let user_id = user.id;
+    let trainings = db.get_trainings_for_user(user_id)?;
+    let tz = FixedOffset::east_opt(user_tz_offset_secs(db, user_id)?).unwrap_or_else(moscow_tz);
+    let focus = db.get_weekday_template(user_id)?
+        .focus(Utc::now().with_timezone(&tz).weekday());
+    let bonus_threshold = user.bonus_unlock_percent.unwrap_or(100);
+    let mut recommender = Recommender::new(trainings.clone())
+        .with_sore_groups(active_sore_groups(db, user_id)?)
+        .with_focus(focus)
+        .with_bonus_threshold(bonus_threshold as u32)
+        .with_max_difficulty(user.max_difficulty);
+    if let Some(equipment) = &user.equipment {
+        recommender = recommender.with_available_equipment(equipment);
+    }
+    Ok((trainings, recommender))
+}
+
+/// Appended to the ML section when progress has stalled;
+/// suggests the harder variant of the exercise when there is one
+fn format_deload_hint(harder: Option<&Exercise>) -> String {
+    let tip = tips::get_random_tip_by_category(tips::TipCategory::Recovery)
+        .map(|tip| format!("\n{} {}", tip.category.emoji(), tip.text))
+        .unwrap_or_default();
+    let progression = harder
+        .map(|ex| format!("\nИли переходи на вариант сложнее: {}.", ex.name))
+        .unwrap_or_default();
+    format!(
+        "\n\n📉 Плато: результат не растёт две недели.\n\
+        Попробуй разгрузку — лёгкую тренировку вполсилы, затем снова вверх.{}{}",
+        progression, tip
+    )
+}
+
+/// /records reply: rep records and hold-time records in separate sections
+fn format_records(records: &[(String, i32, bool)]) -> String {
+    if records.is_empty() {
+        return "Рекордов пока нет — начни с /train".to_string();
+    }
+    let section = |title: &str, timed: bool| {
+        let lines: Vec<String> = records
+            .iter()
+            .filter(|(_, _, is_timed)| *is_timed == timed)
+            .map(|(name, best, _)| {
+                let value = if timed { format_duration(*best) } else { format!("{} повт.", best) };
+                format!("• {} — {}", name, value)
+            })
+            .collect();
+        (!lines.is_empty()).then(|| format!("{}\n{}", title, lines.join("\n")))
+    };
+    let sections: Vec<String> = [section("💪 Повторы:", false), section("⏱ Время:", true)]
+        .into_iter()
+        .flatten()
+        .collect();
+    format!("🏆 Личные рекорды\n\n{}", sections.join("\n\n"))
+}
+
+/// Weeks shown by /balance_trend
+const BALANCE_TREND_WEEKS: usize = 8;
+
+/// /balance_trend reply: one ASCII bar per week, oldest first
+fn format_balance_trend(weeks: &[(chrono::NaiveDate, f32)]) -> String {
+    if weeks.is_empty() {
+        return "Нет тренировок за последние недели — начни с /train".to_string();
+    }
+    let lines: Vec<String> = weeks
+        .iter()
+        .map(|(monday, score)| {
+            let filled = (score / 10.0).round().clamp(0.0, 10.0) as usize;
+            format!(
+                "{} [{}{}] {:.0}%",
+                monday.format("%d.%m"),
+                "#".repeat(filled),
+                ".".repeat(10 - filled),
+                score
+            )
+        })
+        .collect();
+    format!("⚖️ Баланс по неделям\n\n{}", lines.join("\n"))
+}
+
+/// /muscle reply: weekly volume bars scaled to the best week, with the
+/// direction of the last week against the one before
+fn format_muscle_trend(group: MuscleGroup, weeks: &[(chrono::NaiveDate, i32)]) -> String {
+    let max = weeks.iter().map(|(_, reps)| *reps).max().unwrap_or(0);
+    if max == 0 {
+        return format!("За последние недели группа «{}» не нагружалась", group.name_ru());
+    }
+    let lines: Vec<String> = weeks
+        .iter()
+        .map(|(monday, reps)| {
+            let filled = (*reps as f32 / max as f32 * 10.0).round() as usize;
+            format!(
+                "{} [{}{}] {}",
+                monday.format("%d.%m"),
+                "#".repeat(filled),
+                ".".repeat(10 - filled),
+                reps
+            )
+        })
+        .collect();
+    let trend = match weeks {
+        [.., (_, previous), (_, last)] if last > previous => " 📈",
+        [.., (_, previous), (_, last)] if last < previous => " 📉",
+        _ => "",
+    };
+    format!("💪 {} по неделям{}\n\n{}", group.name_ru(), trend, lines.join("\n"))
+}
+
+/// /streak reply: current and longest run of training days
+fn format_streaks(current: u32, longest: u32) -> String {
+    let mut text = format!(
+        "🔥 Серия: {} {}\n🏆 Рекорд: {} {}",
+        current, days_word(current), longest, days_word(longest)
+    );
+    if current == 0 {
+        text.push_str("\n\nНачни новую серию: /train");
+    } else if current >= longest {
+        text.push_str("\n\nЭто твоя лучшая серия!");
+    }
+    text
+}
+
+/// Russian plural for days: 1 день, 2 дня, 5 дней
+fn days_word(n: u32) -> &'static str {
+    match (n % 10, n % 100) {
+        (1, r) if r != 11 => "день",
+        (2..=4, r) if !(12..=14).contains(&r) => "дня",
+        _ => "дней",
+    }
+}
+
+/// Sets with both pulses shown by /pulse
+const PULSE_SPARKLINE_POINTS: usize = 20;
+
+/// One block character per value, scaled between the minimum (▁) and maximum (█);
+/// a flat series sits mid-height
+fn sparkline(values: &[i32]) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let (Some(min), Some(max)) = (values.iter().min(), values.iter().max()) else {
+        return String::new();
+    };
+    let range = i64::from(*max) - i64::from(*min);
+    values
+        .iter()
+        .map(|v| {
+            if range == 0 {
+                return BLOCKS[BLOCKS.len() / 2 - 1];
+            }
+            let top = (BLOCKS.len() - 1) as i64;
+            let level = ((i64::from(*v) - i64::from(*min)) * top + range / 2) / range;
+            BLOCKS[level as usize]
+        })
+        .collect()
+}
+
+/// /pulse reply: sparkline of pulse rises per set, oldest first
+fn format_pulse_sparkline(exercise: &str, deltas: &[i32]) -> String {
+    let [first, .., last] = deltas else {
+        return format!("Мало данных для «{}»: нужно хотя бы 2 подхода с пульсом до и после", exercise);
+    };
+    let verdict = match last.cmp(first) {
+        std::cmp::Ordering::Less => " — сердце работает легче 💪",
+        std::cmp::Ordering::Greater => " — пульс растёт сильнее",
+        std::cmp::Ordering::Equal => "",
+    };
+    format!(
+        "🫀 {}: прирост пульса за {} подх.\n{}\n{} → {} уд/мин{}",
+        exercise, deltas.len(), sparkline(deltas), first, last, verdict
+    )
+}
+
+/// Caption for the /graph picture (the image itself has no text)
+fn format_graph_caption(exercise: &str, predictor: &ProgressPredictor) -> String {
+    let points = predictor.points();
+    let (first, last) = match (points.first(), points.last()) {
+        (Some(first), Some(last)) => (*first, *last),
+        _ => return format!("📈 {}", exercise),
+    };
+    format!(
+        "📈 {}: {} → {} повт. за {} дн.\n🔵 подходы, 🔴 тренд {:+.2} повт./день",
+        exercise, first.1, last.1, (last.0 - first.0) as i64, predictor.daily_progress()
+    )
+}
+
+/// Match a /stats argument to a logged exercise name (case-insensitive, id or name)
+fn resolve_logged_exercise(arg: &str, trainings: &[Training]) -> Option<String> {
+    let wanted = arg.trim().to_lowercase();
+    let catalog_name = find_exercise(&wanted).map(|ex| ex.name.to_lowercase());
+    trainings
+        .iter()
+        .map(|t| &t.exercise)
+        .find(|name| {
+            let name = name.to_lowercase();
+            name == wanted || catalog_name.as_deref() == Some(name.as_str())
+        })
+        .cloned()
+}
+
+/// Parse a /gear argument: "всё" lifts the restriction, "нет" means no gear,
+/// otherwise a list of gear names
+fn parse_gear(arg: &str) -> Result<Option<Vec<Equipment>>, String> {
+    match arg.trim().to_lowercase().replace('ё', "е").as_str() {
+        "все" => Ok(None),
+        "нет" => Ok(Some(Vec::new())),
+        list => list
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|w| !w.is_empty())
+            .map(|w| Equipment::from_ru(w).ok_or_else(|| format!("Неизвестный инвентарь: {}", w)))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Some),
+    }
+}
+
+/// Human-readable gear list for /gear
+fn format_gear(equipment: Option<&[Equipment]>) -> String {
+    match equipment {
+        None => "весь".to_string(),
+        Some([]) => "нет".to_string(),
+        Some(list) => list.iter().map(|e| e.name_ru()).collect::<Vec<_>>().join(", "),
+    }
+}
+
+/// Exercises of a past day in the order first done, each with the best
+/// result that day as target (seconds for timed exercises)
+fn repeat_plan(trainings: &[Training]) -> Vec<(&'static Exercise, i32)> {
+    let mut sorted: Vec<_> = trainings.iter().collect();
+    sorted.sort_by_key(|t| t.date);
+
+    let mut plan: Vec<(&'static Exercise, i32)> = Vec::new();
+    for t in sorted {
+        let Some(exercise) = find_exercise_by_name(&t.exercise) else {
+            continue;
+        };
+        let result = if exercise.is_timed { t.duration_secs.unwrap_or(0) } else { t.reps };
+        match plan.iter_mut().find(|(ex, _)| ex.id == exercise.id) {
+            Some((_, target)) => *target = (*target).max(result),
+            None => plan.push((exercise, result)),
+        }
+    }
+    plan
+}
+
+/// /repeat message for a past day's plan
+fn format_repeat_plan(date: chrono::NaiveDate, plan: &[(&Exercise, i32)]) -> String {
+    let mut text = format!("🔁 Повтор тренировки {}\n\n", date);
+    for (i, (exercise, target)) in plan.iter().enumerate() {
+        let unit = if exercise.is_timed { "с" } else { "" };
+        text.push_str(&format!("{}. {} — {}{}\n", i + 1, exercise.name, target, unit));
+    }
+    text.push_str("\nЖми по порядку 👇");
+    text
+}
+
+/// Today's plan as a tick-off list for /checklist
+fn format_checklist(plan: &[PlanItem]) -> String {
+    if plan.is_empty() {
+        return "📋 На сегодня в плане ничего нет".to_string();
+    }
+    let done = plan.iter().filter(|item| item.done).count();
+    let mut text = String::from("📋 План на сегодня\n\n");
+    for item in plan {
+        let mark = if item.done { "✅" } else { "⬜" };
+        text.push_str(&format!("{} {}\n", mark, item.exercise.name));
+    }
+    text.push_str(&format!("\nГотово: {}/{}", done, plan.len()));
+    if done == plan.len() {
+        text.push_str(" 🎉");
+    }
+    text
+}
+
+/// Parse a /weight argument in kg, accepting a decimal comma ("72,5")
+fn parse_weight(arg: &str) -> Option<f64> {
+    arg.trim()
+        .trim_end_matches("кг")
+        .trim()
+        .replace(',', ".")
+        .parse::<f64>()
+        .ok()
+        .filter(|kg| BODYWEIGHT_RANGE_KG.contains(kg))
+}
+
+/// Parse an RPE answer: `Some(None)` for "-" (skip), `Some(Some(1..=10))` for a rating
+fn parse_rpe(text: &str) -> Option<Option<i32>> {
+    match text.trim() {
+        "-" => Some(None),
+        text => text.parse().ok().filter(|r| (1..=10).contains(r)).map(Some),
+    }
+}
+
+/// Split a /setgoal argument into exercise and target text.
+/// The target may be split from its unit: "планка 3 мин".
+fn parse_set_goal(arg: &str) -> Option<(String, String)> {
+    let words: Vec<&str> = arg.split_whitespace().collect();
+    let has_digit = |w: &str| w.chars().any(|c| c.is_ascii_digit());
+    let value_words = match words.as_slice() {
+        [.., last] if has_digit(last) => 1,
+        [.., value, _unit] if has_digit(value) => 2,
+        _ => return None,
+    };
+    if words.len() <= value_words {
+        return None;
+    }
+    let (exercise, value) = words.split_at(words.len() - value_words);
+    Some((exercise.join(" "), value.concat()))
+}
+
+/// Parse a goal target: reps, or for timed exercises seconds ("90", "90с")
+/// or minutes ("3м", "3мин")
+fn parse_goal_value(text: &str, is_timed: bool) -> Option<i32> {
+    let text = text.trim().to_lowercase();
+    let digits_end = text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+    let value: i32 = text[..digits_end].parse().ok().filter(|v| *v > 0)?;
+    match (text[digits_end..].trim(), is_timed) {
+        ("", _) => Some(value),
+        ("с" | "сек" | "s" | "sec", true) => Some(value),
+        ("м" | "мин" | "m" | "min", true) => value.checked_mul(60),
+        _ => None,
+    }
+}
+
+/// Catalog exercise a goal is for: id, name or alias; errors explain the miss
+fn resolve_goal_exercise(query: &str) -> Result<&'static Exercise, String> {
+    if let Some(exercise) = find_exercise(query.trim()) {
+        return Ok(exercise);
+    }
+    match find_exercise_by_alias(query).as_slice() {
+        [exercise] => Ok(*exercise),
+        [] => Err(format!("Не знаю упражнение «{}».", query.trim())),
+        several => Err(format!(
+            "Уточни упражнение: {}",
+            several.iter().map(|ex| ex.name).collect::<Vec<_>>().join(", ")
+        )),
+    }
+}
+
+/// Goal target as shown to the user
+fn format_goal_value(value: i32, is_timed: bool) -> String {
+    if is_timed {
+        format_duration(value)
+    } else {
+        format!("{} повт.", value)
+    }
+}
+
+/// Best result so far for an exercise: max reps, or max seconds if timed
+fn best_value(trainings: &[Training], exercise: &str, is_timed: bool) -> i32 {
+    trainings
+        .iter()
+        .filter(|t| t.exercise == exercise)
+        .filter_map(|t| if is_timed { t.duration_secs } else { Some(t.reps) })
+        .max()
+        .unwrap_or(0)
+}
+
+/// /goals reply: open goals with progress, then achieved ones
+fn format_goals(goals: &[Goal], trainings: &[Training], tz: FixedOffset) -> String {
+    if goals.is_empty() {
+        return "Целей пока нет. Пример: /setgoal планка 3м".to_string();
+    }
+    let (achieved, open): (Vec<_>, Vec<_>) = goals.iter().partition(|g| g.achieved_at.is_some());
+
+    let mut text = String::from("🎯 Цели");
+    if open.is_empty() {
+        text.push_str("\nВсе цели достигнуты! Новая: /setgoal");
+    }
+    for goal in &open {
+        let best = best_value(trainings, &goal.exercise, goal.is_timed);
+        text.push_str(&format!(
+            "\n#{} {} — {} (лучшее: {}, {}%)",
+            goal.id,
+            goal.exercise,
+            format_goal_value(goal.target_value, goal.is_timed),
+            format_goal_value(best, goal.is_timed),
+            (best * 100 / goal.target_value).min(99)
+        ));
+    }
+    if !achieved.is_empty() {
+        text.push_str("\n\n✅ Достигнуто");
+        for goal in &achieved {
+            let date = goal.achieved_at.map(|d| d.with_timezone(&tz).format("%d.%m").to_string()).unwrap_or_default();
+            text.push_str(&format!(
+                "\n#{} {} — {} ({})",
+                goal.id, goal.exercise, format_goal_value(goal.target_value, goal.is_timed), date
+            ));
+        }
+    }
+    text.push_str("\n\nУдалить: /delgoal <номер>");
+    text
+}
+
+/// Congratulation for goals reached by the set just logged
+fn format_goals_achieved(goals: &[Goal]) -> String {
+    let lines: Vec<_> = goals
+        .iter()
+        .map(|g| format!("• {} — {}", g.exercise, format_goal_value(g.target_value, g.is_timed)))
+        .collect();
+    format!("🎯 Цель достигнута! Поздравляю!\n{}", lines.join("\n"))
+}
+
+/// ETA line for /goal: days ahead and the projected local date
+fn format_goal_eta(exercise: &str, target: i32, days: i32, today: chrono::NaiveDate) -> String {
+    if days == 0 {
+        return format!("🎯 {}: тренд уже на {} повт. — пробуй!", exercise, target);
+    }
+    match today.checked_add_signed(chrono::Duration::days(days as i64)) {
+        Some(date) => format!(
+            "🎯 {}: {} повт. — через ~{} {} (к {})",
+            exercise, target, days, days_word(days as u32), date.format("%d.%m.%Y")
+        ),
+        None => format!("🎯 {}: {} повт. — не скоро", exercise, target),
+    }
+}
+
+/// Split a /goal argument into exercise name and target reps within `REPS_RANGE`
+fn parse_goal_reps(arg: &str) -> Option<(&str, i32)> {
+    let (exercise, target) = arg.trim().rsplit_once(char::is_whitespace)?;
+    let target = target.parse().ok().and_then(|t| validate_reps(t).ok())?;
+    let exercise = exercise.trim();
+    (!exercise.is_empty()).then_some((exercise, target))
+}
+
+/// Pulse trend line for /stats <exercise>; a falling pulse is highlighted
+fn format_pulse_trend(slope_per_day: Option<f64>) -> String {
+    let Some(slope) = slope_per_day else {
+        return "❤️ Пульс после: мало замеров для тренда".to_string();
+    };
+    let per_week = slope * 7.0;
+    if per_week.abs() < 0.5 {
+        "❤️ Пульс после: стабилен".to_string()
+    } else if per_week < 0.0 {
+        format!("❤️ Пульс после: ↓ {:.1} уд/мин в неделю — выносливость растёт 💪", -per_week)
+    } else {
+        format!("❤️ Пульс после: ↑ {:.1} уд/мин в неделю", per_week)
+    }
+}
+
+/// Conditioning line for /stats <exercise> from the change in pulse rise per set
+fn format_pulse_recovery(change: Option<f64>) -> String {
+    let Some(change) = change else {
+        return "🫀 Кондиции: нужно 4+ подхода с пульсом до и после".to_string();
+    };
+    if change.abs() < 2.0 {
+        "🫀 Кондиции: прирост пульса за подход не меняется".to_string()
+    } else if change < 0.0 {
+        format!("🫀 Кондиции: пульс за подход растёт на {:.0} уд/мин меньше — сердце справляется легче 💪", -change)
+    } else {
+        format!("🫀 Кондиции: пульс за подход растёт на {:.0} уд/мин больше — возможно, усталость", change)
+    }
+}
+
+/// Load-management warning for an acute:chronic workload ratio outside 0.8-1.5
+fn acwr_warning(acwr: Option<f64>) -> Option<String> {
+    match acwr? {
+        ratio if ratio > 1.5 => Some(format!("⚠️ Нагрузка ×{:.1} к обычной: резкий рост нагрузки — риск травмы", ratio)),
+        ratio if ratio < 0.8 => Some(format!("⚠️ Нагрузка ×{:.1} к обычной: спад — форма может уходить", ratio)),
+        _ => None,
+    }
+}
+
+/// Recovery warning when the resting pulse keeps climbing day to day
+fn resting_pulse_warning(trend: Option<f64>) -> Option<String> {
+    let trend = trend.filter(|slope| *slope > RESTING_PULSE_DRIFT_BPM_PER_DAY)?;
+    Some(format!(
+        "⚠️ Пульс покоя растёт на {:.1} уд/мин в день — признак усталости. \
+        Сбавь нагрузку и загляни в советы: /tip → 😴 Восстановление",
+        trend
+    ))
+}
+
+/// One-line stats for /s
+fn format_compact_stats(today_sets: usize, week_sets: usize, streak: u32, balance: f32) -> String {
+    format!(
+        "Сегодня: {} подх. / Неделя: {} / Стрик: {} {} / Баланс: {:.0}%",
+        today_sets, week_sets, streak, days_word(streak), balance
+    )
+}
+
+/// Trainings of the latest session: chain back from the newest training
+/// while gaps between consecutive sets stay under `idle_secs`
+fn latest_session(trainings: &[Training], idle_secs: i64) -> Vec<&Training> {
+    let mut sorted: Vec<_> = trainings.iter().collect();
+    sorted.sort_by_key(|t| std::cmp::Reverse(t.date));
+
+    let mut session = Vec::new();
+    for t in sorted {
+        if let Some(prev) = session.last().map(|p: &&Training| p.date)
+            && (prev - t.date).num_seconds() >= idle_secs {
+                break;
+            }
+        session.push(t);
+    }
+    session
+}
+
+/// Format end-of-session summary (sets, time, muscles hit)
+fn format_session_summary(session: &[&Training]) -> String {
+    let total_time: i32 = session.iter().filter_map(|t| t.duration_secs).sum();
+
+    // Sets per exercise, in the order they were done
+    let mut exercises: Vec<(&str, usize)> = Vec::new();
+    for t in session.iter().rev() {
+        match exercises.iter_mut().find(|(name, _)| *name == t.exercise) {
+            Some(entry) => entry.1 += 1,
+            None => exercises.push((&t.exercise, 1)),
+        }
+    }
+
+    let mut muscles: Vec<_> = session.iter()
+        .flat_map(|t| t.muscle_groups().iter().copied())
+        .collect();
+    muscles.sort();
+    muscles.dedup();
+
+    let mut text = format!(
+        "🏁 Сессия завершена\n\n\
+        Подходов: {}\n\
+        Время: {}\n",
+        session.len(),
+        format_duration(total_time)
+    );
+    for (name, sets) in exercises {
+        text.push_str(&format!("• {} - {} подх.\n", name, sets));
+    }
+    if !muscles.is_empty() {
+        let names: Vec<_> = muscles.iter().map(|m| m.name_ru()).collect();
+        text.push_str(&format!("\n💪 Мышцы: {}", names.join(", ")));
+    }
+    text
+}
+
+/// (Re)start the inactivity timer for a chat. When it fires, the bot sends
+/// a summary of the latest session and drops a half-finished set; other
+/// dialogues (setup wizard, message to the owner) are left alone.
+async fn touch_session(
+    bot: Bot,
+    dialogue: MyDialogue,
+    db: Arc<Mutex<Database>>,
+    runtime: Runtime,
+    user_id: i64,
+) {
+    let chat_id = dialogue.chat_id();
+    let task = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(SESSION_IDLE_SECS as u64)).await;
+
+        let trainings = {
+            let db = db.lock().await;
+            match db.get_trainings_for_user(user_id) {
+                Ok(t) => t,
+                Err(e) => {
+                    error!("Failed to load session for {}: {}", chat_id, e);
+                    return;
+                }
+            }
+        };
+
+        // Only summarize sets logged within the idle window before now
+        let session = latest_session(&trainings, SESSION_IDLE_SECS);
+        let recent = session.first()
+            .is_some_and(|t| (Utc::now() - t.date).num_seconds() <= SESSION_IDLE_SECS + 60);
+        if recent {
+            let text = format_session_summary(&session);
+            if let Err(e) = bot.send_message(chat_id, text)
+                .reply_markup(make_commands_keyboard())
+                .await
+            {
+                error!("Failed to send session summary to {}: {}", chat_id, e);
+            }
+        }
+
+        match dialogue.get().await {
+            Ok(Some(state)) if state.is_logging() => {
+                if let Err(e) = dialogue.reset().await {
+                    error!("Failed to reset dialogue for {}: {}", chat_id, e);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to read dialogue for {}: {}", chat_id, e),
+        }
+    });
+
+    let mut runtime = runtime.lock().await;
+    if let Some(previous) = runtime.session_timers.insert(chat_id, task) {
+        previous.abort();
+    }
+}
+
+/// Parse /rest seconds; no argument means `DEFAULT_REST_SECS`
+fn parse_rest_secs(arg: &str) -> Option<u64> {
+    match arg.trim() {
+        "" => Some(DEFAULT_REST_SECS),
+        arg => arg.parse().ok().filter(|secs| (1..=MAX_REST_SECS).contains(secs)),
+    }
+}
+
+/// Pick a tip not shown in this chat recently and remember it
+async fn next_tip(chat_id: ChatId, runtime: &Runtime) -> &'static tips::Tip {
+    let mut runtime = runtime.lock().await;
+    let recent = runtime.recent_tips.entry(chat_id).or_default();
+    let (tip, index) = tips::get_random_tip_excluding(recent.make_contiguous());
+    recent.push_back(index);
+    if recent.len() > RECENT_TIPS {
+        recent.pop_front();
+    }
+    tip
+}
+
+/// Start a one-shot rest timer for the chat, replacing a running one.
+/// Returns true if a previous timer was still running.
+async fn start_rest_timer(bot: Bot, chat_id: ChatId, runtime: Runtime, secs: u64) -> bool {
+    let task = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(secs)).await;
+        if let Err(e) = bot.send_message(chat_id, "⏰ Отдых окончен!").await {
+            error!("Failed to send rest alarm to {}: {}", chat_id, e);
+        }
+    });
+
+    let mut runtime = runtime.lock().await;
+    match runtime.rest_timers.insert(chat_id, task) {
+        Some(previous) => {
+            let running = !previous.is_finished();
+            previous.abort();
+            running
+        }
+        None => false,
+    }
+}
+
+/// Next due time for a fresh (or just reminded) subscriber
+fn next_reminder_due(now: DateTime<Utc>) -> DateTime<Utc> {
+    now + chrono::Duration::seconds(REMINDER_INTERVAL_SECS as i64)
+}
+
+/// Subscribers whose next reminder is due at `now`
+fn due_subscribers(subs: &HashMap<ChatId, DateTime<Utc>>, now: DateTime<Utc>) -> Vec<ChatId> {
+    subs.iter()
+        .filter(|(_, due)| **due <= now)
+        .map(|(chat_id, _)| *chat_id)
+        .collect()
+}
+
+/// Reminder keyboard: the user's base exercises with fatigue-aware targets, plus snooze
+fn make_reminder_keyboard(enabled: &[String], targets: &HashMap<&str, i32>) -> InlineKeyboardMarkup {
+    make_exercises_keyboard_with_targets(enabled, targets).append_row(vec![
+        InlineKeyboardButton::callback(
+            format!("😴 Через {} мин", SNOOZE_MINUTES),
+            format!("snooze:{}", SNOOZE_MINUTES),
+        )
+    ])
+}
+
+/// Build a chat's reminder keyboard from its user's history
+fn reminder_keyboard_for(db: &Database, chat_id: ChatId) -> anyhow::Result<InlineKeyboardMarkup> {
+    let Some(user) = db.get_user_by_chat_id(chat_id.0)? else {
+        return Ok(make_reminder_keyboard(&[], &HashMap::new()));
+    };
+    let enabled = user_base_exercises(db, user.id)?;
+    let trainings = db.get_trainings_for_user(user.id)?;
+    let targets: HashMap<&str, i32> = enabled_base_exercises(&enabled)
+        .iter()
+        .filter_map(|ex| {
+            GoalCalculator::calculate_in(&trainings, ex.name, user_tz(user.tz_offset_minutes))
+                .map(|g| (ex.id, g.target_value))
+        })
+        .collect();
+    Ok(make_reminder_keyboard(&enabled, &targets))
+}
+
+/// Background task that sends each subscriber a reminder when it's due
+async fn reminder_task(bot: Bot, db: Arc<Mutex<Database>>, subscribers: Subscribers) {
+    info!("Reminder task started (interval: {} seconds)", REMINDER_INTERVAL_SECS);
+
+    loop {
+        tokio::time::sleep(Duration::from_secs(REMINDER_TICK_SECS)).await;
+
+        let now = Utc::now();
+        let due = {
+            let mut subs = subscribers.lock().await;
+            let due = due_subscribers(&subs, now);
+            for chat_id in &due {
+                subs.insert(*chat_id, next_reminder_due(now));
+            }
+            due
+        };
+        if due.is_empty() {
+            continue;
+        }
+
+        info!("Sending reminders to {} subscribers", due.len());
+
+        for chat_id in due {
+            // No nudges on a planned rest day or during quiet hours
+            let silenced = {
+                let db = db.lock().await;
+                let resting = match db.get_user_by_chat_id(chat_id.0) {
+                    Ok(Some(user)) => is_rest_day_today(&db, user.id).unwrap_or(false),
+                    _ => false,
+                };
+                resting || in_quiet_hours(&db, chat_id, now).unwrap_or(false)
+            };
+            if silenced {
+                continue;
+            }
+
+            let keyboard = match reminder_keyboard_for(&*db.lock().await, chat_id) {
+                Ok(keyboard) => keyboard,
+                Err(e) => {
+                    error!("Failed to build reminder targets for {}: {}", chat_id, e);
+                    make_reminder_keyboard(&[], &HashMap::new())
+                }
+            };
+            let nudge = match weekly_nudge_for(&*db.lock().await, chat_id, now) {
+                Ok(nudge) => nudge,
+                Err(e) => {
+                    error!("Failed to check weekly target for {}: {}", chat_id, e);
+                    None
+                }
+            };
+            let text = match nudge {
+                Some(nudge) => format!("⏰ Время размяться!\n\n{}\n\nВыбери упражнение:", nudge),
+                None => "⏰ Время размяться!\n\nВыбери упражнение:".to_string(),
+            };
+            let result = bot
+                .send_message(chat_id, text)
+                .reply_markup(keyboard)
+                .await;
+
+            if let Err(e) = result {
+                error!("Failed to send reminder to {}: {}", chat_id, e);
+            }
+        }
+    }
+}
+
+/// Weekly-target line for a reminder, if the chat's user set /weekly and is behind
+fn weekly_nudge_for(db: &Database, chat_id: ChatId, now: DateTime<Utc>) -> anyhow::Result<Option<String>> {
+    let Some(user) = db.get_user_by_chat_id(chat_id.0)? else {
+        return Ok(None);
+    };
+    let Some(target) = user.weekly_target else {
+        return Ok(None);
+    };
+    let weekday = now.with_timezone(&user_tz(user.tz_offset_minutes)).weekday();
+    Ok(format_weekly_nudge(weekly_sessions_done(db, user.id, now)?, target, weekday))
+}
+
+/// Hourly removal of trainings deleted more than `TRASH_RETENTION_HOURS` ago
+async fn trash_purge_task(db: Arc<Mutex<Database>>) {
+    loop {
+        let older_than = Utc::now() - chrono::Duration::hours(TRASH_RETENTION_HOURS);
+        match db.lock().await.purge_trash(older_than) {
+            Ok(0) => {}
+            Ok(purged) => info!("Purged {} trashed trainings", purged),
+            Err(e) => error!("Failed to purge trash: {}", e),
+        }
+        tokio::time::sleep(Duration::from_secs(REMINDER_INTERVAL_SECS)).await;
+    }
+}
+
+/// Sends the morning tip to /dailytip subscribers
+async fn daily_tip_task(bot: Bot, db: Arc<Mutex<Database>>) {
+    info!("Daily tip task started");
+
+    loop {
+        tokio::time::sleep(Duration::from_secs(REMINDER_TICK_SECS)).await;
+
+        let now = Utc::now();
+        let subscriptions = match db.lock().await.get_daily_tip_subscriptions() {
+            Ok(subscriptions) => subscriptions,
+            Err(e) => {
+                error!("Failed to load daily tip subscriptions: {}", e);
+                continue;
+            }
+        };
+
+        for subscription in subscriptions {
+            let Some(date) = daily_tip_due(&subscription, now) else {
+                continue;
+            };
+            let tip = tips::get_daily_tip(subscription.last_category);
+            let chat_id = ChatId(subscription.chat_id);
+            let text = format!("📖 Совет дня\n\n{}", tips::format_tip(tip));
+            if let Err(e) = bot.send_message(chat_id, text).await {
+                error!("Failed to send daily tip to {}: {}", chat_id, e);
+                continue;
+            }
+            if let Err(e) = db.lock().await.mark_daily_tip_sent(subscription.chat_id, date, tip.category) {
+                error!("Failed to record daily tip for {}: {}", chat_id, e);
+            }
+        }
+    }
+}
+
+/// Turn off every background notification for a chat.
+/// Returns labels of what was actually enabled and is now off.
+async fn stop_all_notifications(
+    chat_id: ChatId,
+    user_id: i64,
+    db: &Mutex<Database>,
+    subscribers: &Subscribers,
+    runtime: &Runtime,
+) -> anyhow::Result<Vec<&'static str>> {
+    let mut stopped = Vec::new();
+
+    // Hourly reminders (including a pending snooze) and the stored preference
+    let was_subscribed = subscribers.lock().await.remove(&chat_id).is_some();
+    let had_preference = {
+        let db = db.lock().await;
+        db.remove_subscriber(chat_id.0)?;
+        match db.get_settings(user_id)? {
+            Some(settings) if settings.reminders_enabled => {
+                db.save_settings(&UserSettings { reminders_enabled: false, ..settings })?;
+                true
+            }
+            _ => false,
+        }
+    };
+    if was_subscribed || had_preference {
+        stopped.push("напоминания раз в час");
+    }
+
+    if db.lock().await.set_daily_tip(chat_id.0, None)? {
+        stopped.push("совет дня");
+    }
+
+    // Pending end-of-session summary
+    if let Some(timer) = runtime.lock().await.session_timers.remove(&chat_id) {
+        if !timer.is_finished() {
+            stopped.push("итог тренировки");
+        }
+        timer.abort();
+    }
+
+    Ok(stopped)
+}
+
+/// User access check result
+enum AccessResult {
+    Allowed(User),
+    NewUser(User),
+    LimitReached,
+}
+
+/// Check user access and register if allowed
+fn check_user_access(
+    db: &Database,
+    chat_id: i64,
+    username: Option<&str>,
+    first_name: Option<&str>,
+    config: &BotConfig,
+) -> anyhow::Result<AccessResult> {
+    // Check if user already exists
+    if let Some(user) = db.get_user_by_chat_id(chat_id)? {
+        return Ok(AccessResult::Allowed(user));
+    }
+
+    // Check user limit
+    let user_count = db.count_users()?;
+    if user_count >= config.max_users {
+        return Ok(AccessResult::LimitReached);
+    }
+
+    // Register new user (first user becomes owner)
+    let user = db.get_or_create_user(chat_id, username, first_name)?;
+
+    // Migrate existing trainings to owner if this is the first user
+    if user.is_owner {
+        let migrated = db.migrate_trainings_to_owner()?;
+        if migrated > 0 {
+            info!("Migrated {} trainings to owner", migrated);
+        }
+    }
+
+    Ok(AccessResult::NewUser(user))
+}
+
+/// Answer a chat over the user cap: offer to write to the owner, unless already denied
+async fn reply_limit_reached(
+    bot: &Bot,
+    chat_id: ChatId,
+    dialogue: &MyDialogue,
+    denied: bool,
+    max_users: usize,
+) -> HandlerResult {
+    if denied {
+        bot.send_message(chat_id, "Владелец отклонил запрос на доступ.").await?;
+        return Ok(());
+    }
+    let text = format!(
+        "Бот достиг лимита пользователей ({}).\n\n\
+        Напиши сообщение ниже - я передам его владельцу для обсуждения доступа.",
+        max_users
+    );
+    bot.send_message(chat_id, text).await?;
+    dialogue.update(State::WaitingForOwnerMessage).await?;
+    Ok(())
+}
+
+/// Parse the chat id argument of /approve and /deny
+fn parse_chat_id(arg: &str) -> Option<i64> {
+    arg.trim().parse().ok()
+}
+
+/// "/merge <from_chat> <into_chat>" arguments
+fn parse_merge_args(arg: &str) -> Option<(i64, i64)> {
+    let mut parts = arg.split_whitespace();
+    let from = parse_chat_id(parts.next()?)?;
+    let into = parse_chat_id(parts.next()?)?;
+    parts.next().is_none().then_some((from, into))
+}
+
+/// /leaderboard: first names only, with medals for the top three
+fn format_leaderboard(board: &[(Option<String>, i64)]) -> String {
+    if board.is_empty() {
+        return "🏆 За неделю ещё никто не тренировался".to_string();
+    }
+    let mut text = "🏆 Рейтинг за 7 дней (подходы):".to_string();
+    for (i, (first_name, sets)) in board.iter().enumerate() {
+        let place = match i {
+            0 => "🥇".to_string(),
+            1 => "🥈".to_string(),
+            2 => "🥉".to_string(),
+            _ => format!("{}.", i + 1),
+        };
+        let name = first_name.as_deref().unwrap_or("Без имени");
+        text.push_str(&format!("\n{} {} — {} подх.", place, name, sets));
+    }
+    text.push_str("\n\nСкрыть себя: /private");
+    text
+}
+
+/// /users list: name and training count per user, in the given order
+fn format_users(users: &[(User, usize)]) -> String {
+    let mut text = format!("👥 Пользователи ({}):", users.len());
+    for (user, count) in users {
+        let name = match (&user.username, &user.first_name) {
+            (Some(username), _) => format!("@{}", username),
+            (None, Some(first_name)) => first_name.clone(),
+            (None, None) => user.chat_id.to_string(),
+        };
+        let owner = if user.is_owner { " 👑" } else { "" };
+        text.push_str(&format!("\n{}{} — {} подх.", name, owner, count));
+    }
+    text
+}
+
+/// Why the bot could not start
+#[derive(Debug)]
+pub enum StartupError {
+    /// The Telegram token is empty or whitespace
+    EmptyToken,
+    /// The database could not be opened, even after a retry
+    DbOpen { path: String, source: anyhow::Error },
+}
+
+impl std::fmt::Display for StartupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StartupError::EmptyToken => {
+                write!(f, "Telegram bot token is empty: pass --token or set TELOXIDE_TOKEN")
+            }
+            StartupError::DbOpen { path, source } => write!(f, "cannot open DB at {}: {:#}", path, source),
+        }
+    }
+}
+
+impl std::error::Error for StartupError {}
+
+/// Trimmed bot token, rejecting an empty one before the dispatcher starts
+fn validate_token(token: &str) -> Result<&str, StartupError> {
+    match token.trim() {
+        "" => Err(StartupError::EmptyToken),
+        token => Ok(token),
+    }
+}
+
+/// Open the database, retrying once after a short pause for transient locks
+async fn open_db(path: &str) -> Result<Database, StartupError> {
+    match Database::open(path) {
+        Ok(db) => return Ok(db),
+        Err(e) => warn!("Cannot open DB at {}: {:#}, retrying in {} ms", path, e, DB_OPEN_RETRY_MS),
+    }
+    tokio::time::sleep(Duration::from_millis(DB_OPEN_RETRY_MS)).await;
+    Database::open(path).map_err(|source| {
+        let err = StartupError::DbOpen { path: path.to_string(), source };
+        error!("{}", err);
+        err
+    })
+}
+
+/// Start the Telegram bot with reminders
+pub async fn run_bot(token: String, db_path: &str) -> anyhow::Result<()> {
+    let bot = Bot::new(validate_token(&token)?);
+    let db = Arc::new(Mutex::new(open_db(db_path).await?));
+    let config = Arc::new(BotConfig::default());
+    let runtime: Runtime = Arc::new(Mutex::new(ChatRuntime::default()));
+
+    // Restore reminder subscriptions saved before the last restart
+    let first_due = next_reminder_due(Utc::now());
+    let restored: HashMap<ChatId, DateTime<Utc>> = db.lock().await
+        .get_subscribers()?
+        .into_iter()
+        .map(|chat_id| (ChatId(chat_id), first_due))
+        .collect();
+    info!("Restored {} reminder subscribers", restored.len());
+    let subscribers: Subscribers = Arc::new(Mutex::new(restored));
+
+    info!("Bot started with max_users={}", config.max_users);
+
+    // Start reminder background task
+    let reminder_bot = bot.clone();
+    let reminder_db = db.clone();
+    let reminder_subs = subscribers.clone();
+    tokio::spawn(async move {
+        reminder_task(reminder_bot, reminder_db, reminder_subs).await;
+    });
+
+    // Start trash purge background task
+    let purge_db = db.clone();
+    tokio::spawn(async move {
+        trash_purge_task(purge_db).await;
+    });
+
+    // Start daily tip background task
+    let tip_bot = bot.clone();
+    let tip_db = db.clone();
+    tokio::spawn(async move {
+        daily_tip_task(tip_bot, tip_db).await;
+    });
+
+    let handler = dptree::entry()
+        .enter_dialogue::<Update, InMemStorage<State>, State>()
+        .branch(
+            Update::filter_message()
+                .filter_command::<Command>()
+                .endpoint(handle_command),
+        )
+        .branch(
+            Update::filter_message()
+                .endpoint(handle_message),
+        )
+        .branch(
+            Update::filter_callback_query()
+                .endpoint(handle_callback),
+        );
+
+    Dispatcher::builder(bot, handler)
+        .dependencies(dptree::deps![InMemStorage::<State>::new(), db, config, subscribers, runtime])
+        .enable_ctrlc_handler()
+        .build()
+        .dispatch()
+        .await;
+
+    Ok(())
+}
+
+// dptree injects dependencies by argument, so handlers take them all
+#[allow(clippy::too_many_arguments)]
+async fn handle_command(
+    bot: Bot,
+    msg: Message,
+    cmd: Command,
+    dialogue: MyDialogue,
     db: Arc<Mutex<Database>>,
     config: Arc<BotConfig>,
     subscribers: Subscribers,
+    runtime: Runtime,
 ) -> HandlerResult {
     let chat_id = msg.chat.id.0;
     let username = msg.from.as_ref().and_then(|u| u.username.as_deref());
@@ -370,297 +2164,1001 @@ async fn handle_command(
                 };
                 bot.send_message(msg.chat.id, welcome).await?;
                 info!("New user registered: {} (owner={})", chat_id, user.is_owner);
+                if user.is_owner {
+                    start_setup_wizard(&bot, msg.chat.id, &dialogue, user.id).await?;
+                }
+                return Ok(());
+            }
+            AccessResult::LimitReached => {
+                let denied = db.get_pending_request(chat_id)?.is_some_and(|r| r.denied);
+                reply_limit_reached(&bot, msg.chat.id, &dialogue, denied, config.max_users).await?;
+                return Ok(());
+            }
+        }
+    };
+
+    match cmd {
+        Command::Start => {
+            // Owner's first run: onboard through the setup wizard
+            let needs_setup = user.is_owner && db.lock().await.get_settings(user.id)?.is_none();
+            if needs_setup {
+                start_setup_wizard(&bot, msg.chat.id, &dialogue, user.id).await?;
+                return Ok(());
+            }
+
+            bot.send_message(msg.chat.id, t(Key::Welcome, user.lang)).await?;
+        }
+
+        Command::Help => {
+            bot.send_message(msg.chat.id, format_help(user.lang))
+                .reply_markup(make_commands_keyboard())
+                .await?;
+        }
+
+        Command::Train => {
+            // Get recommendation based on muscle balance for this user
+            let (trainings, recommender) = training_recommender(&*db.lock().await, &user)?;
+
+            if let Some(rec) = recommender.get_recommendation() {
+                // Calculate fatigue-aware goal for the recommended exercise
+                let goal_info = GoalCalculator::calculate_in(&trainings, rec.exercise.name, user_tz(user.tz_offset_minutes))
+                    .map(|g| format!("\n\n📊 {}", g.format_short()))
+                    .unwrap_or_default();
+
+                // Show recommendation with option to choose other
+                let text = if rec.is_bonus {
+                    // Bonus exercise - show with detailed description and focus cues
+                    let desc = rec.detailed_description
+                        .as_deref()
+                        .or(rec.exercise.description)
+                        .unwrap_or("");
+
+                    let focus = rec.focus_cues
+                        .as_deref()
+                        .or(rec.exercise.focus_cues)
+                        .map(|f| format!("\n\n🎯 Фокус: {}", f))
+                        .unwrap_or_default();
+
+                    let muscles: Vec<_> = rec.exercise.muscle_groups
+                        .iter()
+                        .map(|m| m.name_ru())
+                        .collect();
+                    let muscle_info = format!("\n\n💪 Мышцы: {}", muscles.join(", "));
+
+                    format!(
+                        "🎁 Бонус! База выполнена!\n\n{} {}\n\n{}\n\n📖 {}{}{}{}\n\nВыбрать или пропустить?",
+                        rec.exercise.category.emoji(),
+                        rec.exercise.name,
+                        rec.reason,
+                        desc,
+                        focus,
+                        muscle_info,
+                        goal_info
+                    )
+                } else {
+                    // Base exercise
+                    format!(
+                        "🎯 Рекомендую: {} {}\n\n{}{}\n\nВыбрать рекомендованное или другое?",
+                        rec.exercise.category.emoji(),
+                        rec.exercise.name,
+                        rec.reason,
+                        goal_info
+                    )
+                };
+                let keyboard = if rec.is_bonus {
+                    // Bonus exercise: main button + optional shadow boxing + skip
+                    let mut rows = vec![
+                        vec![
+                            InlineKeyboardButton::callback(
+                                format!("✓ {}", rec.exercise.name),
+                                format!("ex:{}", rec.exercise.id)
+                            ),
+                        ],
+                    ];
+                    // Add shadow boxing button if recommended something else
+                    if rec.exercise.id != "shadow_boxing" {
+                        rows.push(vec![
+                            InlineKeyboardButton::callback("☯ бой с тенью", "ex:shadow_boxing")
+                        ]);
+                    }
+                    rows.push(vec![
+                        InlineKeyboardButton::callback("Пропустить", "skip_bonus")
+                    ]);
+                    InlineKeyboardMarkup::new(rows)
+                } else {
+                    // Base exercise: main button + choose another
+                    InlineKeyboardMarkup::new(vec![
+                        vec![
+                            InlineKeyboardButton::callback(
+                                format!("✓ {}", rec.exercise.name),
+                                format!("ex:{}", rec.exercise.id)
+                            ),
+                        ],
+                        vec![
+                            InlineKeyboardButton::callback("Выбрать другое", "show_all")
+                        ],
+                    ])
+                };
+                bot.send_message(msg.chat.id, text)
+                    .reply_markup(keyboard)
+                    .await?;
+            } else {
+                // No recommendation, show all exercises
+                let base = user_base_exercises(&*db.lock().await, user.id)?;
+                let keyboard = make_exercises_keyboard(&base);
+                bot.send_message(msg.chat.id, "Выбери упражнение:")
+                    .reply_markup(keyboard)
+                    .await?;
+            }
+        }
+
+        Command::Today => {
+            let db = db.lock().await;
+            let today_trainings = db.get_trainings_today_minutes(user.id, db.user_tz_offset_minutes(user.id)?)?;
+
+            if today_trainings.is_empty() {
+                let text = if is_rest_day_today(&db, user.id)? {
+                    "😴 День отдыха"
+                } else {
+                    "Сегодня ещё не было тренировок."
+                };
+                bot.send_message(msg.chat.id, text)
+                    .reply_markup(make_commands_keyboard())
+                    .await?;
+            } else {
+                let mut text = String::from("📊 Сегодня:\n\n");
+                for t in &today_trainings {
+                    text.push_str(&format_today_entry(t));
+                }
+                bot.send_message(msg.chat.id, text)
+                    .reply_markup(make_commands_keyboard())
+                    .await?;
+            }
+        }
+
+        Command::Stats(arg) if !arg.trim().is_empty() => {
+            let trainings = {
+                let db = db.lock().await;
+                db.get_trainings_for_user(user.id)?
+            };
+
+            let text = match resolve_logged_exercise(&arg, &trainings) {
+                Some(exercise) => {
+                    let sets: Vec<_> = trainings.iter().filter(|t| t.exercise == exercise).collect();
+                    let best = sets.iter().map(|t| t.reps).max().unwrap_or(0);
+                    let recovery = Analytics::new(trainings.clone()).pulse_recovery_trend(&exercise);
+                    format!(
+                        "📈 {}\n\nВсего: {} подх., рекорд: {} повт.\n{}\n{}",
+                        exercise,
+                        sets.len(),
+                        best,
+                        format_pulse_trend(pulse_trend(&trainings, &exercise)),
+                        format_pulse_recovery(recovery)
+                    )
+                }
+                None => format!("Нет записей по упражнению «{}».", arg.trim()),
+            };
+            bot.send_message(msg.chat.id, text)
+                .reply_markup(make_commands_keyboard())
+                .await?;
+        }
+
+        Command::Stats(_) => {
+            let now = Utc::now();
+            let (trainings, total) = {
+                let db = db.lock().await;
+                let trainings = db.get_trainings_in_range(
+                    user.id,
+                    now - chrono::Duration::days(STATS_WINDOW_DAYS),
+                    now + chrono::Duration::days(1),
+                )?;
+                (trainings, db.count_trainings_for_user(user.id)?)
+            };
+
+            bot.send_message(msg.chat.id, format_stats_summary(&trainings, total, now, user_tz(user.tz_offset_minutes), user.lang))
+                .reply_markup(make_commands_keyboard())
+                .await?;
+        }
+
+        Command::SetGoal(arg) => {
+            let text = match parse_set_goal(&arg) {
+                None => "Пример: /setgoal планка 3м или /setgoal пловец 30".to_string(),
+                Some((query, value)) => match resolve_goal_exercise(&query) {
+                    Err(e) => e,
+                    Ok(exercise) => match parse_goal_value(&value, exercise.is_timed) {
+                        None if exercise.is_timed => "Время цели: 90 (секунды), 90с или 3м".to_string(),
+                        None => "Цель — число повторов, например 30".to_string(),
+                        Some(target) => {
+                            let db = db.lock().await;
+                            let best = best_value(&db.get_trainings_for_user(user.id)?, exercise.name, exercise.is_timed);
+                            if best >= target {
+                                format!(
+                                    "🏆 {}: {} уже есть (лучшее {})",
+                                    exercise.name,
+                                    format_goal_value(target, exercise.is_timed),
+                                    format_goal_value(best, exercise.is_timed)
+                                )
+                            } else {
+                                let id = db.add_goal(user.id, exercise.name, target, exercise.is_timed)?;
+                                format!(
+                                    "🎯 Цель #{}: {} — {}. Поздравлю, когда дойдёшь! Список: /goals",
+                                    id, exercise.name, format_goal_value(target, exercise.is_timed)
+                                )
+                            }
+                        }
+                    },
+                },
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
+
+        Command::Goals => {
+            let text = {
+                let db = db.lock().await;
+                format_goals(&db.get_goals(user.id)?, &db.get_trainings_for_user(user.id)?, user_tz(user.tz_offset_minutes))
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
+
+        Command::DelGoal(arg) => {
+            let text = match arg.trim().trim_start_matches('#').parse::<i64>() {
+                Err(_) => "Укажи номер цели из /goals: /delgoal 3".to_string(),
+                Ok(id) => {
+                    if db.lock().await.delete_goal(id, user.id)? {
+                        format!("🗑 Цель #{} удалена", id)
+                    } else {
+                        format!("Цели #{} нет. Список: /goals", id)
+                    }
+                }
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
+
+        Command::Pulse(arg) => {
+            let trainings = db.lock().await.get_trainings_for_user(user.id)?;
+            let text = match resolve_logged_exercise(&arg, &trainings) {
+                Some(exercise) => {
+                    let deltas = Analytics::new(trainings).pulse_deltas(&exercise, PULSE_SPARKLINE_POINTS);
+                    format_pulse_sparkline(&exercise, &deltas)
+                }
+                None if arg.trim().is_empty() => "Укажи упражнение: /pulse пловец".to_string(),
+                None => format!("Нет записей по упражнению «{}».", arg.trim()),
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
+
+        Command::Graph(arg) => {
+            let trainings = db.lock().await.get_trainings_for_user(user.id)?;
+            let Some(exercise) = resolve_logged_exercise(&arg, &trainings) else {
+                let text = if arg.trim().is_empty() {
+                    "Укажи упражнение: /graph пловец".to_string()
+                } else {
+                    format!("Нет записей по упражнению «{}».", arg.trim())
+                };
+                bot.send_message(msg.chat.id, text).await?;
+                return Ok(());
+            };
+            let is_timed = trainings.iter()
+                .find(|t| t.exercise == exercise)
+                .and_then(|t| t.exercise_is_timed())
+                .unwrap_or(false);
+            if is_timed {
+                bot.send_message(msg.chat.id, "График строится по повторам, а это упражнение на время").await?;
+                return Ok(());
+            }
+
+            match ProgressPredictor::best_fit(&trainings, &exercise) {
+                Some(predictor) => {
+                    let png = render_progress_png(&predictor)?;
+                    bot.send_photo(msg.chat.id, InputFile::memory(png).file_name("progress.png"))
+                        .caption(format_graph_caption(&exercise, &predictor))
+                        .await?;
+                }
+                None => {
+                    let text = format!(
+                        "Мало данных для графика «{}»: нужно хотя бы {} подхода",
+                        exercise, MIN_DATA_POINTS
+                    );
+                    bot.send_message(msg.chat.id, text).await?;
+                }
+            }
+        }
+
+        Command::Compare => {
+            let (trainings, tz_offset_secs) = {
+                let db = db.lock().await;
+                (db.get_trainings_for_user(user.id)?, user_tz_offset_secs(&db, user.id)?)
+            };
+            let tz = FixedOffset::east_opt(tz_offset_secs).unwrap_or_else(moscow_tz);
+            let now = Utc::now();
+            let week_start = week_start_utc(now, tz_offset_secs);
+            let week = chrono::Duration::days(7);
+
+            // Same elapsed span of last week, so early-week numbers aren't always down
+            let cmp = Analytics::new(trainings)
+                .compare_periods(week_start..now, week_start - week..now - week, tz);
+            bot.send_message(msg.chat.id, format_week_comparison(&cmp))
+                .reply_markup(make_commands_keyboard())
+                .await?;
+        }
+
+        Command::Leaderboard => {
+            let board = db.lock().await.get_weekly_leaderboard(Utc::now())?;
+            bot.send_message(msg.chat.id, format_leaderboard(&board)).await?;
+        }
+
+        Command::Private => {
+            let hide = !user.hide_from_board;
+            db.lock().await.set_user_hide_from_board(user.id, hide)?;
+            let text = if hide {
+                "🙈 Ты скрыт из рейтинга. Вернуться: /private"
+            } else {
+                "👀 Ты снова в рейтинге /leaderboard"
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
+
+        Command::Age(arg) => {
+            let text = match arg.trim() {
+                "" => match user.age {
+                    Some(age) => format!("Возраст: {}. Макс. пульс ≈ {}", age, max_hr(age)),
+                    None => "Возраст не указан. Пример: /age 35".to_string(),
+                },
+                arg => match arg.parse::<i32>() {
+                    Ok(age) if (10..=100).contains(&age) => {
+                        db.lock().await.set_user_age(user.id, age)?;
+                        format!("✅ Возраст: {}. Макс. пульс ≈ {}", age, max_hr(age))
+                    }
+                    _ => "Возраст должен быть числом от 10 до 100".to_string(),
+                },
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
+
+        Command::Lang(arg) => {
+            let text = match Lang::from_code(&arg) {
+                Some(lang) => {
+                    db.lock().await.set_user_lang(user.id, lang)?;
+                    t(Key::LangSet, lang)
+                }
+                None => t(Key::LangUsage, user.lang),
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
+
+        Command::Tz(arg) => {
+            let text = match arg.trim() {
+                "" => format!(
+                    "Часовой пояс: {}. Изменить: /tz +180",
+                    format_tz_offset(user.tz_offset_minutes)
+                ),
+                arg => match parse_tz_offset(arg) {
+                    Some(minutes) => {
+                        db.lock().await.set_user_tz(user.id, minutes)?;
+                        format!("✅ Часовой пояс: {}", format_tz_offset(minutes))
+                    }
+                    None => "Смещение в минутах от UTC, кратное 15: /tz +180 (Москва), /tz -300".to_string(),
+                },
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
+
+        Command::Approve(_) | Command::Deny(_) | Command::Users | Command::Merge(_) if !user.is_owner => {
+            bot.send_message(msg.chat.id, "Команда доступна только владельцу").await?;
+        }
+
+        Command::Approve(arg) => {
+            let Some(target) = parse_chat_id(&arg) else {
+                bot.send_message(msg.chat.id, "Укажи chat_id из запроса: /approve 123456").await?;
                 return Ok(());
-            }
-            AccessResult::LimitReached => {
-                let text = "Бот достиг лимита пользователей (10).\n\n\
-                    Напиши сообщение ниже - я передам его владельцу для обсуждения доступа.";
-                bot.send_message(msg.chat.id, text).await?;
-                dialogue.update(State::WaitingForOwnerMessage).await?;
+            };
+            let approved = db.lock().await.approve_request(target)?;
+            let text = match approved {
+                Some(approved) => {
+                    bot.send_message(ChatId(target), "✅ Владелец открыл доступ! Жми /train, чтобы начать").await?;
+                    info!("Owner approved access for chat_id={}", target);
+                    format!(
+                        "✅ Доступ открыт: {}",
+                        approved.username.map(|u| format!("@{}", u)).unwrap_or_else(|| target.to_string())
+                    )
+                }
+                None => format!("Нет запроса на доступ от {}", target),
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
+
+        Command::Users => {
+            let users = db.lock().await.get_users_with_counts()?;
+            bot.send_message(msg.chat.id, format_users(&users)).await?;
+        }
+
+        Command::Merge(arg) => {
+            let Some((from_chat, into_chat)) = parse_merge_args(&arg) else {
+                bot.send_message(msg.chat.id, "Укажи chat_id старого и нового аккаунта: /merge 111 222").await?;
                 return Ok(());
-            }
+            };
+            let text = {
+                let db = db.lock().await;
+                match (db.get_user_by_chat_id(from_chat)?, db.get_user_by_chat_id(into_chat)?) {
+                    (None, _) => format!("Пользователь {} не найден", from_chat),
+                    (_, None) => format!("Пользователь {} не найден", into_chat),
+                    (Some(_), Some(_)) if from_chat == into_chat => "Нельзя объединить аккаунт сам с собой".to_string(),
+                    (Some(from), Some(_)) if from.is_owner => "Аккаунт владельца нельзя перенести".to_string(),
+                    (Some(from), Some(into)) => {
+                        let moved = db.merge_users(from.id, into.id)?;
+                        info!("Owner merged chat_id={} into chat_id={} ({} trainings)", from_chat, into_chat, moved);
+                        format!("🔀 Перенесено {} подх. из {} в {}, старый аккаунт удалён", moved, from_chat, into_chat)
+                    }
+                }
+            };
+            bot.send_message(msg.chat.id, text).await?;
         }
-    };
 
-    match cmd {
-        Command::Start => {
-            let text = "🥋 无极 majowuji\n\n\
-                Трекер тренировок боевых искусств\n\n\
-                /train - выбрать упражнение\n\
-                /today - сегодняшние тренировки\n\
-                /stats - статистика\n\
-                /balance - баланс мышц\n\
-                /remind - напоминания раз в час\n\
-                /stop - выключить напоминания";
+        Command::Deny(arg) => {
+            let Some(target) = parse_chat_id(&arg) else {
+                bot.send_message(msg.chat.id, "Укажи chat_id из запроса: /deny 123456").await?;
+                return Ok(());
+            };
+            let text = if db.lock().await.deny_request(target)? {
+                bot.send_message(ChatId(target), "Владелец отклонил запрос на доступ.").await?;
+                info!("Owner denied access for chat_id={}", target);
+                format!("🚫 Запрос {} отклонён", target)
+            } else {
+                format!("Нет запроса на доступ от {}", target)
+            };
             bot.send_message(msg.chat.id, text).await?;
         }
 
-        Command::Help => {
-            bot.send_message(msg.chat.id, Command::descriptions().to_string())
-                .await?;
+        Command::Weight(arg) => {
+            let db = db.lock().await;
+            let previous = db.latest_bodyweight(user.id)?;
+            let text = if arg.trim().is_empty() {
+                match previous {
+                    Some(w) => format!("⚖️ Последний вес: {:.1} кг. Записать: /weight 72.5", w.weight_kg),
+                    None => "Вес не записан. Пример: /weight 72.5".to_string(),
+                }
+            } else {
+                match parse_weight(&arg) {
+                    Some(kg) => {
+                        db.add_bodyweight(user.id, Utc::now(), kg)?;
+                        match previous {
+                            Some(w) => format!("⚖️ {:.1} кг ({:+.1} кг)", kg, kg - w.weight_kg),
+                            None => format!("⚖️ {:.1} кг", kg),
+                        }
+                    }
+                    None => format!(
+                        "Вес должен быть числом от {} до {} кг",
+                        BODYWEIGHT_RANGE_KG.start(), BODYWEIGHT_RANGE_KG.end()
+                    ),
+                }
+            };
+            bot.send_message(msg.chat.id, text).await?;
         }
 
-        Command::Train => {
-            // Get recommendation based on muscle balance for this user
-            let trainings = {
-                let db = db.lock().await;
-                db.get_trainings_for_user(user.id)?
+        Command::Where(arg) => {
+            let text = match arg.trim() {
+                "" => match &user.current_context {
+                    Some(context) => format!("📍 Сейчас: {}. Сброс: /where -", context),
+                    None => "📍 Место не задано. Пример: /where дома".to_string(),
+                },
+                "-" => {
+                    db.lock().await.set_current_context(user.id, None)?;
+                    "📍 Место сброшено".to_string()
+                }
+                context => {
+                    let context = context.to_lowercase();
+                    db.lock().await.set_current_context(user.id, Some(&context))?;
+                    format!("📍 Теперь: {}. Новые подходы будут с этой пометкой", context)
+                }
             };
-            let recommender = Recommender::new(trainings.clone());
+            bot.send_message(msg.chat.id, text).await?;
+        }
 
-            if let Some(rec) = recommender.get_recommendation() {
-                // Calculate fatigue-aware goal for the recommended exercise
-                let goal_info = GoalCalculator::calculate(&trainings, rec.exercise.name)
-                    .map(|g| format!("\n\n📊 {}", g.format_short()))
-                    .unwrap_or_default();
+        Command::Gear(arg) => {
+            let names: Vec<_> = Equipment::all().iter().map(|e| e.name_ru()).collect();
+            let text = if arg.trim().is_empty() {
+                format!(
+                    "🎒 Инвентарь: {}\n\nПример: /gear ручки ступенька, /gear нет, /gear всё\nВарианты: {}",
+                    format_gear(user.equipment.as_deref()),
+                    names.join(", ")
+                )
+            } else {
+                match parse_gear(&arg) {
+                    Ok(equipment) => {
+                        db.lock().await.set_user_equipment(user.id, equipment.as_deref())?;
+                        format!("✅ Инвентарь: {}", format_gear(equipment.as_deref()))
+                    }
+                    Err(e) => format!("{}\nВарианты: {}", e, names.join(", ")),
+                }
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
 
-                // Show recommendation with option to choose other
-                let text = if rec.is_bonus {
-                    // Bonus exercise - show with detailed description and focus cues
-                    let desc = rec.detailed_description
-                        .as_deref()
-                        .or(rec.exercise.description)
-                        .unwrap_or("");
+        Command::BonusRule(arg) => {
+            let text = match arg.trim().trim_end_matches('%') {
+                "" => format!(
+                    "Бонус открывается после {}% базовой программы. Пример: /bonusrule 80",
+                    user.bonus_unlock_percent.unwrap_or(100)
+                ),
+                arg => match arg.parse::<i32>() {
+                    Ok(percent) if (1..=100).contains(&percent) => {
+                        db.lock().await.set_bonus_unlock_percent(user.id, percent)?;
+                        format!("✅ Бонус откроется после {}% базовой программы", percent)
+                    }
+                    _ => "Порог должен быть числом от 1 до 100".to_string(),
+                },
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
 
-                    let focus = rec.focus_cues
-                        .as_deref()
-                        .or(rec.exercise.focus_cues)
-                        .map(|f| format!("\n\n🎯 Фокус: {}", f))
-                        .unwrap_or_default();
+        Command::Level(arg) => {
+            let text = match arg.trim().to_lowercase().as_str() {
+                "" => match user.max_difficulty {
+                    Some(max) => format!("Бонус: упражнения сложностью до {} из 5. Снять ограничение: /level off", max),
+                    None => "Бонус: упражнения любой сложности. Пример: /level 2".to_string(),
+                },
+                "off" | "нет" => {
+                    db.lock().await.set_user_max_difficulty(user.id, None)?;
+                    "✅ Бонус: упражнения любой сложности".to_string()
+                }
+                arg => match arg.parse::<u8>() {
+                    Ok(max) if DIFFICULTY_RANGE.contains(&max) => {
+                        db.lock().await.set_user_max_difficulty(user.id, Some(max))?;
+                        format!("✅ Бонус: упражнения сложностью до {} из 5", max)
+                    }
+                    _ => "Сложность должна быть числом от 1 до 5 (или /level off)".to_string(),
+                },
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
 
-                    let muscles: Vec<_> = rec.exercise.muscle_groups
-                        .iter()
-                        .map(|m| m.name_ru())
-                        .collect();
-                    let muscle_info = format!("\n\n💪 Мышцы: {}", muscles.join(", "));
+        Command::Weekly(arg) => {
+            let target = match arg.trim().to_lowercase().as_str() {
+                "" => user.weekly_target,
+                "off" | "нет" => {
+                    db.lock().await.set_user_weekly_target(user.id, None)?;
+                    bot.send_message(msg.chat.id, "✅ Недельная цель снята").await?;
+                    return Ok(());
+                }
+                arg => match arg.parse::<u8>() {
+                    Ok(target) if WEEKLY_TARGET_RANGE.contains(&target) => {
+                        db.lock().await.set_user_weekly_target(user.id, Some(target))?;
+                        Some(target)
+                    }
+                    _ => {
+                        bot.send_message(msg.chat.id, "Цель — число тренировок от 1 до 7 в неделю: /weekly 4 (или /weekly off)")
+                            .await?;
+                        return Ok(());
+                    }
+                },
+            };
+            let text = match target {
+                Some(target) => {
+                    let now = Utc::now();
+                    let done = weekly_sessions_done(&*db.lock().await, user.id, now)?;
+                    let weekday = now.with_timezone(&user_tz(user.tz_offset_minutes)).weekday();
+                    format_weekly_status(done, target, weekday)
+                }
+                None => "Недельной цели нет. Пример: /weekly 4 — четыре тренировки в неделю".to_string(),
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
 
-                    format!(
-                        "🎁 Бонус! База выполнена!\n\n{} {}\n\n{}\n\n📖 {}{}{}{}\n\nВыбрать или пропустить?",
-                        rec.exercise.category.emoji(),
-                        rec.exercise.name,
-                        rec.reason,
-                        desc,
-                        focus,
-                        muscle_info,
-                        goal_info
-                    )
-                } else {
-                    // Base exercise
-                    format!(
-                        "🎯 Рекомендую: {} {}\n\n{}{}\n\nВыбрать рекомендованное или другое?",
-                        rec.exercise.category.emoji(),
-                        rec.exercise.name,
-                        rec.reason,
-                        goal_info
-                    )
-                };
-                let keyboard = if rec.is_bonus {
-                    // Bonus exercise: main button + optional shadow boxing + skip
-                    let mut rows = vec![
-                        vec![
-                            InlineKeyboardButton::callback(
-                                format!("✓ {}", rec.exercise.name),
-                                format!("ex:{}", rec.exercise.id)
-                            ),
-                        ],
-                    ];
-                    // Add shadow boxing button if recommended something else
-                    if rec.exercise.id != "shadow_boxing" {
-                        rows.push(vec![
-                            InlineKeyboardButton::callback("☯ бой с тенью", "ex:shadow_boxing")
-                        ]);
+        Command::Quiet(arg) => {
+            let text = match parse_quiet_hours(&arg) {
+                None => "Укажи часы начала и конца: /quiet 22 7 (или /quiet off)".to_string(),
+                Some(hours) => {
+                    if !db.lock().await.set_quiet_hours(msg.chat.id.0, hours)? {
+                        "Напоминания выключены — сначала /remind".to_string()
+                    } else if let Some((start, end)) = hours {
+                        format!("🌙 Тихие часы: {:02}:00–{:02}:00, напоминаний не будет", start, end)
+                    } else {
+                        "🔔 Тихие часы выключены".to_string()
                     }
-                    rows.push(vec![
-                        InlineKeyboardButton::callback("Пропустить", "skip_bonus")
-                    ]);
-                    InlineKeyboardMarkup::new(rows)
-                } else {
-                    // Base exercise: main button + choose another
-                    InlineKeyboardMarkup::new(vec![
-                        vec![
-                            InlineKeyboardButton::callback(
-                                format!("✓ {}", rec.exercise.name),
-                                format!("ex:{}", rec.exercise.id)
-                            ),
-                        ],
-                        vec![
-                            InlineKeyboardButton::callback("Выбрать другое", "show_all")
-                        ],
-                    ])
-                };
-                bot.send_message(msg.chat.id, text)
-                    .reply_markup(keyboard)
+                }
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
+
+        Command::Remind => {
+            db.lock().await.add_subscriber(msg.chat.id.0)?;
+            let mut subs = subscribers.lock().await;
+            subs.insert(msg.chat.id, next_reminder_due(Utc::now()));
+            let count = subs.len();
+
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "✅ Напоминания включены!\n\n\
+                    Буду напоминать раз в час.\n\
+                    Активных подписчиков: {}",
+                    count
+                )
+            )
+            .reply_markup(make_commands_keyboard())
+            .await?;
+
+            info!("User {} subscribed to reminders", msg.chat.id);
+        }
+
+        Command::Stop => {
+            let was_persisted = db.lock().await.remove_subscriber(msg.chat.id.0)?;
+            let mut subs = subscribers.lock().await;
+            let was_subscribed = subs.remove(&msg.chat.id).is_some() || was_persisted;
+
+            if was_subscribed {
+                bot.send_message(msg.chat.id, "🔕 Напоминания выключены.")
+                    .reply_markup(make_commands_keyboard())
                     .await?;
+                info!("User {} unsubscribed from reminders", msg.chat.id);
             } else {
-                // No recommendation, show all exercises
-                let keyboard = make_exercises_keyboard();
-                bot.send_message(msg.chat.id, "Выбери упражнение:")
-                    .reply_markup(keyboard)
+                bot.send_message(msg.chat.id, "Напоминания и так выключены.")
+                    .reply_markup(make_commands_keyboard())
                     .await?;
             }
         }
 
-        Command::Today => {
+        Command::StopAll => {
+            let stopped = stop_all_notifications(msg.chat.id, user.id, &db, &subscribers, &runtime).await?;
+            let text = if stopped.is_empty() {
+                "Фоновых уведомлений нет — выключать нечего.".to_string()
+            } else {
+                let items: Vec<_> = stopped.iter().map(|s| format!("• {}", s)).collect();
+                format!("🔕 Выключено:\n{}", items.join("\n"))
+            };
+            bot.send_message(msg.chat.id, text)
+                .reply_markup(make_commands_keyboard())
+                .await?;
+            info!("User {} stopped all notifications: {:?}", msg.chat.id, stopped);
+        }
+
+        Command::Sore(arg) => {
             let db = db.lock().await;
-            let trainings = db.get_trainings_for_user(user.id)?;
-            let today = Utc::now().with_timezone(&moscow_tz()).date_naive();
+            let text = if arg.trim().is_empty() {
+                let sore = active_sore_groups(&db, user.id)?;
+                if sore.is_empty() {
+                    "Ничего не болит 💪\n\nПример: /sore спина".to_string()
+                } else {
+                    let names: Vec<_> = sore.iter().map(|mg| mg.name_ru()).collect();
+                    format!("🤕 Болит: {}\n\nЭти мышцы /train грузит в последнюю очередь.", names.join(", "))
+                }
+            } else if let Some(group) = MuscleGroup::from_ru(&arg) {
+                db.add_soreness(user.id, user_today(&db, user.id)?, group.name_ru())?;
+                format!(
+                    "🤕 Отмечено: {}\n\nСегодня и завтра буду рекомендовать упражнения без этой группы.",
+                    group.name_ru()
+                )
+            } else {
+                let names: Vec<_> = MuscleGroup::all().iter().map(|mg| mg.name_ru()).collect();
+                format!("Не знаю такую группу мышц. Варианты: {}", names.join(", "))
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
 
-            let today_trainings: Vec<_> = trainings
-                .iter()
-                .filter(|t| t.date.with_timezone(&moscow_tz()).date_naive() == today)
-                .collect();
+        Command::Template(arg) => {
+            let db = db.lock().await;
+            let mut template = db.get_weekday_template(user.id)?;
+            let text = if arg.trim().is_empty() {
+                format_weekday_template(&template)
+            } else {
+                match apply_template_arg(&mut template, &arg) {
+                    Ok(()) => {
+                        db.save_weekday_template(user.id, &template)?;
+                        format!("✅ Сохранено\n\n{}", format_weekday_template(&template))
+                    }
+                    Err(e) => e,
+                }
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
 
-            if today_trainings.is_empty() {
-                bot.send_message(msg.chat.id, "Сегодня ещё не было тренировок.")
-                    .reply_markup(make_commands_keyboard())
-                    .await?;
+        Command::Rest(arg) => {
+            let text = match parse_rest_secs(&arg) {
+                Some(secs) => {
+                    let replaced = start_rest_timer(bot.clone(), msg.chat.id, runtime.clone(), secs).await;
+                    let note = if replaced { " (предыдущий таймер сброшен)" } else { "" };
+                    format!("⏱ Отдых {}{}", format_duration(secs as i32), note)
+                }
+                None => format!("Секунды отдыха от 1 до {}: /rest 60", MAX_REST_SECS),
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
+
+        Command::RestDay => {
+            let added = {
+                let db = db.lock().await;
+                let today = user_today(&db, user.id)?;
+                db.add_rest_day(user.id, today)?
+            };
+            let text = if added {
+                "😴 День отдыха записан. Стрик не прервётся, напоминаний сегодня не будет."
             } else {
-                let mut text = String::from("📊 Сегодня:\n\n");
-                for t in today_trainings {
-                    text.push_str(&format!(
-                        "• {} - {}x{}\n",
-                        t.exercise, t.sets, t.reps
-                    ));
+                "😴 Сегодня уже отмечен как день отдыха."
+            };
+            bot.send_message(msg.chat.id, text)
+                .reply_markup(make_commands_keyboard())
+                .await?;
+        }
+
+        Command::Cancel => {
+            let text = cancel_reply(dialogue.get().await?.as_ref());
+            dialogue.reset().await?;
+            bot.send_message(msg.chat.id, text)
+                .reply_markup(make_commands_keyboard())
+                .await?;
+        }
+
+        Command::Undo => {
+            let (removed, tz_offset_secs) = {
+                let db = db.lock().await;
+                // get_trainings_for_user is newest first
+                let removed = match db.get_trainings_for_user(user.id)?.into_iter().next() {
+                    Some(last) => match last.id {
+                        Some(id) if db.delete_training(id, user.id)? => Some(last),
+                        _ => None,
+                    },
+                    None => None,
+                };
+                (removed, user_tz_offset_secs(&db, user.id)?)
+            };
+            let tz = FixedOffset::east_opt(tz_offset_secs).unwrap_or_else(moscow_tz);
+            match removed {
+                Some(t) => {
+                    let text = format!(
+                        "🗑 Удалено: {} - {}x{} ({})\nВернуть можно в течение {} ч",
+                        t.exercise,
+                        t.sets,
+                        t.reps,
+                        t.date.with_timezone(&tz).format("%d.%m %H:%M"),
+                        TRASH_RETENTION_HOURS
+                    );
+                    let mut request = bot.send_message(msg.chat.id, text);
+                    if let Some(id) = t.id {
+                        request = request.reply_markup(make_restore_keyboard(id));
+                    }
+                    request.await?;
+                }
+                None => {
+                    bot.send_message(msg.chat.id, "Нечего удалять").await?;
                 }
-                bot.send_message(msg.chat.id, text)
-                    .reply_markup(make_commands_keyboard())
-                    .await?;
             }
         }
 
-        Command::Stats => {
-            let db = db.lock().await;
-            let trainings = db.get_trainings_for_user(user.id)?;
+        Command::Repeat(arg) => {
+            let (date, trainings) = {
+                let db = db.lock().await;
+                let date = match arg.trim() {
+                    "" => Ok(user_today(&db, user.id)? - chrono::Duration::days(1)),
+                    arg => chrono::NaiveDate::parse_from_str(arg, "%Y-%m-%d"),
+                };
+                match date {
+                    Ok(date) => {
+                        let tz_offset_secs = user_tz_offset_secs(&db, user.id)?;
+                        (Some(date), db.get_trainings_on_day(Some(user.id), date, tz_offset_secs)?)
+                    }
+                    Err(_) => (None, Vec::new()),
+                }
+            };
 
-            let total = trainings.len();
-            let today = Utc::now().with_timezone(&moscow_tz()).date_naive();
-            let week_ago = today - chrono::Duration::days(7);
-            let month_ago = today - chrono::Duration::days(30);
+            let Some(date) = date else {
+                bot.send_message(msg.chat.id, "Дата в формате ГГГГ-ММ-ДД, например /repeat 2026-01-05").await?;
+                return Ok(());
+            };
+            let plan = repeat_plan(&trainings);
+            if plan.is_empty() {
+                bot.send_message(msg.chat.id, format!("{}: тренировок не было", date)).await?;
+                return Ok(());
+            }
 
-            let today_trainings: Vec<_> = trainings
+            let buttons: Vec<Vec<InlineKeyboardButton>> = plan
                 .iter()
-                .filter(|t| t.date.with_timezone(&moscow_tz()).date_naive() == today)
+                .map(|(exercise, target)| vec![InlineKeyboardButton::callback(
+                    exercise_button_label(exercise, Some(*target)),
+                    format!("ex:{}", exercise.id),
+                )])
                 .collect();
+            bot.send_message(msg.chat.id, format_repeat_plan(date, &plan))
+                .reply_markup(InlineKeyboardMarkup::new(buttons))
+                .await?;
+        }
 
-            let week_trainings: Vec<_> = trainings
-                .iter()
-                .filter(|t| t.date.with_timezone(&moscow_tz()).date_naive() > week_ago)
+        Command::Checklist => {
+            let (plan, enabled) = {
+                let db = db.lock().await;
+                let (_, recommender) = training_recommender(&db, &user)?;
+                (recommender.daily_plan(), user_base_exercises(&db, user.id)?)
+            };
+            let plan: Vec<_> = plan
+                .into_iter()
+                .filter(|item| enabled.is_empty() || enabled.iter().any(|id| id == item.exercise.id))
                 .collect();
 
-            let month_trainings: Vec<_> = trainings
-                .iter()
-                .filter(|t| t.date.with_timezone(&moscow_tz()).date_naive() > month_ago)
-                .collect();
+            bot.send_message(msg.chat.id, format_checklist(&plan))
+                .reply_markup(make_commands_keyboard())
+                .await?;
+        }
 
-            let today_time: i32 = today_trainings.iter()
-                .filter_map(|t| t.duration_secs)
-                .sum();
-            let week_time: i32 = week_trainings.iter()
-                .filter_map(|t| t.duration_secs)
-                .sum();
-            let month_time: i32 = month_trainings.iter()
-                .filter_map(|t| t.duration_secs)
-                .sum();
-
-            let mut text = format!(
-                "📈 Статистика\n\n\
-                Всего: {} подх.\n\
-                Сегодня: {} ({})\n\
-                Неделя: {} ({})\n\
-                Месяц: {} ({})\n",
-                total,
-                today_trainings.len(), format_duration(today_time),
-                week_trainings.len(), format_duration(week_time),
-                month_trainings.len(), format_duration(month_time)
-            );
+        Command::Tip => {
+            let tip = next_tip(msg.chat.id, &runtime).await;
+            bot.send_message(msg.chat.id, format_book_tip(tip))
+                .reply_markup(make_tip_keyboard())
+                .await?;
+        }
 
-            // Group today's trainings by exercise
-            if !today_trainings.is_empty() {
-                text.push_str("\n📊 Сегодня:\n");
-                // (sets, total_reps, total_time, max_time)
-                let mut exercise_stats: std::collections::HashMap<&str, (usize, i32, i32, i32)> = std::collections::HashMap::new();
-                for t in &today_trainings {
-                    let duration = t.duration_secs.unwrap_or(0);
-                    let entry = exercise_stats.entry(&t.exercise).or_insert((0, 0, 0, 0));
-                    entry.0 += 1;  // sets count
-                    entry.1 += t.reps;  // total reps
-                    entry.2 += duration;  // total time
-                    entry.3 = entry.3.max(duration);  // max time (record)
+        Command::DailyTip(arg) => {
+            let text = if arg.trim().is_empty() {
+                match db.lock().await.get_daily_tip_hour(chat_id)? {
+                    Some(hour) => format!("📖 Совет дня приходит в {:02}:00. Выключить: /dailytip off", hour),
+                    None => "Совет дня выключен. Включить: /dailytip on 8".to_string(),
                 }
-                for (exercise, (sets, reps, total_time, max_time)) in exercise_stats {
-                    // Check if exercise is timed
-                    let is_timed = find_exercise_by_name(exercise)
-                        .map(|ex| ex.is_timed)
-                        .unwrap_or(false);
-
-                    if is_timed {
-                        // For timed exercises: show max time and total
-                        text.push_str(&format!(
-                            "• {} - {} подх., макс. {}с, всего {}\n",
-                            exercise, sets, max_time, format_duration(total_time)
-                        ));
-                    } else {
-                        // For rep-based: show reps and time
-                        text.push_str(&format!(
-                            "• {} - {} подх., {} повт., {}\n",
-                            exercise, sets, reps, format_duration(total_time)
-                        ));
+            } else {
+                match parse_daily_tip(&arg) {
+                    None => "Укажи час (0-23): /dailytip on 8 (или /dailytip off)".to_string(),
+                    Some(Some(hour)) => {
+                        db.lock().await.set_daily_tip(chat_id, Some(hour))?;
+                        format!("📖 Совет дня будет приходить в {:02}:00", hour)
+                    }
+                    Some(None) => {
+                        if db.lock().await.set_daily_tip(chat_id, None)? {
+                            "🔕 Совет дня выключен".to_string()
+                        } else {
+                            "Совет дня и так выключен".to_string()
+                        }
                     }
                 }
-            }
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
 
-            bot.send_message(msg.chat.id, text)
-                .reply_markup(make_commands_keyboard())
+        Command::S => {
+            let (trainings, rest_days, tz_offset_secs) = {
+                let db = db.lock().await;
+                (
+                    db.get_trainings_for_user(user.id)?,
+                    db.get_rest_days(user.id)?,
+                    user_tz_offset_secs(&db, user.id)?,
+                )
+            };
+            let tz = FixedOffset::east_opt(tz_offset_secs).unwrap_or_else(moscow_tz);
+            let today = Utc::now().with_timezone(&tz).date_naive();
+            let week_ago = today - chrono::Duration::days(7);
+
+            // Same day/week windows as /stats
+            let local_date = |t: &Training| t.date.with_timezone(&tz).date_naive();
+            let today_sets = trainings.iter().filter(|t| local_date(t) == today).count();
+            let week_sets = trainings.iter().filter(|t| local_date(t) > week_ago).count();
+            let balance = Recommender::new(trainings.clone()).get_balance_score();
+            let training_days = Analytics::new(trainings).training_days(tz);
+            let streak = streak_days_with_rest(&training_days, &rest_days, today);
+
+            bot.send_message(msg.chat.id, format_compact_stats(today_sets, week_sets, streak, balance))
                 .await?;
         }
 
-        Command::Remind => {
-            let mut subs = subscribers.lock().await;
-            subs.insert(msg.chat.id);
-            let count = subs.len();
-
-            bot.send_message(
-                msg.chat.id,
-                format!(
-                    "✅ Напоминания включены!\n\n\
-                    Буду напоминать раз в час.\n\
-                    Активных подписчиков: {}",
-                    count
-                )
-            )
-            .reply_markup(make_commands_keyboard())
-            .await?;
+        Command::Records => {
+            let trainings = db.lock().await.get_trainings_for_user(user.id)?;
+            let records = Analytics::new(trainings).personal_records();
+            bot.send_message(msg.chat.id, format_records(&records)).await?;
+        }
 
-            info!("User {} subscribed to reminders", msg.chat.id);
+        Command::Streak => {
+            let (trainings, tz_offset_secs) = {
+                let db = db.lock().await;
+                (db.get_trainings_for_user(user.id)?, user_tz_offset_secs(&db, user.id)?)
+            };
+            let tz = FixedOffset::east_opt(tz_offset_secs).unwrap_or_else(moscow_tz);
+            let today = Utc::now().with_timezone(&tz).date_naive();
+            let analytics = Analytics::new(trainings);
+            let text = format_streaks(analytics.current_streak_at(today, tz), analytics.longest_streak_in(tz));
+            bot.send_message(msg.chat.id, text).await?;
         }
 
-        Command::Stop => {
-            let mut subs = subscribers.lock().await;
-            let was_subscribed = subs.remove(&msg.chat.id);
+        Command::Goal(arg) | Command::GoalReps(arg) => {
+            let trainings = {
+                let db = db.lock().await;
+                db.get_trainings_for_user(user.id)?
+            };
 
-            if was_subscribed {
-                bot.send_message(msg.chat.id, "🔕 Напоминания выключены.")
-                    .reply_markup(make_commands_keyboard())
-                    .await?;
-                info!("User {} unsubscribed from reminders", msg.chat.id);
-            } else {
-                bot.send_message(msg.chat.id, "Напоминания и так выключены.")
-                    .reply_markup(make_commands_keyboard())
-                    .await?;
+            let text = match parse_goal_reps(&arg) {
+                None => "Пример: /goal отжимания на кулаках 50".to_string(),
+                Some((query, target)) => match resolve_logged_exercise(query, &trainings) {
+                    None => format!("Нет записей по упражнению «{}».", query),
+                    Some(exercise) => {
+                        let best = trainings.iter()
+                            .filter(|t| t.exercise == exercise)
+                            .map(|t| t.reps)
+                            .max()
+                            .unwrap_or(0);
+                        if best >= target {
+                            format!("🏆 {}: {} повт. уже есть (рекорд {})", exercise, target, best)
+                        } else {
+                            match ProgressPredictor::train(&trainings, &exercise) {
+                                None => format!("{}: мало данных для прогноза (нужно от 3 подходов)", exercise),
+                                Some(predictor) => match predictor.days_until(target) {
+                                    Some(days) => {
+                                        let today = Utc::now().with_timezone(&user_tz(user.tz_offset_minutes)).date_naive();
+                                        format_goal_eta(&exercise, target, days, today)
+                                    }
+                                    None => format!(
+                                        "📉 {}: тренд не растёт (≈ {:.0} повт.), прогноз до {} невозможен",
+                                        exercise, predictor.current_level(), target
+                                    ),
+                                },
+                            }
+                        }
+                    }
+                },
+            };
+            bot.send_message(msg.chat.id, text)
+                .reply_markup(make_commands_keyboard())
+                .await?;
+        }
+
+        Command::Day(arg) => {
+            let day = {
+                let db = db.lock().await;
+                let date = match arg.trim() {
+                    "" => Ok(user_today(&db, user.id)?),
+                    arg => chrono::NaiveDate::parse_from_str(arg, "%Y-%m-%d"),
+                };
+                match date {
+                    Ok(date) => Some((date, db.get_trainings_on_day(Some(user.id), date, user_tz_offset_secs(&db, user.id)?)?)),
+                    Err(_) => None,
+                }
+            };
+            match day {
+                // A busy day's JSON exceeds Telegram's message limit, so it goes as a file
+                Some((date, trainings)) => {
+                    let json = serde_json::to_vec_pretty(&trainings)?;
+                    let file_name = format!("trainings-{}.json", date.format("%Y-%m-%d"));
+                    bot.send_document(msg.chat.id, InputFile::memory(json).file_name(file_name))
+                        .caption(format!("📄 {}: {} подх.", date.format("%d.%m.%Y"), trainings.len()))
+                        .await?;
+                }
+                None => {
+                    bot.send_message(msg.chat.id, "Дата в формате ГГГГ-ММ-ДД, например /day 2026-01-05").await?;
+                }
             }
         }
 
-        Command::Tip => {
-            let tip = tips::get_random_tip();
-            let text = format!(
-                "📖 Совет из книги\n\"You Are Your Own Gym\"\n\n{}",
-                tips::format_tip(tip)
-            );
-            bot.send_message(msg.chat.id, text)
+        Command::Body => {
+            let trainings = {
+                let db = db.lock().await;
+                db.get_trainings_for_user(user.id)?
+            };
+            let tracker = MuscleTracker::from_trainings(&trainings);
+
+            bot.send_message(msg.chat.id, format!("🧍 Нагрузка сегодня\n\n{}", tracker.get_body_map()))
                 .reply_markup(make_commands_keyboard())
                 .await?;
         }
 
+        Command::Muscle(arg) => {
+            let group = MuscleGroup::from_ru(&arg).filter(|g| *g != MuscleGroup::FullBody);
+            let text = match group {
+                Some(group) => {
+                    let trainings = db.lock().await.get_trainings_for_user(user.id)?;
+                    let weeks = Analytics::new(trainings).muscle_volume_by_week(group, BALANCE_TREND_WEEKS);
+                    format_muscle_trend(group, &weeks)
+                }
+                None => {
+                    let names: Vec<_> = MuscleGroup::all()
+                        .iter()
+                        .filter(|g| **g != MuscleGroup::FullBody)
+                        .map(|g| g.name_ru())
+                        .collect();
+                    format!("Группа мышц: /muscle спина\nВарианты: {}", names.join(", "))
+                }
+            };
+            bot.send_message(msg.chat.id, text).await?;
+        }
+
+        Command::BalanceTrend => {
+            let trainings = db.lock().await.get_trainings_for_user(user.id)?;
+            let weeks = Analytics::new(trainings).balance_score_by_week(BALANCE_TREND_WEEKS);
+            bot.send_message(msg.chat.id, format_balance_trend(&weeks)).await?;
+        }
+
         Command::Balance => {
             let trainings = {
                 let db = db.lock().await;
@@ -684,7 +3182,8 @@ async fn handle_callback(
     dialogue: MyDialogue,
     db: Arc<Mutex<Database>>,
     config: Arc<BotConfig>,
-    _subscribers: Subscribers,
+    subscribers: Subscribers,
+    runtime: Runtime,
 ) -> HandlerResult {
     // Get user_id for this callback
     let chat_id = q.message.as_ref().map(|m| m.chat().id.0).unwrap_or(0);
@@ -713,9 +3212,107 @@ async fn handle_callback(
                 ).await?;
             }
         }
+        // Setup wizard step 1: timezone chosen
+        else if let Some(minutes) = data.strip_prefix("setup_tz:")
+            && let Ok(tz_offset_minutes) = minutes.parse::<i32>()
+            && let Some(State::SetupTimezone { user_id }) = dialogue.get().await? {
+                let base_exercises: Vec<String> = get_base_exercises()
+                    .iter()
+                    .map(|ex| ex.id.to_string())
+                    .collect();
+                if let Some(msg) = &q.message {
+                    bot.edit_message_text(
+                        msg.chat().id,
+                        msg.id(),
+                        "⚙️ Настройка (2/3)\n\nБазовые упражнения (нажми, чтобы убрать/вернуть):"
+                    )
+                    .reply_markup(make_setup_base_keyboard(&base_exercises))
+                    .await?;
+                }
+                dialogue.update(State::SetupBase { user_id, tz_offset_minutes, base_exercises }).await?;
+            }
+        // Setup wizard step 2: toggle a base exercise
+        else if let Some(exercise_id) = data.strip_prefix("setup_base:")
+            && let Some(State::SetupBase { user_id, tz_offset_minutes, mut base_exercises }) = dialogue.get().await? {
+                toggle_base_exercise(&mut base_exercises, exercise_id);
+                if let Some(msg) = &q.message {
+                    bot.edit_message_reply_markup(msg.chat().id, msg.id())
+                        .reply_markup(make_setup_base_keyboard(&base_exercises))
+                        .await?;
+                }
+                dialogue.update(State::SetupBase { user_id, tz_offset_minutes, base_exercises }).await?;
+            }
+        // Setup wizard step 2 done
+        else if data == "setup_base_done"
+            && let Some(State::SetupBase { user_id, tz_offset_minutes, base_exercises }) = dialogue.get().await? {
+                if base_exercises.is_empty() {
+                    bot.answer_callback_query(q.id)
+                        .text("Выбери хотя бы одно упражнение")
+                        .await?;
+                    return Ok(());
+                }
+                if let Some(msg) = &q.message {
+                    bot.edit_message_text(msg.chat().id, msg.id(), "⚙️ Настройка (3/3)\n\nНапоминать о тренировке?")
+                        .reply_markup(make_setup_reminders_keyboard())
+                        .await?;
+                }
+                dialogue.update(State::SetupReminders { user_id, tz_offset_minutes, base_exercises }).await?;
+            }
+        // Setup wizard step 3: reminders chosen, save settings
+        else if let Some(choice) = data.strip_prefix("setup_remind:")
+            && let Some(State::SetupReminders { user_id, tz_offset_minutes, base_exercises }) = dialogue.get().await? {
+                // All exercises selected is stored as "no restriction"
+                let base_exercises = if base_exercises.len() == get_base_exercises().len() {
+                    Vec::new()
+                } else {
+                    base_exercises
+                };
+                let settings = UserSettings {
+                    user_id,
+                    tz_offset_minutes,
+                    base_exercises,
+                    reminders_enabled: choice == "on",
+                };
+                db.lock().await.save_settings(&settings)?;
+                dialogue.reset().await?;
+
+                if let Some(msg) = &q.message {
+                    if settings.reminders_enabled {
+                        db.lock().await.add_subscriber(msg.chat().id.0)?;
+                        subscribers.lock().await.insert(msg.chat().id, next_reminder_due(Utc::now()));
+                    }
+                    bot.edit_message_text(msg.chat().id, msg.id(), format_settings_summary(&settings))
+                        .await?;
+                    bot.send_message(msg.chat().id, "Готово! Начнём?")
+                        .reply_markup(make_commands_keyboard())
+                        .await?;
+                }
+                info!("User {} completed setup", user_id);
+            }
+        // Snooze: push only this chat's next reminder
+        else if let Some(minutes) = data.strip_prefix("snooze:")
+            && let Ok(minutes) = minutes.parse::<i64>() {
+                let chat_id_tg = ChatId(chat_id);
+                let snoozed = match subscribers.lock().await.get_mut(&chat_id_tg) {
+                    Some(due) => {
+                        *due = Utc::now() + chrono::Duration::minutes(minutes);
+                        true
+                    }
+                    None => false,
+                };
+                let text = if snoozed {
+                    format!("😴 Напомню через {} минут", minutes)
+                } else {
+                    "Напоминания выключены. Включить: /remind".to_string()
+                };
+                if let Some(msg) = &q.message {
+                    bot.edit_message_text(msg.chat().id, msg.id(), text).await?;
+                }
+            }
         // Handle "show all exercises" callback
         else if data == "show_all" {
-            let keyboard = make_exercises_keyboard();
+            let base = user_base_exercises(&*db.lock().await, user.id)?;
+            let keyboard = make_exercises_keyboard(&base);
             if let Some(msg) = &q.message {
                 bot.edit_message_text(msg.chat().id, msg.id(), "Выбери упражнение:")
                     .reply_markup(keyboard)
@@ -738,14 +3335,10 @@ async fn handle_callback(
                 match cmd {
                     "train" => {
                         // Get recommendation based on muscle balance
-                        let trainings = {
-                            let db = db.lock().await;
-                            db.get_trainings_for_user(user.id)?
-                        };
-                        let recommender = Recommender::new(trainings.clone());
+                        let (trainings, recommender) = training_recommender(&*db.lock().await, &user)?;
 
                         if let Some(rec) = recommender.get_recommendation() {
-                            let goal_info = GoalCalculator::calculate(&trainings, rec.exercise.name)
+                            let goal_info = GoalCalculator::calculate_in(&trainings, rec.exercise.name, user_tz(user.tz_offset_minutes))
                                 .map(|g| format!("\n\n📊 {}", g.format_short()))
                                 .unwrap_or_default();
 
@@ -820,80 +3413,26 @@ async fn handle_callback(
                                 .reply_markup(keyboard)
                                 .await?;
                         } else {
-                            let keyboard = make_exercises_keyboard();
+                            let base = user_base_exercises(&*db.lock().await, user.id)?;
+                            let keyboard = make_exercises_keyboard(&base);
                             bot.send_message(chat_id_tg, "Выбери упражнение:")
                                 .reply_markup(keyboard)
                                 .await?;
                         }
                     }
                     "stats" => {
-                        let trainings = {
+                        let now = Utc::now();
+                        let (trainings, total) = {
                             let db = db.lock().await;
-                            db.get_trainings_for_user(user.id)?
+                            let trainings = db.get_trainings_in_range(
+                                user.id,
+                                now - chrono::Duration::days(STATS_WINDOW_DAYS),
+                                now + chrono::Duration::days(1),
+                            )?;
+                            (trainings, db.count_trainings_for_user(user.id)?)
                         };
 
-                        let total = trainings.len();
-                        let today = Utc::now().with_timezone(&moscow_tz()).date_naive();
-                        let week_ago = today - chrono::Duration::days(7);
-                        let month_ago = today - chrono::Duration::days(30);
-
-                        let today_trainings: Vec<_> = trainings.iter()
-                            .filter(|t| t.date.with_timezone(&moscow_tz()).date_naive() == today)
-                            .collect();
-                        let week_trainings: Vec<_> = trainings.iter()
-                            .filter(|t| t.date.with_timezone(&moscow_tz()).date_naive() > week_ago)
-                            .collect();
-                        let month_trainings: Vec<_> = trainings.iter()
-                            .filter(|t| t.date.with_timezone(&moscow_tz()).date_naive() > month_ago)
-                            .collect();
-
-                        let today_time: i32 = today_trainings.iter().filter_map(|t| t.duration_secs).sum();
-                        let week_time: i32 = week_trainings.iter().filter_map(|t| t.duration_secs).sum();
-                        let month_time: i32 = month_trainings.iter().filter_map(|t| t.duration_secs).sum();
-
-                        let mut text = format!(
-                            "📈 Статистика\n\n\
-                            Всего: {} подх.\n\
-                            Сегодня: {} ({})\n\
-                            Неделя: {} ({})\n\
-                            Месяц: {} ({})\n",
-                            total,
-                            today_trainings.len(), format_duration(today_time),
-                            week_trainings.len(), format_duration(week_time),
-                            month_trainings.len(), format_duration(month_time)
-                        );
-
-                        // Group today's trainings by exercise
-                        if !today_trainings.is_empty() {
-                            text.push_str("\n📊 Сегодня:\n");
-                            let mut exercise_stats: std::collections::HashMap<&str, (usize, i32, i32, i32)> = std::collections::HashMap::new();
-                            for t in &today_trainings {
-                                let duration = t.duration_secs.unwrap_or(0);
-                                let entry = exercise_stats.entry(&t.exercise).or_insert((0, 0, 0, 0));
-                                entry.0 += 1;
-                                entry.1 += t.reps;
-                                entry.2 += duration;
-                                entry.3 = entry.3.max(duration);
-                            }
-                            for (exercise, (sets, reps, total_time, max_time)) in exercise_stats {
-                                let is_timed = find_exercise_by_name(exercise)
-                                    .map(|ex| ex.is_timed)
-                                    .unwrap_or(false);
-                                if is_timed {
-                                    text.push_str(&format!(
-                                        "• {} - {} подх., макс. {}с, всего {}\n",
-                                        exercise, sets, max_time, format_duration(total_time)
-                                    ));
-                                } else {
-                                    text.push_str(&format!(
-                                        "• {} - {} подх., {} повт., {}\n",
-                                        exercise, sets, reps, format_duration(total_time)
-                                    ));
-                                }
-                            }
-                        }
-
-                        bot.send_message(chat_id_tg, text)
+                        bot.send_message(chat_id_tg, format_stats_summary(&trainings, total, now, user_tz(user.tz_offset_minutes), user.lang))
                             .reply_markup(make_commands_keyboard())
                             .await?;
                     }
@@ -909,19 +3448,47 @@ async fn handle_callback(
                             .await?;
                     }
                     "tip" => {
-                        let tip = tips::get_random_tip();
-                        let text = format!(
-                            "📖 Совет из книги\n\"You Are Your Own Gym\"\n\n{}",
-                            tips::format_tip(tip)
-                        );
-                        bot.send_message(chat_id_tg, text)
-                            .reply_markup(make_commands_keyboard())
+                        let tip = next_tip(chat_id_tg, &runtime).await;
+                        bot.send_message(chat_id_tg, format_book_tip(tip))
+                            .reply_markup(make_tip_keyboard())
                             .await?;
                     }
                     _ => {}
                 }
             }
         }
+        // Rest timer offered after a set
+        else if let Some(secs) = data.strip_prefix("rest:")
+            && let Some(secs) = parse_rest_secs(secs)
+            && let Some(msg) = &q.message {
+                let replaced = start_rest_timer(bot.clone(), msg.chat().id, runtime.clone(), secs).await;
+                let note = if replaced { " (предыдущий таймер сброшен)" } else { "" };
+                bot.send_message(msg.chat().id, format!("⏱ Отдых {}{}", format_duration(secs as i32), note)).await?;
+            }
+        // Restore a set removed with /undo
+        else if let Some(id) = data.strip_prefix("restore:")
+            && let Ok(id) = id.parse::<i64>()
+            && let Some(msg) = &q.message {
+                let restored = db.lock().await.restore_training(id, user.id)?;
+                let text = if restored {
+                    "↩️ Подход возвращён"
+                } else {
+                    "Этот подход уже не вернуть"
+                };
+                bot.edit_message_text(msg.chat().id, msg.id(), text).await?;
+            }
+        // Tip from a chosen category
+        else if let Some(category) = data.strip_prefix("tipcat:")
+            && let Some(msg) = &q.message {
+                let tip = tips::TipCategory::from_id(category).and_then(tips::get_random_tip_by_category);
+                let text = match tip {
+                    Some(tip) => format_book_tip(tip),
+                    None => "В этой категории пока нет советов — попробуй другую".to_string(),
+                };
+                bot.send_message(msg.chat().id, text)
+                    .reply_markup(make_tip_keyboard())
+                    .await?;
+            }
         // Handle exercise selection
         else if let Some(exercise_id) = data.strip_prefix("ex:")
             && let Some(exercise) = find_exercise(exercise_id) {
@@ -931,12 +3498,13 @@ async fn handle_callback(
                     exercise_name: exercise.name.to_string(),
                     user_id: user.id,
                 }).await?;
+                touch_session(bot.clone(), dialogue.clone(), db.clone(), runtime.clone(), user.id).await;
 
                 // Get trainings and calculate fatigue-aware goal
                 let goal_info = {
                     let db = db.lock().await;
                     let trainings = db.get_trainings_for_user(user.id)?;
-                    GoalCalculator::calculate(&trainings, exercise.name)
+                    GoalCalculator::calculate_in(&trainings, exercise.name, user_tz(user.tz_offset_minutes))
                         .map(|g| format!("\n\n📊 Прогресс:\n{}", g.format()))
                         .unwrap_or_default()
                 };
@@ -960,9 +3528,73 @@ async fn handle_callback(
 
                 if let Some(msg) = &q.message {
                     bot.edit_message_text(msg.chat().id, msg.id(), text)
+                        .reply_markup(make_cancel_keyboard())
+                        .await?;
+                }
+            }
+        // Another set of the same exercise: reuse pulse before, restart the timer
+        else if let Some((exercise_id, pulse_before)) = parse_more_set(data)
+            && let Some(exercise) = find_exercise(exercise_id) {
+                dialogue.update(State::WaitingForReps {
+                    exercise_id: exercise_id.to_string(),
+                    exercise_name: exercise.name.to_string(),
+                    pulse_before,
+                    start_time: Utc::now(),
+                    user_id: user.id,
+                }).await?;
+                touch_session(bot.clone(), dialogue.clone(), db.clone(), runtime.clone(), user.id).await;
+
+                if let Some(msg) = &q.message {
+                    bot.edit_message_reply_markup(msg.chat().id, msg.id())
+                        .reply_markup(make_commands_keyboard())
+                        .await?;
+                    let question = if exercise.is_timed { "Сколько секунд продержался?" } else { "Сколько повторов?" };
+                    bot.send_message(
+                        msg.chat().id,
+                        format!("➕ Ещё подход: {}\n\nВыполняй!\n\n{}", exercise.name, question),
+                    )
+                    .reply_markup(make_cancel_keyboard())
+                    .await?;
+                }
+            }
+        // "❌ Отмена" under a dialogue prompt
+        else if data == "cancel" {
+            let text = cancel_reply(dialogue.get().await?.as_ref());
+            dialogue.reset().await?;
+            if let Some(msg) = &q.message {
+                bot.edit_message_reply_markup(msg.chat().id, msg.id()).await?;
+                bot.send_message(msg.chat().id, text)
+                    .reply_markup(make_commands_keyboard())
+                    .await?;
+            }
+        }
+        // Note step skipped with the button
+        else if data == "skip_note"
+            && let Some(State::WaitingForNotes { exercise_id, exercise_name, pulse_before, reps, duration_secs, pulse_after, user_id }) = dialogue.get().await? {
+                dialogue.update(State::WaitingForRpe {
+                    exercise_id,
+                    exercise_name,
+                    pulse_before,
+                    reps,
+                    duration_secs,
+                    pulse_after,
+                    notes: None,
+                    user_id,
+                }).await?;
+                if let Some(msg) = &q.message {
+                    bot.edit_message_reply_markup(msg.chat().id, msg.id()).await?;
+                    bot.send_message(msg.chat().id, RPE_PROMPT)
+                        .reply_markup(make_cancel_keyboard())
                         .await?;
                 }
             }
+        // Finish the exercise: keep only the command buttons
+        else if data == "set_done"
+            && let Some(msg) = &q.message {
+                bot.edit_message_reply_markup(msg.chat().id, msg.id())
+                    .reply_markup(make_commands_keyboard())
+                    .await?;
+            }
     }
 
     bot.answer_callback_query(q.id).await?;
@@ -976,6 +3608,7 @@ async fn handle_message(
     db: Arc<Mutex<Database>>,
     config: Arc<BotConfig>,
     _subscribers: Subscribers,
+    runtime: Runtime,
 ) -> HandlerResult {
     let state = dialogue.get().await?.unwrap_or_default();
 
@@ -989,17 +3622,19 @@ async fn handle_message(
                 };
 
                 if let Some(owner) = owner {
-                    let from_username = msg.from.as_ref()
-                        .and_then(|u| u.username.as_ref())
+                    let username = msg.from.as_ref().and_then(|u| u.username.as_deref());
+                    let first_name = msg.from.as_ref().map(|u| u.first_name.as_str());
+                    // Kept in the DB so /approve works after a restart
+                    db.lock().await.add_pending_request(msg.chat.id.0, username, first_name, text)?;
+
+                    let from_username = username
                         .map(|u| format!("@{}", u))
                         .unwrap_or_else(|| "без username".to_string());
-                    let from_name = msg.from.as_ref()
-                        .map(|u| u.first_name.as_str())
-                        .unwrap_or("Аноним");
+                    let from_name = first_name.unwrap_or("Аноним");
 
                     let forward_text = format!(
-                        "📩 Запрос на доступ от {} ({}):\n\n{}",
-                        from_username, from_name, text
+                        "📩 Запрос на доступ от {} ({}):\n\n{}\n\n/approve {} — открыть доступ\n/deny {} — отклонить",
+                        from_username, from_name, text, msg.chat.id.0, msg.chat.id.0
                     );
 
                     bot.send_message(ChatId(owner.chat_id), forward_text).await?;
@@ -1035,18 +3670,31 @@ async fn handle_message(
                         user_id,
                     }).await?;
 
+                    // Compare with the usual resting pulse before this reading is saved
+                    let baseline = Analytics::new(db.lock().await.get_trainings_for_user(user_id)?)
+                        .resting_pulse_baseline();
+                    let readiness = match baseline {
+                        Some(baseline) if resting_pulse_elevated(pulse, baseline) => format!(
+                            "\n⚠️ Пульс выше обычного (~{:.0}) — возможно, стоит взять день отдыха",
+                            baseline
+                        ),
+                        _ => String::new(),
+                    };
+
                     let response = if is_timed {
                         format!(
-                            "Пульс: {} уд/мин\n\nВыполняй {}!\n\nСколько секунд продержался?",
-                            pulse, exercise_name
+                            "Пульс: {} уд/мин{}\n\nВыполняй {}!\n\nСколько секунд продержался?",
+                            pulse, readiness, exercise_name
                         )
                     } else {
                         format!(
-                            "Пульс: {} уд/мин\n\nВыполняй {}!\n\nСколько повторов?",
-                            pulse, exercise_name
+                            "Пульс: {} уд/мин{}\n\nВыполняй {}!\n\nСколько повторов?",
+                            pulse, readiness, exercise_name
                         )
                     };
-                    bot.send_message(msg.chat.id, response).await?;
+                    bot.send_message(msg.chat.id, response)
+                        .reply_markup(make_cancel_keyboard())
+                        .await?;
                 } else {
                     bot.send_message(msg.chat.id, "Введи пульс (число)").await?;
                 }
@@ -1082,15 +3730,24 @@ async fn handle_message(
                             "⏱ {} - {}с\n\nПульс после упражнения?",
                             exercise_name, duration_secs
                         );
-                        bot.send_message(msg.chat.id, response).await?;
+                        bot.send_message(msg.chat.id, response)
+                            .reply_markup(make_cancel_keyboard())
+                            .await?;
                     } else {
                         bot.send_message(msg.chat.id, "Введи число секунд").await?;
                     }
                 } else {
                     // For rep-based exercises: require a number
                     if let Ok(reps) = text.trim().parse::<i32>() {
-                        let now = Utc::now();
-                        let duration_secs = (now - start_time).num_seconds() as i32;
+                        if let Err(message) = validate_reps(reps) {
+                            bot.send_message(msg.chat.id, message).await?;
+                            return Ok(());
+                        }
+                        let (duration_secs, clamped) = finalize_duration(
+                            (Utc::now() - start_time).num_seconds(),
+                            SET_PREP_SECS,
+                            config.max_set_duration_secs,
+                        );
 
                         dialogue.update(State::WaitingForPulseAfter {
                             exercise_id,
@@ -1101,11 +3758,20 @@ async fn handle_message(
                             user_id,
                         }).await?;
 
-                        let response = format!(
-                            "{} - {} повторов за {}с\n\nПульс после упражнения?",
+                        let mut response = format!(
+                            "{} - {} повторов за {}с",
                             exercise_name, reps, duration_secs
                         );
-                        bot.send_message(msg.chat.id, response).await?;
+                        if clamped {
+                            response.push_str(&format!(
+                                "\n⚠️ Похоже, таймер забыт: время ограничено {}",
+                                format_duration(duration_secs)
+                            ));
+                        }
+                        response.push_str("\n\nПульс после упражнения?");
+                        bot.send_message(msg.chat.id, response)
+                            .reply_markup(make_cancel_keyboard())
+                            .await?;
                     } else {
                         bot.send_message(msg.chat.id, "Введи число повторов").await?;
                     }
@@ -1121,6 +3787,45 @@ async fn handle_message(
                         return Ok(());
                     }
 
+                    dialogue.update(State::WaitingForNotes {
+                        exercise_id,
+                        exercise_name,
+                        pulse_before,
+                        reps,
+                        duration_secs,
+                        pulse_after,
+                        user_id,
+                    }).await?;
+                    bot.send_message(msg.chat.id, "Заметка? (или -)")
+                        .reply_markup(make_skip_note_keyboard())
+                        .await?;
+                } else {
+                    bot.send_message(msg.chat.id, "Введи пульс (число)").await?;
+                }
+            }
+        }
+
+        State::WaitingForNotes { exercise_id, exercise_name, pulse_before, reps, duration_secs, pulse_after, user_id } => {
+            if let Some(text) = msg.text() {
+                dialogue.update(State::WaitingForRpe {
+                    exercise_id,
+                    exercise_name,
+                    pulse_before,
+                    reps,
+                    duration_secs,
+                    pulse_after,
+                    notes: parse_note(text),
+                    user_id,
+                }).await?;
+                bot.send_message(msg.chat.id, RPE_PROMPT)
+                    .reply_markup(make_cancel_keyboard())
+                    .await?;
+            }
+        }
+
+        State::WaitingForRpe { exercise_id, exercise_name, pulse_before, reps, duration_secs, pulse_after, notes, user_id } => {
+            if let Some(text) = msg.text() {
+                if let Some(rpe) = parse_rpe(text) {
                     // Check if exercise is timed
                     let is_timed = find_exercise(&exercise_id)
                         .map(|ex| ex.is_timed)
@@ -1136,12 +3841,18 @@ async fn handle_message(
                         duration_secs: Some(duration_secs),
                         pulse_before: Some(pulse_before),
                         pulse_after: Some(pulse_after),
-                        notes: None,
+                        rpe,
+                        notes: notes.clone(),
                         user_id: Some(user_id),
+                        context: db.lock().await
+                            .get_user_by_chat_id(msg.chat.id.0)?
+                            .and_then(|u| u.current_context),
+                        category: find_exercise(&exercise_id).map(|ex| ex.category),
+                        is_timed: Some(is_timed),
                     };
 
                     // Count today's sets, total time, personal record, and ML prediction
-                    let (today_sets, total_time, personal_record, is_new_record, ml_prediction) = {
+                    let (today_sets, total_time, personal_record, is_new_record, ml_prediction, achieved_goals) = {
                         let db = db.lock().await;
 
                         // Get previous record BEFORE adding current training
@@ -1162,15 +3873,13 @@ async fn handle_message(
                         let had_previous_attempts = trainings_before.iter()
                             .any(|t| t.exercise == exercise_name);
 
-                        // Now add the training
-                        db.add_training(&training, user_id)?;
-
-                        let trainings = db.get_trainings_for_user(user_id)?;
-                        let today = Utc::now().with_timezone(&moscow_tz()).date_naive();
+                        // Now add the training; history stays newest first
+                        let mut trainings = trainings_before;
+                        trainings.insert(0, db.add_training_returning(&training, user_id)?);
 
                         // Today's stats
-                        let today_exercises: Vec<_> = trainings.iter()
-                            .filter(|t| t.date.with_timezone(&moscow_tz()).date_naive() == today)
+                        let today_exercises: Vec<_> = db.get_trainings_today_minutes(user_id, db.user_tz_offset_minutes(user_id)?)?
+                            .into_iter()
                             .filter(|t| t.exercise == exercise_name)
                             .collect();
 
@@ -1183,18 +3892,38 @@ async fn handle_message(
                         let current_value = if is_timed { duration_secs } else { reps };
                         let is_new = had_previous_attempts && current_value > previous_record;
                         let record = current_value.max(previous_record);
+                        let achieved = db.achieve_goals(user_id, &exercise_name, current_value, training.date)?;
 
                         // ML prediction (only for rep-based exercises with enough data)
                         let prediction = if !is_timed {
-                            ProgressPredictor::train(&trainings, &exercise_name)
-                                .map(|p| p.format_prediction())
+                            ProgressPredictor::train(&trainings, &exercise_name).map(|p| {
+                                let mut text = p.format_prediction();
+                                if p.detect_plateau() {
+                                    text.push_str(&format_deload_hint(progression(&exercise_id).1));
+                                }
+                                text
+                            })
                         } else {
                             None
                         };
 
-                        (sets, time, record, is_new, prediction)
+                        (sets, time, record, is_new, prediction, achieved)
                     };
 
+                    // Intensity zone needs the user's age; omitted otherwise
+                    let zone_info = db.lock().await
+                        .get_user_by_chat_id(msg.chat.id.0)?
+                        .and_then(|u| u.age)
+                        .map(|age| format!("\nЗона: {}", zone(pulse_after, age).name_ru()))
+                        .unwrap_or_default();
+
+                    let rpe_info = rpe
+                        .map(|r| format!("\nТяжесть: {}/10", r))
+                        .unwrap_or_default();
+                    let note_info = notes
+                        .map(|n| format!("\n📝 {}", n))
+                        .unwrap_or_default();
+
                     let pulse_diff = pulse_after - pulse_before;
                     let pulse_indicator = if pulse_diff > 30 { "+++" } else if pulse_diff > 15 { "++" } else if pulse_diff > 0 { "+" } else { "-" };
 
@@ -1220,6 +3949,10 @@ async fn handle_message(
                         format!("Рекорд: {} повторов", personal_record)
                     };
 
+                    let rest_info = find_exercise(&exercise_id)
+                        .map(|ex| format!("\n{}", format_rest_window(ex)))
+                        .unwrap_or_default();
+
                     // Build response with optional ML prediction
                     let ml_section = ml_prediction
                         .map(|p| format!("\n\n{}", p))
@@ -1228,20 +3961,25 @@ async fn handle_message(
                     let response = format!(
                         "Записано!\n\n\
                         {}\n\
-                        Пульс: {} -> {} ({}{}) уд/мин\n\n\
+                        Пульс: {} -> {} ({}{}) уд/мин{}{}{}\n\n\
                         {}\n\
-                        Сегодня: {} подх., {}{}",
+                        Сегодня: {} подх., {}{}{}",
                         exercise_info,
-                        pulse_before, pulse_after, pulse_indicator, pulse_diff,
+                        pulse_before, pulse_after, pulse_indicator, pulse_diff, zone_info, rpe_info, note_info,
                         record_info,
                         today_sets, time_str,
+                        rest_info,
                         ml_section
                     );
 
                     bot.send_message(msg.chat.id, response)
-                        .reply_markup(make_commands_keyboard())
+                        .reply_markup(make_after_set_keyboard(&exercise_id, pulse_before))
                         .await?;
 
+                    if !achieved_goals.is_empty() {
+                        bot.send_message(msg.chat.id, format_goals_achieved(&achieved_goals)).await?;
+                    }
+
                     // Check if base program is now complete (this was the last exercise)
                     {
                         let db = db.lock().await;
@@ -1254,24 +3992,30 @@ async fn handle_message(
                             bot.send_message(msg.chat.id, summary_msg).await?;
 
                             // Show bonus recommendation
-                            if let Some(rec) = recommender.get_recommendation() {
-                                if rec.is_bonus {
-                                    let bonus_msg = format_bonus_recommendation(&rec, &trainings);
-                                    bot.send_message(msg.chat.id, bonus_msg)
-                                        .reply_markup(make_bonus_keyboard(&rec))
-                                        .await?;
-                                }
+                            if let Some(rec) = recommender.get_recommendation()
+                                && rec.is_bonus
+                            {
+                                let tz = user_tz(db.user_tz_offset_minutes(user_id)?);
+                                let bonus_msg = format_bonus_recommendation(&rec, &trainings, tz);
+                                bot.send_message(msg.chat.id, bonus_msg)
+                                    .reply_markup(make_bonus_keyboard(&rec))
+                                    .await?;
                             }
                         }
                     }
 
                     dialogue.reset().await?;
+                    touch_session(bot.clone(), dialogue.clone(), db.clone(), runtime.clone(), user_id).await;
                 } else {
-                    bot.send_message(msg.chat.id, "Введи пульс (число)").await?;
+                    bot.send_message(msg.chat.id, "Оцени тяжесть числом от 1 до 10 или «-», чтобы пропустить").await?;
                 }
             }
         }
 
+        State::SetupTimezone { .. } | State::SetupBase { .. } | State::SetupReminders { .. } => {
+            bot.send_message(msg.chat.id, "Выбери вариант кнопкой выше 👆").await?;
+        }
+
         State::Start => {
             // Check if user exists, if not - might need registration check
             let chat_id = msg.chat.id.0;
@@ -1285,10 +4029,8 @@ async fn handle_message(
 
             match access {
                 AccessResult::LimitReached => {
-                    let text = "Бот достиг лимита пользователей (10).\n\n\
-                        Напиши сообщение ниже - я передам его владельцу для обсуждения доступа.";
-                    bot.send_message(msg.chat.id, text).await?;
-                    dialogue.update(State::WaitingForOwnerMessage).await?;
+                    let denied = db.lock().await.get_pending_request(chat_id)?.is_some_and(|r| r.denied);
+                    reply_limit_reached(&bot, msg.chat.id, &dialogue, denied, config.max_users).await?;
                 }
                 _ => {
                     // User is registered, suggest /train
@@ -1347,17 +4089,920 @@ mod tests {
         assert_eq!(REMINDER_INTERVAL_SECS, 3600);
     }
 
+    #[test]
+    fn test_due_subscribers_respects_snooze() {
+        let now = Utc::now();
+        let mut subs = HashMap::new();
+        subs.insert(ChatId(1), now - chrono::Duration::seconds(1));
+        subs.insert(ChatId(2), now + chrono::Duration::minutes(SNOOZE_MINUTES));
+
+        assert_eq!(due_subscribers(&subs, now), vec![ChatId(1)]);
+        assert_eq!(due_subscribers(&subs, now + chrono::Duration::minutes(SNOOZE_MINUTES)).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stop_all_notifications() {
+        let db = Database::open(":memory:").unwrap();
+        let user = db.get_or_create_user(1, None, None).unwrap();
+        db.save_settings(&UserSettings {
+            user_id: user.id,
+            tz_offset_minutes: 180,
+            base_exercises: vec![],
+            reminders_enabled: true,
+        }).unwrap();
+        let db = Mutex::new(db);
+        db.lock().await.add_subscriber(1).unwrap();
+        let subscribers: Subscribers = Arc::new(Mutex::new(HashMap::new()));
+        subscribers.lock().await.insert(ChatId(1), Utc::now());
+        let runtime: Runtime = Arc::new(Mutex::new(ChatRuntime::default()));
+
+        let stopped = stop_all_notifications(ChatId(1), user.id, &db, &subscribers, &runtime).await.unwrap();
+        assert_eq!(stopped, vec!["напоминания раз в час"]);
+        assert!(subscribers.lock().await.is_empty());
+        assert!(db.lock().await.get_subscribers().unwrap().is_empty());
+        assert!(!db.lock().await.get_settings(user.id).unwrap().unwrap().reminders_enabled);
+
+        // Nothing left to stop
+        let stopped = stop_all_notifications(ChatId(1), user.id, &db, &subscribers, &runtime).await.unwrap();
+        assert!(stopped.is_empty());
+    }
+
+    #[test]
+    fn test_exercise_button_label_with_target() {
+        let pushups = find_exercise("pushups_fist").unwrap();
+        assert_eq!(exercise_button_label(pushups, None), "💪 отжимания на кулаках");
+        assert_eq!(exercise_button_label(pushups, Some(13)), "💪 отжимания на кулаках ~13");
+
+        let plank = find_exercise("plank_elbows").unwrap();
+        assert!(exercise_button_label(plank, Some(45)).ends_with(" ~45с"));
+    }
+
+    #[test]
+    fn test_exercise_button_label_fits_limit() {
+        for ex in get_base_exercises() {
+            let label = exercise_button_label(ex, Some(99999));
+            assert!(label.chars().count() <= MAX_BUTTON_LABEL_CHARS, "too long: {}", label);
+            assert!(label.ends_with("~99999") || label.ends_with("~99999с"), "{}", label);
+        }
+    }
+
+    #[test]
+    fn test_tip_keyboard_has_all_categories() {
+        let keyboard = make_tip_keyboard();
+        let categories: Vec<_> = keyboard.inline_keyboard.iter()
+            .flatten()
+            .filter_map(|button| match &button.kind {
+                teloxide::types::InlineKeyboardButtonKind::CallbackData(data) => data.strip_prefix("tipcat:"),
+                _ => None,
+            })
+            .map(|id| tips::TipCategory::from_id(id).unwrap())
+            .collect();
+        assert_eq!(categories, tips::TipCategory::all());
+        assert!(format_book_tip(&tips::TIPS[0]).contains(tips::TIPS[0].text));
+    }
+
+    #[test]
+    fn test_reminder_keyboard_has_snooze() {
+        let keyboard = make_reminder_keyboard(&[], &HashMap::new());
+        let last = keyboard.inline_keyboard.last().unwrap();
+        assert_eq!(last[0].text, "😴 Через 30 мин");
+    }
+
+    fn training_minutes_ago(exercise: &str, minutes_ago: i64) -> Training {
+        Training {
+            id: None,
+            date: Utc::now() - chrono::Duration::minutes(minutes_ago),
+            exercise: exercise.to_string(),
+            sets: 1,
+            reps: 10,
+            duration_secs: Some(30),
+            pulse_before: None,
+            pulse_after: None,
+            rpe: None,
+            notes: None,
+            user_id: None,
+            context: None,
+            category: None,
+            is_timed: None,
+        }
+    }
+
+    #[test]
+    fn test_state_is_logging() {
+        let reps = State::WaitingForReps {
+            exercise_id: "swimmer".to_string(),
+            exercise_name: "пловец".to_string(),
+            pulse_before: 70,
+            start_time: Utc::now(),
+            user_id: 1,
+        };
+        assert!(reps.is_logging());
+        assert!(!State::Start.is_logging());
+        assert!(!State::WaitingForOwnerMessage.is_logging());
+        assert!(!State::SetupTimezone { user_id: 1 }.is_logging());
+    }
+
+    #[test]
+    fn test_latest_session_empty() {
+        assert!(latest_session(&[], SESSION_IDLE_SECS).is_empty());
+    }
+
+    #[test]
+    fn test_latest_session_stops_at_gap() {
+        let trainings = vec![
+            training_minutes_ago("пловец", 5),
+            training_minutes_ago("пловец", 20),
+            training_minutes_ago("отжимания на кулаках", 45),
+            // 2 hours gap - previous session
+            training_minutes_ago("пловец", 165),
+        ];
+        let session = latest_session(&trainings, SESSION_IDLE_SECS);
+        assert_eq!(session.len(), 3);
+        // Newest first
+        assert_eq!(session[0].exercise, "пловец");
+        assert_eq!(session[2].exercise, "отжимания на кулаках");
+    }
+
+    #[test]
+    fn test_format_session_summary() {
+        let trainings = vec![
+            training_minutes_ago("отжимания на кулаках", 5),
+            training_minutes_ago("отжимания на кулаках", 10),
+            training_minutes_ago("пловец", 15),
+        ];
+        let session = latest_session(&trainings, SESSION_IDLE_SECS);
+        let text = format_session_summary(&session);
+        assert!(text.contains("Подходов: 3"), "Summary: {}", text);
+        assert!(text.contains("Время: 1м 30с"), "Summary: {}", text);
+        assert!(text.contains("отжимания на кулаках - 2 подх."), "Summary: {}", text);
+        assert!(text.contains("спина"), "Should list muscles: {}", text);
+    }
+
     #[test]
     fn test_moscow_offset_constant() {
         // UTC+3 = 3 * 3600 = 10800
         assert_eq!(MOSCOW_OFFSET_SECS, 10800);
     }
 
+    #[test]
+    fn test_every_command_has_help_entry() {
+        // format_help skips commands that don't parse, so check each entry is
+        // actually rendered, with its example, inside its own section
+        let help = format_help(Lang::Ru);
+        let section_start = |section: HelpSection| help.find(&format!("\n{}\n", section.title(Lang::Ru))).unwrap();
+        for bot_command in Command::bot_commands() {
+            let cmd = Command::parse(&bot_command.command, "").unwrap_or_else(|_| {
+                panic!("Command {} should parse without arguments", bot_command.command)
+            });
+            let (section, example) = cmd.help_info();
+            let entry = format!("{} — {}\n   {}\n", bot_command.command, bot_command.description, example);
+            let at = help.find(&entry)
+                .unwrap_or_else(|| panic!("Command {} is missing from /help", bot_command.command));
+
+            let start = section_start(section);
+            let end = HelpSection::ALL.iter()
+                .map(|other| section_start(*other))
+                .filter(|other| *other > start)
+                .min()
+                .unwrap_or(help.len());
+            assert!((start..end).contains(&at), "Command {} is outside its section", bot_command.command);
+        }
+    }
+
+    #[test]
+    fn test_format_help_groups_commands() {
+        let help = format_help(Lang::Ru);
+        for section in HelpSection::ALL {
+            assert!(help.contains(section.title(Lang::Ru)), "Missing section: {}", section.title(Lang::Ru));
+        }
+        for bot_command in Command::bot_commands() {
+            assert!(help.contains(&bot_command.command), "Missing command: {}", bot_command.command);
+        }
+        // Training section comes before settings
+        assert!(help.find("/train").unwrap() < help.find("/remind").unwrap());
+    }
+
+    #[test]
+    fn test_format_graph_caption() {
+        let trainings: Vec<_> = [(10, 20), (14, 10), (18, 0)]
+            .into_iter()
+            .map(|(reps, days_ago)| Training {
+                reps,
+                date: Utc::now() - chrono::Duration::days(days_ago),
+                ..training_minutes_ago("пловец", 0)
+            })
+            .collect();
+        let predictor = ProgressPredictor::train(&trainings, "пловец").unwrap();
+        let caption = format_graph_caption("пловец", &predictor);
+        assert!(caption.starts_with("📈 пловец: 10 → 18 повт. за 20 дн."), "{}", caption);
+        assert!(caption.contains("тренд +0.40 повт./день"), "{}", caption);
+    }
+
+    #[test]
+    fn test_parse_rest_secs() {
+        assert_eq!(parse_rest_secs(""), Some(DEFAULT_REST_SECS));
+        assert_eq!(parse_rest_secs(" 60 "), Some(60));
+        assert_eq!(parse_rest_secs("0"), None);
+        assert_eq!(parse_rest_secs("3600"), None);
+        assert_eq!(parse_rest_secs("минута"), None);
+    }
+
+    #[tokio::test]
+    async fn test_rest_timer_replaces_running_one() {
+        let runtime: Runtime = Arc::new(Mutex::new(ChatRuntime::default()));
+        let bot = Bot::new("0:test");
+        let chat = ChatId(42);
+
+        assert!(!start_rest_timer(bot.clone(), chat, runtime.clone(), 600).await);
+        assert!(start_rest_timer(bot.clone(), chat, runtime.clone(), 600).await);
+        assert!(!start_rest_timer(bot, ChatId(43), runtime.clone(), 600).await, "timers are per chat");
+
+        let runtime = runtime.lock().await;
+        assert_eq!(runtime.rest_timers.len(), 2);
+        runtime.rest_timers.values().for_each(|task| task.abort());
+    }
+
+    #[tokio::test]
+    async fn test_next_tip_avoids_recent_repeats() {
+        let runtime: Runtime = Arc::new(Mutex::new(ChatRuntime::default()));
+        let chat = ChatId(42);
+
+        let mut shown: Vec<&str> = Vec::new();
+        for _ in 0..20 {
+            let tip = next_tip(chat, &runtime).await;
+            let window = shown.len().saturating_sub(RECENT_TIPS);
+            assert!(!shown[window..].contains(&tip.text));
+            shown.push(tip.text);
+        }
+        assert_eq!(runtime.lock().await.recent_tips[&chat].len(), RECENT_TIPS);
+    }
+
+    #[test]
+    fn test_format_leaderboard() {
+        assert!(format_leaderboard(&[]).contains("никто"));
+
+        let board = vec![
+            (Some("Анна".to_string()), 12),
+            (None, 9),
+            (Some("Борис".to_string()), 5),
+            (Some("Вера".to_string()), 1),
+        ];
+        let text = format_leaderboard(&board);
+        assert!(text.contains("🥇 Анна — 12 подх."), "{}", text);
+        assert!(text.contains("🥈 Без имени — 9 подх."), "{}", text);
+        assert!(text.contains("🥉 Борис — 5 подх."), "{}", text);
+        assert!(text.contains("4. Вера — 1 подх."), "{}", text);
+    }
+
+    #[test]
+    fn test_format_users() {
+        let db = Database::open(":memory:").unwrap();
+        let owner = db.get_or_create_user(1, Some("ivan"), Some("Иван")).unwrap();
+        let guest = db.get_or_create_user(2, None, Some("Пётр")).unwrap();
+        let anonymous = db.get_or_create_user(3, None, None).unwrap();
+
+        let text = format_users(&[(owner, 42), (guest, 3), (anonymous, 0)]);
+        assert_eq!(
+            text,
+            "👥 Пользователи (3):\n@ivan 👑 — 42 подх.\nПётр — 3 подх.\n3 — 0 подх."
+        );
+    }
+
+    #[test]
+    fn test_parse_chat_id() {
+        assert_eq!(parse_chat_id(" 123456 "), Some(123456));
+        assert_eq!(parse_chat_id("-100123"), Some(-100123));
+        assert_eq!(parse_chat_id("@guest"), None);
+        assert_eq!(parse_chat_id(""), None);
+    }
+
+    #[test]
+    fn test_format_weekly_nudge() {
+        assert_eq!(
+            format_weekly_nudge(1, 4, Weekday::Tue).unwrap(),
+            "🎯 Цель недели: 1/4, осталось 3 трен."
+        );
+        // Friday: 3 days left including today
+        assert!(format_weekly_nudge(2, 4, Weekday::Fri).unwrap().starts_with("🔥 Неделя на исходе: до цели 2 трен. за 3 дн."));
+        assert!(format_weekly_nudge(0, 4, Weekday::Sat).unwrap().starts_with("⚠️"), "behind beyond reach");
+        assert_eq!(format_weekly_nudge(4, 4, Weekday::Sun), None);
+        assert_eq!(format_weekly_nudge(5, 4, Weekday::Wed), None);
+        assert_eq!(format_weekly_status(5, 4, Weekday::Wed), "✅ Цель недели выполнена: 5/4");
+    }
+
+    #[test]
+    fn test_validate_token() {
+        assert_eq!(validate_token(" 123:abc \n").unwrap(), "123:abc");
+        assert!(matches!(validate_token(""), Err(StartupError::EmptyToken)));
+        assert!(matches!(validate_token("  \t"), Err(StartupError::EmptyToken)));
+    }
+
+    #[test]
+    fn test_startup_error_message() {
+        let err = StartupError::DbOpen {
+            path: "/nope/bot.db".to_string(),
+            source: anyhow::anyhow!("unable to open database file"),
+        };
+        assert_eq!(err.to_string(), "cannot open DB at /nope/bot.db: unable to open database file");
+    }
+
+    #[test]
+    fn test_parse_merge_args() {
+        assert_eq!(parse_merge_args(" 111  222 "), Some((111, 222)));
+        assert_eq!(parse_merge_args("111"), None);
+        assert_eq!(parse_merge_args("111 @guest"), None);
+        assert_eq!(parse_merge_args("111 222 333"), None);
+    }
+
+    #[test]
+    fn test_parse_tz_offset() {
+        assert_eq!(parse_tz_offset("+180"), Some(180));
+        assert_eq!(parse_tz_offset(" -300 "), Some(-300));
+        assert_eq!(parse_tz_offset("330"), Some(330));
+        assert_eq!(parse_tz_offset("+3"), None);
+        assert_eq!(parse_tz_offset("900"), None);
+        assert_eq!(parse_tz_offset("москва"), None);
+    }
+
+    #[test]
+    fn test_format_tz_offset() {
+        assert_eq!(format_tz_offset(180), "UTC+3");
+        assert_eq!(format_tz_offset(330), "UTC+5:30");
+        assert_eq!(format_tz_offset(-300), "UTC-5");
+        assert_eq!(format_tz_offset(0), "UTC+0");
+    }
+
+    #[test]
+    fn test_stats_summary_uses_user_day_boundaries() {
+        // 15:00 UTC = 01:00 next day in UTC+10, still 18:00 the same day in Moscow
+        let now = DateTime::parse_from_rfc3339("2026-01-05T15:00:00+00:00").unwrap().with_timezone(&Utc);
+        let evening = Training { date: now - chrono::Duration::hours(2), ..training_minutes_ago("пловец", 0) };
+        let trainings = vec![evening];
+
+        let moscow = format_stats_summary(&trainings, 1, now, moscow_tz(), Lang::Ru);
+        assert!(moscow.contains("Сегодня: 1 ("), "{}", moscow);
+
+        let vladivostok = format_stats_summary(&trainings, 1, now, user_tz(600), Lang::Ru);
+        assert!(vladivostok.contains("Сегодня: 0 ("), "{}", vladivostok);
+    }
+
+    #[test]
+    fn test_parse_note() {
+        assert_eq!(parse_note("  болит плечо "), Some("болит плечо".to_string()));
+        assert_eq!(parse_note("-"), None);
+        assert_eq!(parse_note(" - "), None);
+        assert_eq!(parse_note("   "), None);
+    }
+
+    #[test]
+    fn test_format_today_entry_shows_note() {
+        let plain = training_minutes_ago("пловец", 0);
+        assert_eq!(format_today_entry(&plain), "• пловец - 1x10\n");
+
+        let noted = Training { notes: Some("медленно".into()), ..plain };
+        assert_eq!(format_today_entry(&noted), "• пловец - 1x10\n   📝 медленно\n");
+    }
+
+    #[test]
+    fn test_cancel_reply_by_state() {
+        assert_eq!(cancel_reply(None), "Нечего отменять");
+        assert_eq!(cancel_reply(Some(&State::Start)), "Нечего отменять");
+
+        let waiting = State::WaitingForPulseBefore {
+            exercise_id: "swimmer".into(),
+            exercise_name: "пловец".into(),
+            user_id: 1,
+        };
+        assert!(cancel_reply(Some(&waiting)).contains("не сохранён"));
+        assert!(cancel_reply(Some(&State::SetupTimezone { user_id: 1 })).contains("Настройка отменена"));
+    }
+
+    #[test]
+    fn test_more_set_callback_round_trip() {
+        let keyboard = make_after_set_keyboard("pushups_fist", 72);
+        let teloxide::types::InlineKeyboardButtonKind::CallbackData(data) = &keyboard.inline_keyboard[0][0].kind else {
+            panic!("Expected callback button");
+        };
+        assert!(data.len() <= 64, "Telegram limits callback data to 64 bytes");
+        assert_eq!(parse_more_set(data), Some(("pushups_fist", 72)));
+        let teloxide::types::InlineKeyboardButtonKind::CallbackData(rest) = &keyboard.inline_keyboard[0][2].kind else {
+            panic!("Expected rest button");
+        };
+        assert_eq!(rest, "rest:90");
+        assert_eq!(parse_more_set("more:pushups_fist:x"), None);
+        assert_eq!(parse_more_set("cmd:stats"), None);
+    }
+
+    #[test]
+    fn test_format_help_in_english() {
+        let help = format_help(Lang::En);
+        assert!(help.contains("commands"));
+        assert!(help.contains(HelpSection::Training.title(Lang::En)));
+        assert!(help.contains("/train — Pick an exercise"));
+        for bot_command in Command::bot_commands() {
+            assert!(
+                command_description_en(&bot_command.command).is_some(),
+                "No English description: {}", bot_command.command
+            );
+        }
+        assert!(!help.contains("Выбрать упражнение"));
+    }
+
+    #[test]
+    fn test_format_stats_summary_in_english() {
+        let trainings = vec![Training { reps: 20, ..training_minutes_ago("пловец", 0) }];
+        let now = Utc::now();
+        let text = format_stats_summary(&trainings, 5, now, moscow_tz(), Lang::En);
+        assert!(text.starts_with("📈 Statistics"));
+        assert!(text.contains("Total: 5 sets"));
+        assert!(text.contains("Today: 1 (30с)"));
+        assert!(text.contains("• пловец - 1 sets, 20 reps"));
+
+        let text = format_stats_summary(&trainings, 5, now, moscow_tz(), Lang::Ru);
+        assert!(text.contains("Всего: 5 подх."));
+        assert!(text.contains("• пловец - 1 подх., 20 повт."));
+    }
+
+    #[test]
+    fn test_format_stats_summary_uses_timing_snapshot() {
+        // Gone from the catalog, but stored as a timed hold
+        let trainings = vec![Training { is_timed: Some(true), ..training_minutes_ago("старая планка", 0) }];
+        let text = format_stats_summary(&trainings, 1, Utc::now(), moscow_tz(), Lang::Ru);
+        assert!(text.contains("• старая планка - 1 подх., макс. 30"), "{}", text);
+    }
+
+    #[test]
+    fn test_week_start_utc() {
+        // Wednesday 2026-01-07 01:00 Moscow = Tuesday 22:00 UTC
+        let now = DateTime::parse_from_rfc3339("2026-01-06T22:00:00+00:00").unwrap().with_timezone(&Utc);
+        let start = week_start_utc(now, MOSCOW_OFFSET_SECS);
+        // Monday 2026-01-05 00:00 Moscow
+        assert_eq!(start.to_rfc3339(), "2026-01-04T21:00:00+00:00");
+    }
+
+    #[test]
+    fn test_format_week_comparison() {
+        let cmp = PeriodComparison {
+            current: crate::ml::PeriodStats { sessions: 3, sets: 10, volume: 150, top_exercise: Some("пловец".into()) },
+            previous: crate::ml::PeriodStats { sessions: 2, sets: 12, volume: 150, top_exercise: None },
+        };
+        let text = format_week_comparison(&cmp);
+        assert!(text.contains("Тренировок: 3 📈 (было 2)"), "{}", text);
+        assert!(text.contains("Подходов: 10 📉 (было 12)"), "{}", text);
+        assert!(text.contains("Объём: 150 ➡️ (было 150)"), "{}", text);
+        assert!(text.contains("Топ: пловец (было —)"), "{}", text);
+    }
+
+    #[test]
+    fn test_apply_template_arg() {
+        let mut template = WeekdayTemplate::default();
+        apply_template_arg(&mut template, "чт жим").unwrap();
+        assert_eq!(template.focus(Weekday::Thu), Some(Category::Push));
+
+        apply_template_arg(&mut template, "ЧТ -").unwrap();
+        assert_eq!(template.focus(Weekday::Thu), None);
+
+        assert!(apply_template_arg(&mut template, "чт").is_err());
+        assert!(apply_template_arg(&mut template, "xx жим").is_err());
+        assert!(apply_template_arg(&mut template, "пн бег").is_err());
+    }
+
+    #[test]
+    fn test_format_weekday_template() {
+        let mut template = WeekdayTemplate::default();
+        template.set(Weekday::Tue, Some(Category::Legs));
+        let text = format_weekday_template(&template);
+        assert!(text.contains("вт: 🦵 ноги"), "{}", text);
+        assert!(text.contains("пн: —"), "{}", text);
+    }
+
+    #[test]
+    fn test_days_word() {
+        assert_eq!(days_word(1), "день");
+        assert_eq!(days_word(4), "дня");
+        assert_eq!(days_word(5), "дней");
+        assert_eq!(days_word(11), "дней");
+        assert_eq!(days_word(12), "дней");
+        assert_eq!(days_word(21), "день");
+        assert_eq!(days_word(0), "дней");
+    }
+
+    #[test]
+    fn test_acwr_warning() {
+        assert_eq!(acwr_warning(None), None);
+        assert_eq!(acwr_warning(Some(1.0)), None);
+        assert!(acwr_warning(Some(1.6)).unwrap().contains("риск травмы"));
+        assert!(acwr_warning(Some(0.5)).unwrap().contains("спад"));
+    }
+
+    #[test]
+    fn test_format_rest_window() {
+        let pushups = find_exercise("pushups_fist").unwrap();
+        assert_eq!(format_rest_window(pushups), "Отдых: 1м 30с – 2м 0с");
+        let plank = find_exercise("plank_elbows").unwrap();
+        assert_eq!(format_rest_window(plank), "Отдых: 30с – 1м 0с");
+    }
+
+    #[test]
+    fn test_resting_pulse_warning() {
+        assert_eq!(resting_pulse_warning(None), None);
+        assert_eq!(resting_pulse_warning(Some(0.0)), None);
+        assert_eq!(resting_pulse_warning(Some(-1.0)), None);
+        let warning = resting_pulse_warning(Some(1.2)).unwrap();
+        assert!(warning.contains("1.2 уд/мин"));
+        assert!(warning.contains(tips::TipCategory::Recovery.name()));
+    }
+
+    #[test]
+    fn test_format_compact_stats() {
+        assert_eq!(
+            format_compact_stats(3, 12, 4, 67.6),
+            "Сегодня: 3 подх. / Неделя: 12 / Стрик: 4 дня / Баланс: 68%"
+        );
+    }
+
+    #[test]
+    fn test_repeat_plan_order_and_targets() {
+        let mut swim1 = training_minutes_ago("пловец", 30);
+        swim1.reps = 15;
+        let mut swim2 = training_minutes_ago("пловец", 10);
+        swim2.reps = 18;
+        let mut plank = training_minutes_ago("стойка на локтях", 20);
+        plank.duration_secs = Some(60);
+        let custom = training_minutes_ago("что-то своё", 25);
+
+        // Input newest first, as the DB returns it
+        let plan = repeat_plan(&[swim2, plank, custom, swim1]);
+        let ids: Vec<_> = plan.iter().map(|(ex, target)| (ex.id, *target)).collect();
+        assert_eq!(ids, vec![("swimmer", 18), ("plank_elbows", 60)]);
+    }
+
+    #[test]
+    fn test_format_repeat_plan() {
+        let plan = vec![
+            (find_exercise("swimmer").unwrap(), 18),
+            (find_exercise("plank_elbows").unwrap(), 60),
+        ];
+        let date = chrono::NaiveDate::from_ymd_opt(2026, 1, 5).unwrap();
+        assert_eq!(
+            format_repeat_plan(date, &plan),
+            "🔁 Повтор тренировки 2026-01-05\n\n1. пловец — 18\n2. стойка на локтях — 60с\n\nЖми по порядку 👇"
+        );
+    }
+
+    #[test]
+    fn test_format_checklist() {
+        let plan = vec![
+            PlanItem { exercise: find_exercise("taiji_shadow").unwrap(), done: true },
+            PlanItem { exercise: find_exercise("swimmer").unwrap(), done: false },
+        ];
+        assert_eq!(
+            format_checklist(&plan),
+            "📋 План на сегодня\n\n✅ тайцзи бой с тенью\n⬜ пловец\n\nГотово: 1/2"
+        );
+
+        let all_done: Vec<_> = plan.into_iter().map(|item| PlanItem { done: true, ..item }).collect();
+        assert!(format_checklist(&all_done).ends_with("Готово: 2/2 🎉"));
+        assert_eq!(format_checklist(&[]), "📋 На сегодня в плане ничего нет");
+    }
+
+    #[test]
+    fn test_parse_gear() {
+        assert_eq!(parse_gear("всё"), Ok(None));
+        assert_eq!(parse_gear("Все"), Ok(None));
+        assert_eq!(parse_gear("нет"), Ok(Some(vec![])));
+        assert_eq!(
+            parse_gear("ручки, ступенька"),
+            Ok(Some(vec![Equipment::Handles, Equipment::Step]))
+        );
+        assert!(parse_gear("гантели").is_err());
+    }
+
+    #[test]
+    fn test_format_gear() {
+        assert_eq!(format_gear(None), "весь");
+        assert_eq!(format_gear(Some(&[])), "нет");
+        assert_eq!(format_gear(Some(&[Equipment::Bar, Equipment::Wall])), "перекладина, стена");
+    }
+
+    #[test]
+    fn test_is_quiet_plain_window() {
+        assert!(is_quiet(13, 13, 15));
+        assert!(is_quiet(14, 13, 15));
+        assert!(!is_quiet(15, 13, 15), "end hour is not quiet");
+        assert!(!is_quiet(12, 13, 15));
+        assert!(!is_quiet(5, 8, 8), "empty window");
+    }
+
+    #[test]
+    fn test_is_quiet_wraps_midnight() {
+        assert!(is_quiet(22, 22, 7));
+        assert!(is_quiet(23, 22, 7));
+        assert!(is_quiet(0, 22, 7));
+        assert!(is_quiet(3, 22, 7));
+        assert!(is_quiet(6, 22, 7));
+        assert!(!is_quiet(7, 22, 7));
+        assert!(!is_quiet(12, 22, 7));
+        assert!(!is_quiet(21, 22, 7));
+    }
+
+    #[test]
+    fn test_parse_quiet_hours() {
+        assert_eq!(parse_quiet_hours("22 7"), Some(Some((22, 7))));
+        assert_eq!(parse_quiet_hours(" 0  6 "), Some(Some((0, 6))));
+        assert_eq!(parse_quiet_hours("off"), Some(None));
+        assert_eq!(parse_quiet_hours("выкл"), Some(None));
+        assert_eq!(parse_quiet_hours("22"), None);
+        assert_eq!(parse_quiet_hours("22 24"), None);
+        assert_eq!(parse_quiet_hours("7 7"), None);
+        assert_eq!(parse_quiet_hours("22 7 1"), None);
+        assert_eq!(parse_quiet_hours(""), None);
+    }
+
+    #[test]
+    fn test_parse_daily_tip() {
+        assert_eq!(parse_daily_tip("on"), Some(Some(DEFAULT_DAILY_TIP_HOUR)));
+        assert_eq!(parse_daily_tip("ON 7"), Some(Some(7)));
+        assert_eq!(parse_daily_tip("9"), Some(Some(9)));
+        assert_eq!(parse_daily_tip("off"), Some(None));
+        assert_eq!(parse_daily_tip("выкл"), Some(None));
+        assert_eq!(parse_daily_tip("on 24"), None);
+        assert_eq!(parse_daily_tip("off 8"), None);
+        assert_eq!(parse_daily_tip("утром"), None);
+        assert_eq!(parse_daily_tip(""), None);
+    }
+
+    #[test]
+    fn test_daily_tip_due_once_per_local_day() {
+        let mut subscription = DailyTipSubscription {
+            chat_id: 42,
+            hour: 8,
+            tz_offset_minutes: 180,
+            last_sent: None,
+            last_category: None,
+        };
+        let at = |s: &str| DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc);
+
+        // 07:30 Moscow: too early
+        assert_eq!(daily_tip_due(&subscription, at("2026-01-05T04:30:00Z")), None);
+        // 08:10 Moscow: due for Jan 5
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 1, 5).unwrap();
+        assert_eq!(daily_tip_due(&subscription, at("2026-01-05T05:10:00Z")), Some(today));
+
+        // Already sent today (e.g. before a restart): not again
+        subscription.last_sent = Some(today);
+        assert_eq!(daily_tip_due(&subscription, at("2026-01-05T12:00:00Z")), None);
+        // Next morning
+        assert_eq!(daily_tip_due(&subscription, at("2026-01-06T05:00:00Z")), today.succ_opt());
+    }
+
+    #[test]
+    fn test_parse_weight() {
+        assert_eq!(parse_weight("72.5"), Some(72.5));
+        assert_eq!(parse_weight(" 72,5 "), Some(72.5));
+        assert_eq!(parse_weight("80 кг"), Some(80.0));
+        assert_eq!(parse_weight("20"), Some(20.0));
+        assert_eq!(parse_weight("19.9"), None);
+        assert_eq!(parse_weight("301"), None);
+        assert_eq!(parse_weight("много"), None);
+    }
+
+    #[test]
+    fn test_parse_rpe() {
+        assert_eq!(parse_rpe("7"), Some(Some(7)));
+        assert_eq!(parse_rpe(" 10 "), Some(Some(10)));
+        assert_eq!(parse_rpe("-"), Some(None));
+        assert_eq!(parse_rpe("0"), None);
+        assert_eq!(parse_rpe("11"), None);
+        assert_eq!(parse_rpe("тяжело"), None);
+    }
+
+    #[test]
+    fn test_format_pulse_recovery() {
+        assert!(format_pulse_recovery(None).contains("4+"));
+        assert!(format_pulse_recovery(Some(1.0)).contains("не меняется"));
+        assert!(format_pulse_recovery(Some(-20.0)).contains("на 20 уд/мин меньше"));
+        assert!(format_pulse_recovery(Some(8.0)).contains("на 8 уд/мин больше"));
+    }
+
+    #[test]
+    fn test_format_deload_hint_has_recovery_tip() {
+        let hint = format_deload_hint(None);
+        assert!(hint.contains("Плато"));
+        assert!(hint.contains(tips::TipCategory::Recovery.emoji()));
+        assert!(!hint.contains("сложнее"));
+    }
+
+    #[test]
+    fn test_format_deload_hint_suggests_harder_variant() {
+        let hint = format_deload_hint(progression("pushups_fist").1);
+        assert!(hint.contains("сложнее: отжимания с ручками"));
+    }
+
+    #[test]
+    fn test_format_records_sections() {
+        let records = vec![
+            ("отжимания на кулаках".to_string(), 22, false),
+            ("стойка на локтях".to_string(), 90, true),
+        ];
+        assert_eq!(
+            format_records(&records),
+            "🏆 Личные рекорды\n\n💪 Повторы:\n• отжимания на кулаках — 22 повт.\n\n⏱ Время:\n• стойка на локтях — 1м 30с"
+        );
+
+        let reps_only = vec![("пловец".to_string(), 30, false)];
+        assert!(!format_records(&reps_only).contains("Время"));
+        assert_eq!(format_records(&[]), "Рекордов пока нет — начни с /train");
+    }
+
+    #[test]
+    fn test_format_balance_trend() {
+        let monday = |d| chrono::NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+        assert_eq!(
+            format_balance_trend(&[(monday(5), 20.4), (monday(12), 62.0)]),
+            "⚖️ Баланс по неделям\n\n05.01 [##........] 20%\n12.01 [######....] 62%"
+        );
+        assert!(format_balance_trend(&[]).contains("/train"));
+    }
+
+    #[test]
+    fn test_format_muscle_trend() {
+        let monday = |d| chrono::NaiveDate::from_ymd_opt(2026, 1, d).unwrap();
+        assert_eq!(
+            format_muscle_trend(MuscleGroup::Back, &[(monday(5), 40), (monday(12), 0)]),
+            "💪 спина по неделям 📉\n\n05.01 [##########] 40\n12.01 [..........] 0"
+        );
+        assert!(format_muscle_trend(MuscleGroup::Back, &[(monday(5), 10), (monday(12), 20)]).contains("📈"));
+        assert!(format_muscle_trend(MuscleGroup::Back, &[(monday(5), 0)]).contains("не нагружалась"));
+    }
+
+    #[test]
+    fn test_format_streaks() {
+        assert_eq!(format_streaks(0, 0), "🔥 Серия: 0 дней\n🏆 Рекорд: 0 дней\n\nНачни новую серию: /train");
+        assert_eq!(format_streaks(2, 5), "🔥 Серия: 2 дня\n🏆 Рекорд: 5 дней");
+        assert!(format_streaks(5, 5).ends_with("Это твоя лучшая серия!"));
+    }
+
+    #[test]
+    fn test_parse_set_goal() {
+        assert_eq!(parse_set_goal("планка 3м"), Some(("планка".to_string(), "3м".to_string())));
+        assert_eq!(parse_set_goal("стойка на локтях 3 мин"), Some(("стойка на локтях".to_string(), "3мин".to_string())));
+        assert_eq!(parse_set_goal("пловец 30"), Some(("пловец".to_string(), "30".to_string())));
+        assert_eq!(parse_set_goal("30"), None);
+        assert_eq!(parse_set_goal("пловец"), None);
+        assert_eq!(parse_set_goal(""), None);
+    }
+
+    #[test]
+    fn test_parse_goal_value() {
+        assert_eq!(parse_goal_value("30", false), Some(30));
+        assert_eq!(parse_goal_value("90", true), Some(90));
+        assert_eq!(parse_goal_value("90с", true), Some(90));
+        assert_eq!(parse_goal_value("3м", true), Some(180));
+        assert_eq!(parse_goal_value("3МИН", true), Some(180));
+        assert_eq!(parse_goal_value("3м", false), None);
+        assert_eq!(parse_goal_value("0", false), None);
+        assert_eq!(parse_goal_value("много", false), None);
+    }
+
+    #[test]
+    fn test_resolve_goal_exercise() {
+        assert_eq!(resolve_goal_exercise("планка").unwrap().id, "plank_elbows");
+        assert_eq!(resolve_goal_exercise("swimmer").unwrap().name, "пловец");
+        assert!(resolve_goal_exercise("отжим").unwrap_err().contains("Уточни"));
+        assert!(resolve_goal_exercise("йога").unwrap_err().contains("Не знаю"));
+    }
+
+    #[test]
+    fn test_format_goals() {
+        let goal = |id, exercise: &str, target, is_timed, achieved| Goal {
+            id,
+            user_id: 1,
+            exercise: exercise.to_string(),
+            target_value: target,
+            is_timed,
+            created_at: Utc::now(),
+            achieved_at: achieved,
+        };
+        let trainings = vec![
+            Training { duration_secs: Some(90), ..training_minutes_ago("стойка на локтях", 5) },
+            Training { reps: 12, ..training_minutes_ago("пловец", 5) },
+        ];
+        let goals = vec![
+            goal(1, "стойка на локтях", 180, true, None),
+            goal(2, "пловец", 10, false, Some(Utc::now())),
+        ];
+
+        let text = format_goals(&goals, &trainings, moscow_tz());
+        assert!(text.contains("#1 стойка на локтях — 3м 0с (лучшее: 1м 30с, 50%)"));
+        assert!(text.contains("✅ Достигнуто\n#2 пловец — 10 повт."));
+        assert!(format_goals(&[], &trainings, moscow_tz()).contains("/setgoal"));
+
+        let congrats = format_goals_achieved(&goals[..1]);
+        assert!(congrats.starts_with("🎯 Цель достигнута!"));
+        assert!(congrats.contains("стойка на локтях — 3м 0с"));
+    }
+
+    #[test]
+    fn test_format_goal_eta() {
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 1, 20).unwrap();
+        assert_eq!(
+            format_goal_eta("пловец", 30, 18, today),
+            "🎯 пловец: 30 повт. — через ~18 дней (к 07.02.2026)"
+        );
+        assert_eq!(format_goal_eta("пловец", 30, 0, today), "🎯 пловец: тренд уже на 30 повт. — пробуй!");
+        // A near-flat trend saturates the ETA; the date must not overflow
+        assert_eq!(format_goal_eta("пловец", 1000, i32::MAX, today), "🎯 пловец: 1000 повт. — не скоро");
+    }
+
+    #[test]
+    fn test_parse_goal_reps() {
+        assert_eq!(parse_goal_reps("отжимания на кулаках 30"), Some(("отжимания на кулаках", 30)));
+        assert_eq!(parse_goal_reps("  пловец   25 "), Some(("пловец", 25)));
+        assert_eq!(parse_goal_reps("пловец"), None);
+        assert_eq!(parse_goal_reps("30"), None);
+        assert_eq!(parse_goal_reps("пловец 0"), None);
+        assert_eq!(parse_goal_reps("пловец 1000"), Some(("пловец", 1000)));
+        assert_eq!(parse_goal_reps("пловец 1001"), None);
+        assert_eq!(parse_goal_reps("пловец 2000000000"), None);
+        assert_eq!(parse_goal_reps(""), None);
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[]), "");
+        assert_eq!(sparkline(&[10, 20]), "▁█");
+        assert_eq!(sparkline(&[48, 30, 39]), "█▁▅", "scaled between min and max");
+        assert_eq!(sparkline(&[0, 1, 2, 3, 4, 5, 6, 7]), "▁▂▃▄▅▆▇█");
+        assert_eq!(sparkline(&[-5, 5]), "▁█");
+        assert_eq!(sparkline(&[25, 25, 25]), "▄▄▄", "flat series sits mid-height");
+    }
+
+    #[test]
+    fn test_format_pulse_sparkline() {
+        assert!(format_pulse_sparkline("пловец", &[]).starts_with("Мало данных"));
+        assert!(format_pulse_sparkline("пловец", &[40]).starts_with("Мало данных"));
+        assert_eq!(
+            format_pulse_sparkline("пловец", &[48, 40, 30]),
+            "🫀 пловец: прирост пульса за 3 подх.\n█▅▁\n48 → 30 уд/мин — сердце работает легче 💪"
+        );
+    }
+
+    #[test]
+    fn test_format_pulse_trend() {
+        assert_eq!(format_pulse_trend(None), "❤️ Пульс после: мало замеров для тренда");
+        assert_eq!(format_pulse_trend(Some(0.05)), "❤️ Пульс после: стабилен");
+        assert!(format_pulse_trend(Some(-0.5)).contains("↓ 3.5 уд/мин в неделю"));
+        assert_eq!(format_pulse_trend(Some(0.2)), "❤️ Пульс после: ↑ 1.4 уд/мин в неделю");
+    }
+
+    #[test]
+    fn test_enabled_base_exercises_empty_means_all() {
+        assert_eq!(enabled_base_exercises(&[]).len(), get_base_exercises().len());
+
+        let only = enabled_base_exercises(&["swimmer".to_string()]);
+        assert_eq!(only.len(), 1);
+        assert_eq!(only[0].id, "swimmer");
+    }
+
+    #[test]
+    fn test_toggle_base_exercise() {
+        let mut selected = vec!["swimmer".to_string()];
+        toggle_base_exercise(&mut selected, "jackknife");
+        assert_eq!(selected, vec!["swimmer", "jackknife"]);
+        toggle_base_exercise(&mut selected, "swimmer");
+        assert_eq!(selected, vec!["jackknife"]);
+    }
+
+    #[test]
+    fn test_format_settings_summary() {
+        let settings = UserSettings {
+            user_id: 1,
+            tz_offset_minutes: 420,
+            base_exercises: vec!["swimmer".to_string()],
+            reminders_enabled: false,
+        };
+        let text = format_settings_summary(&settings);
+        assert!(text.contains("UTC+7"), "{}", text);
+        assert!(text.contains("База (1): пловец"), "{}", text);
+        assert!(text.contains("выключены"), "{}", text);
+    }
+
     #[test]
     fn test_bot_config_default() {
-        // Note: this test may fail if MAX_USERS env var is set
+        // Note: this test may fail if MAX_USERS or MAX_SET_DURATION_SECS env vars are set
         // Default max_users should be 10
         let config = BotConfig::default();
         assert_eq!(config.max_users, 10);
+        assert_eq!(config.max_set_duration_secs, DEFAULT_MAX_SET_DURATION_SECS);
+    }
+
+    #[test]
+    fn test_finalize_duration() {
+        assert_eq!(finalize_duration(65, 5, 1800), (60, false));
+        assert_eq!(finalize_duration(3, 5, 1800), (0, false));
+        assert_eq!(finalize_duration(1805, 5, 1800), (1800, false));
+        assert_eq!(finalize_duration(1806, 5, 1800), (1800, true));
+        assert_eq!(finalize_duration(7200, 5, 1800), (1800, true));
     }
 }
@@ -1,10 +1,18 @@
 //! Database module - SQLite storage for training data
 
+use std::collections::HashSet;
+use std::io::Write;
+
 use anyhow::Result;
-use chrono::{DateTime, NaiveDateTime, Utc};
-use rusqlite::{Connection, params};
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use rusqlite::{Connection, ErrorCode, params};
+use tracing::warn;
 use serde::{Deserialize, Serialize};
 
+use crate::exercises::{find_exercise_by_name, Category, DIFFICULTY_RANGE, Equipment, MuscleGroup, WeekdayTemplate};
+use crate::lang::Lang;
+use crate::tips::TipCategory;
+
 /// User record
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
@@ -14,6 +22,76 @@ pub struct User {
     pub first_name: Option<String>,
     pub created_at: DateTime<Utc>,
     pub is_owner: bool,
+    pub age: Option<i32>,              // For max heart rate estimate
+    pub bonus_unlock_percent: Option<i32>, // Share of base program unlocking bonus (None = 100%)
+    pub equipment: Option<Vec<Equipment>>, // Gear at hand (None = not restricted)
+    pub current_context: Option<String>, // Context stamped on new trainings (/where)
+    pub lang: Lang,                    // Bot interface language (/lang)
+    pub tz_offset_minutes: i32,        // Offset east of UTC (/tz), Moscow by default
+    pub max_difficulty: Option<u8>,    // Hardest bonus exercise to suggest (/level), None = any
+    pub hide_from_board: bool,         // Left out of /leaderboard (/private)
+    pub weekly_target: Option<u8>,     // Sessions a week the user committed to (/weekly)
+}
+
+/// Per-user settings chosen in the setup wizard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UserSettings {
+    pub user_id: i64,
+    pub tz_offset_minutes: i32,        // Offset east of UTC, e.g. 180 for Moscow
+    pub base_exercises: Vec<String>,   // Enabled base exercise ids (empty = all)
+    pub reminders_enabled: bool,
+}
+
+/// Accepted bodyweight range, kg
+pub const BODYWEIGHT_RANGE_KG: std::ops::RangeInclusive<f64> = 20.0..=300.0;
+
+/// Bodyweight measurement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Bodyweight {
+    pub id: i64,
+    pub user_id: i64,
+    pub date: DateTime<Utc>,
+    pub weight_kg: f64,
+}
+
+/// Access request from a chat that hit the user cap
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingRequest {
+    pub chat_id: i64,
+    pub username: Option<String>,
+    pub first_name: Option<String>,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+    pub denied: bool,
+}
+
+/// Explicit target set with /setgoal: reps, or seconds for timed exercises
+#[derive(Debug, Clone, PartialEq)]
+pub struct Goal {
+    pub id: i64,
+    pub user_id: i64,
+    pub exercise: String,
+    pub target_value: i32,
+    pub is_timed: bool,
+    pub created_at: DateTime<Utc>,
+    pub achieved_at: Option<DateTime<Utc>>,
+}
+
+/// Chat opted into the morning tip (/dailytip)
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyTipSubscription {
+    pub chat_id: i64,
+    pub hour: u32,                          // Local hour to send at
+    pub tz_offset_minutes: i32,             // The chat's user timezone
+    pub last_sent: Option<NaiveDate>,       // Local date of the last tip sent
+    pub last_category: Option<TipCategory>, // Category of that tip, for rotation
+}
+
+/// Outcome of `import_json`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportReport {
+    pub inserted: usize,
+    pub skipped: Vec<String>, // Why each rejected row was left out ("row 2: empty exercise")
 }
 
 /// Training session record
@@ -27,8 +105,62 @@ pub struct Training {
     pub duration_secs: Option<i32>,  // Time spent on exercise
     pub pulse_before: Option<i32>,   // Heart rate before exercise
     pub pulse_after: Option<i32>,    // Heart rate after exercise
+    pub rpe: Option<i32>,            // Perceived exertion, 1-10
     pub notes: Option<String>,
     pub user_id: Option<i64>,        // Owner of this training record
+    pub context: Option<String>,     // Where it happened, e.g. "дома", "зал"
+    pub category: Option<Category>,  // Catalog category when logged (None for old rows)
+    pub is_timed: Option<bool>,      // Catalog is_timed when logged (None for old rows)
+}
+
+impl Training {
+    /// Category stored with the training, else looked up in the catalog
+    pub fn exercise_category(&self) -> Option<Category> {
+        self.category
+            .or_else(|| find_exercise_by_name(&self.exercise).map(|ex| ex.category))
+    }
+
+    /// Whether the exercise is timed: stored snapshot, else the catalog
+    pub fn exercise_is_timed(&self) -> Option<bool> {
+        self.is_timed
+            .or_else(|| find_exercise_by_name(&self.exercise).map(|ex| ex.is_timed))
+    }
+
+    /// Muscle groups worked: the catalog entry's, or the stored category's
+    /// typical groups once the exercise is gone from the catalog
+    pub fn muscle_groups(&self) -> &'static [MuscleGroup] {
+        match find_exercise_by_name(&self.exercise) {
+            Some(ex) => ex.muscle_groups,
+            None => self.category.map(|c| c.muscle_groups()).unwrap_or(&[]),
+        }
+    }
+
+    /// Human-readable field changes from `self` to `other`, in Russian
+    /// (e.g. "повторы: 15 → 18"). Empty if nothing user-visible changed.
+    pub fn diff(&self, other: &Training) -> Vec<String> {
+        fn opt<T: std::fmt::Display>(value: &Option<T>) -> String {
+            value.as_ref().map(|v| v.to_string()).unwrap_or_else(|| "—".to_string())
+        }
+
+        let fields = [
+            ("дата", self.date.format("%Y-%m-%d %H:%M").to_string(), other.date.format("%Y-%m-%d %H:%M").to_string()),
+            ("упражнение", self.exercise.clone(), other.exercise.clone()),
+            ("подходы", self.sets.to_string(), other.sets.to_string()),
+            ("повторы", self.reps.to_string(), other.reps.to_string()),
+            ("время", opt(&self.duration_secs), opt(&other.duration_secs)),
+            ("пульс до", opt(&self.pulse_before), opt(&other.pulse_before)),
+            ("пульс после", opt(&self.pulse_after), opt(&other.pulse_after)),
+            ("тяжесть", opt(&self.rpe), opt(&other.rpe)),
+            ("заметка", opt(&self.notes), opt(&other.notes)),
+            ("где", opt(&self.context), opt(&other.context)),
+        ];
+
+        fields
+            .into_iter()
+            .filter(|(_, before, after)| before != after)
+            .map(|(name, before, after)| format!("{}: {} → {}", name, before, after))
+            .collect()
+    }
 }
 
 /// Parse date string from database (supports RFC3339 and legacy "YYYY-MM-DD HH:MM:SS" format)
@@ -47,15 +179,199 @@ pub(crate) fn parse_date(date_str: &str) -> DateTime<Utc> {
     DateTime::UNIX_EPOCH
 }
 
+/// Whether an error means the database file itself is damaged
+fn is_corruption(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<rusqlite::Error>(),
+        Some(rusqlite::Error::SqliteFailure(e, _))
+            if matches!(e.code, ErrorCode::NotADatabase | ErrorCode::DatabaseCorrupt)
+    )
+}
+
+/// Column list matching `user_from_row`
+const USER_COLUMNS: &str = "id, chat_id, username, first_name, created_at, is_owner, age, bonus_unlock_percent, equipment, current_context, lang, tz_offset_minutes, max_difficulty, hide_from_board, weekly_target";
+
+/// Map a row selected with `USER_COLUMNS` to a User
+fn user_from_row(row: &rusqlite::Row) -> rusqlite::Result<User> {
+    let date_str: String = row.get(4)?;
+    Ok(User {
+        id: row.get(0)?,
+        chat_id: row.get(1)?,
+        username: row.get(2)?,
+        first_name: row.get(3)?,
+        created_at: DateTime::parse_from_rfc3339(&date_str)
+            .map(|d| d.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+        is_owner: row.get(5)?,
+        age: row.get(6)?,
+        bonus_unlock_percent: row.get(7)?,
+        equipment: row.get::<_, Option<String>>(8)?.map(|ids| {
+            ids.split(',').filter_map(Equipment::from_id).collect()
+        }),
+        current_context: row.get(9)?,
+        lang: row
+            .get::<_, Option<String>>(10)?
+            .and_then(|code| Lang::from_code(&code))
+            .unwrap_or_default(),
+        tz_offset_minutes: row.get::<_, Option<i32>>(11)?.unwrap_or(DEFAULT_TZ_OFFSET_MINUTES),
+        max_difficulty: row.get(12)?,
+        hide_from_board: row.get::<_, Option<bool>>(13)?.unwrap_or(false),
+        weekly_target: row.get(14)?,
+    })
+}
+
+/// Map an `id, user_id, date, weight_kg` row to a Bodyweight
+fn bodyweight_from_row(row: &rusqlite::Row) -> rusqlite::Result<Bodyweight> {
+    let date_str: String = row.get(2)?;
+    Ok(Bodyweight {
+        id: row.get(0)?,
+        user_id: row.get(1)?,
+        date: DateTime::parse_from_rfc3339(&date_str)
+            .map(|d| d.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+        weight_kg: row.get(3)?,
+    })
+}
+
+/// Column list matching `goal_from_row`
+const GOAL_COLUMNS: &str = "id, user_id, exercise, target_value, is_timed, created_at, achieved_at";
+
+/// Map a row selected with `GOAL_COLUMNS` to a Goal
+fn goal_from_row(row: &rusqlite::Row) -> rusqlite::Result<Goal> {
+    let created_at: String = row.get(5)?;
+    Ok(Goal {
+        id: row.get(0)?,
+        user_id: row.get(1)?,
+        exercise: row.get(2)?,
+        target_value: row.get(3)?,
+        is_timed: row.get(4)?,
+        created_at: parse_date(&created_at),
+        achieved_at: row.get::<_, Option<String>>(6)?.map(|d| parse_date(&d)),
+    })
+}
+
+/// Column list matching `training_from_row`
+const TRAINING_COLUMNS: &str =
+    "id, date, exercise, sets, reps, duration_secs, pulse_before, pulse_after, notes, user_id, context, rpe, category, is_timed";
+
+/// INSERT shared by every way of adding trainings; bind it with `insert_training`
+const TRAINING_INSERT: &str = "INSERT INTO trainings (date, exercise, sets, reps, duration_secs, pulse_before, pulse_after, notes, user_id, context, rpe, category, is_timed) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)";
+
+/// Run a statement prepared from `TRAINING_INSERT` for one training,
+/// snapshotting its category and timing. Returns the new row id.
+fn insert_training(stmt: &mut rusqlite::Statement, training: &Training, user_id: Option<i64>) -> Result<i64> {
+    Ok(stmt.insert(params![
+        training.date.to_rfc3339(),
+        training.exercise,
+        training.sets,
+        training.reps,
+        training.duration_secs,
+        training.pulse_before,
+        training.pulse_after,
+        training.notes,
+        user_id,
+        training.context,
+        training.rpe,
+        training.exercise_category().map(|c| c.id()),
+        training.exercise_is_timed(),
+    ])?)
+}
+
+/// Map a row selected with `TRAINING_COLUMNS` to a Training
+fn training_from_row(row: &rusqlite::Row) -> rusqlite::Result<Training> {
+    let date_str: String = row.get(1)?;
+    Ok(Training {
+        id: Some(row.get(0)?),
+        date: parse_date(&date_str),
+        exercise: row.get(2)?,
+        sets: row.get(3)?,
+        reps: row.get(4)?,
+        duration_secs: row.get(5)?,
+        pulse_before: row.get(6)?,
+        pulse_after: row.get(7)?,
+        notes: row.get(8)?,
+        user_id: row.get(9)?,
+        context: row.get(10)?,
+        rpe: row.get(11)?,
+        category: row.get::<_, Option<String>>(12)?.and_then(|id| Category::from_id(&id)),
+        is_timed: row.get(13)?,
+    })
+}
+
+/// UTC bounds `[start, end)` of the local calendar day containing `now`
+/// for a timezone `tz_offset_secs` east of UTC
+pub(crate) fn day_bounds_utc(now: DateTime<Utc>, tz_offset_secs: i32) -> (DateTime<Utc>, DateTime<Utc>) {
+    let tz = FixedOffset::east_opt(tz_offset_secs)
+        .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+    date_bounds_utc(now.with_timezone(&tz).date_naive(), tz_offset_secs)
+}
+
+/// UTC bounds `[start, end)` of a local calendar `date`
+/// for a timezone `tz_offset_secs` east of UTC
+pub(crate) fn date_bounds_utc(date: NaiveDate, tz_offset_secs: i32) -> (DateTime<Utc>, DateTime<Utc>) {
+    let local_midnight = date.and_time(NaiveTime::MIN);
+    let start = local_midnight.and_utc() - Duration::seconds(tz_offset_secs as i64);
+    (start, start + Duration::days(1))
+}
+
+/// Timezone used until a user picks one: Moscow, UTC+3
+pub const DEFAULT_TZ_OFFSET_MINUTES: i32 = 180;
+
+/// How long a deleted training stays restorable before `purge_trash` removes it
+pub const TRASH_RETENTION_HOURS: i64 = 24;
+
+/// Accepted offsets east of UTC, minutes (UTC-12 .. UTC+14)
+pub const TZ_OFFSET_RANGE_MINUTES: std::ops::RangeInclusive<i32> = -720..=840;
+
+/// Accepted weekly session targets (/weekly)
+pub const WEEKLY_TARGET_RANGE: std::ops::RangeInclusive<u8> = 1..=7;
+
+/// Fixed timezone `offset_minutes` east of UTC (default zone if out of range)
+pub fn user_tz(offset_minutes: i32) -> FixedOffset {
+    FixedOffset::east_opt(offset_minutes * 60)
+        .or_else(|| FixedOffset::east_opt(DEFAULT_TZ_OFFSET_MINUTES * 60))
+        .unwrap()
+}
+
+/// Quote a CSV field if it contains a separator, quote or newline
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
 /// Database wrapper
 pub struct Database {
     conn: Connection,
 }
 
 impl Database {
-    /// Open or create database
+    /// Open or create database.
+    /// A corrupt file is moved aside to `<path>.corrupt-<timestamp>` and a fresh database is created.
+    /// File databases run in WAL mode with a 5 s busy timeout, so the TUI can read while the bot
+    /// writes; this leaves `<path>-wal` and `<path>-shm` files next to the database.
     pub fn open(path: &str) -> Result<Self> {
+        match Self::open_existing(path) {
+            Err(e) if path != ":memory:" && is_corruption(&e) => {
+                let backup = format!("{}.corrupt-{}", path, Utc::now().format("%Y%m%d-%H%M%S"));
+                warn!("Database {} is corrupt ({}), moving it to {} and starting fresh", path, e, backup);
+                std::fs::rename(path, &backup)?;
+                Self::open_existing(path)
+            }
+            result => result,
+        }
+    }
+
+    fn open_existing(path: &str) -> Result<Self> {
         let conn = Connection::open(path)?;
+        // The TUI and the bot may share the file: wait out a writer instead of failing
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
+        let journal_mode: String = conn.query_row("PRAGMA journal_mode=WAL", [], |row| row.get(0))?;
+        if path != ":memory:" && !journal_mode.eq_ignore_ascii_case("wal") {
+            anyhow::bail!("Cannot enable WAL mode for {} (journal_mode is {})", path, journal_mode);
+        }
         let db = Self { conn };
         db.init_schema()?;
         Ok(db)
@@ -87,12 +403,233 @@ impl Database {
                 duration_secs INTEGER,
                 pulse_before INTEGER,
                 pulse_after INTEGER,
+                rpe INTEGER,
                 notes TEXT,
                 user_id INTEGER REFERENCES users(id)
             )",
             [],
         )?;
 
+        // User settings table (absent row = setup wizard not completed)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS user_settings (
+                user_id INTEGER PRIMARY KEY REFERENCES users(id),
+                tz_offset_minutes INTEGER NOT NULL,
+                base_exercises TEXT NOT NULL DEFAULT '',
+                reminders_enabled BOOLEAN NOT NULL DEFAULT FALSE
+            )",
+            [],
+        )?;
+
+        // Weekday focus template per user (JSON-serialized WeekdayTemplate)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS weekday_templates (
+                user_id INTEGER PRIMARY KEY REFERENCES users(id),
+                template TEXT NOT NULL
+            )",
+            [],
+        )?;
+
+        // Chats subscribed to hourly reminders (survives bot restarts)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS reminder_subscriptions (
+                chat_id INTEGER PRIMARY KEY,
+                created_at TEXT NOT NULL,
+                quiet_start INTEGER,
+                quiet_end INTEGER
+            )",
+            [],
+        )?;
+
+        // Access requests from chats over the user cap, decided with /approve or /deny
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS pending_requests (
+                chat_id INTEGER PRIMARY KEY,
+                username TEXT,
+                first_name TEXT,
+                message TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                denied INTEGER NOT NULL DEFAULT 0
+            )",
+            [],
+        )?;
+
+        // Explicit per-exercise targets (/setgoal); achieved_at is set when a set reaches the target
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS goals (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_id INTEGER NOT NULL REFERENCES users(id),
+                exercise TEXT NOT NULL,
+                target_value INTEGER NOT NULL,
+                is_timed BOOLEAN NOT NULL DEFAULT FALSE,
+                created_at TEXT NOT NULL,
+                achieved_at TEXT
+            )",
+            [],
+        )?;
+
+        // Chats getting a tip each morning; last_sent_date dedups across restarts
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS daily_tips (
+                chat_id INTEGER PRIMARY KEY,
+                hour INTEGER NOT NULL,
+                last_sent_date TEXT,
+                last_category TEXT
+            )",
+            [],
+        )?;
+
+        // Planned rest days per user (local date)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS rest_days (
+                user_id INTEGER NOT NULL REFERENCES users(id),
+                date TEXT NOT NULL,
+                PRIMARY KEY (user_id, date)
+            )",
+            [],
+        )?;
+
+        // Bodyweight measurements (kg)
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS bodyweight (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                user_id INTEGER NOT NULL REFERENCES users(id),
+                date TEXT NOT NULL,
+                weight_kg REAL NOT NULL
+            )",
+            [],
+        )?;
+
+        // Sore muscle groups per user and local date
+        self.conn.execute(
+            "CREATE TABLE IF NOT EXISTS soreness (
+                user_id INTEGER NOT NULL REFERENCES users(id),
+                date TEXT NOT NULL,
+                muscle_group TEXT NOT NULL,
+                PRIMARY KEY (user_id, date, muscle_group)
+            )",
+            [],
+        )?;
+
+        // Migration: add users.age column if missing
+        let has_age: bool = self.conn
+            .prepare("SELECT age FROM users LIMIT 1")
+            .is_ok();
+        if !has_age {
+            let _ = self.conn.execute(
+                "ALTER TABLE users ADD COLUMN age INTEGER",
+                [],
+            );
+        }
+
+        // Migration: add users.bonus_unlock_percent column if missing
+        let has_bonus_rule: bool = self.conn
+            .prepare("SELECT bonus_unlock_percent FROM users LIMIT 1")
+            .is_ok();
+        if !has_bonus_rule {
+            let _ = self.conn.execute(
+                "ALTER TABLE users ADD COLUMN bonus_unlock_percent INTEGER",
+                [],
+            );
+        }
+
+        // Migration: add users.equipment column if missing
+        let has_equipment: bool = self.conn
+            .prepare("SELECT equipment FROM users LIMIT 1")
+            .is_ok();
+        if !has_equipment {
+            let _ = self.conn.execute(
+                "ALTER TABLE users ADD COLUMN equipment TEXT",
+                [],
+            );
+        }
+
+        // Migration: add users.current_context column if missing
+        let has_current_context: bool = self.conn
+            .prepare("SELECT current_context FROM users LIMIT 1")
+            .is_ok();
+        if !has_current_context {
+            let _ = self.conn.execute(
+                "ALTER TABLE users ADD COLUMN current_context TEXT",
+                [],
+            );
+        }
+
+        // Migration: add users.lang column if missing
+        let has_lang: bool = self.conn
+            .prepare("SELECT lang FROM users LIMIT 1")
+            .is_ok();
+        if !has_lang {
+            let _ = self.conn.execute(
+                "ALTER TABLE users ADD COLUMN lang TEXT",
+                [],
+            );
+        }
+
+        // Migration: add users.tz_offset_minutes, seeded from the setup wizard choice
+        let has_user_tz: bool = self.conn
+            .prepare("SELECT tz_offset_minutes FROM users LIMIT 1")
+            .is_ok();
+        if !has_user_tz {
+            let _ = self.conn.execute(
+                "ALTER TABLE users ADD COLUMN tz_offset_minutes INTEGER",
+                [],
+            );
+            let _ = self.conn.execute(
+                "UPDATE users SET tz_offset_minutes =
+                    (SELECT tz_offset_minutes FROM user_settings WHERE user_settings.user_id = users.id)",
+                [],
+            );
+        }
+
+        // Migration: add users.max_difficulty column if missing
+        let has_max_difficulty: bool = self.conn
+            .prepare("SELECT max_difficulty FROM users LIMIT 1")
+            .is_ok();
+        if !has_max_difficulty {
+            let _ = self.conn.execute(
+                "ALTER TABLE users ADD COLUMN max_difficulty INTEGER",
+                [],
+            );
+        }
+
+        // Migration: add users.hide_from_board column if missing
+        let has_hide_from_board: bool = self.conn
+            .prepare("SELECT hide_from_board FROM users LIMIT 1")
+            .is_ok();
+        if !has_hide_from_board {
+            let _ = self.conn.execute(
+                "ALTER TABLE users ADD COLUMN hide_from_board INTEGER",
+                [],
+            );
+        }
+
+        // Migration: add users.weekly_target column if missing
+        let has_weekly_target: bool = self.conn
+            .prepare("SELECT weekly_target FROM users LIMIT 1")
+            .is_ok();
+        if !has_weekly_target {
+            let _ = self.conn.execute(
+                "ALTER TABLE users ADD COLUMN weekly_target INTEGER",
+                [],
+            );
+        }
+
+        // Migration: add quiet hours to reminder_subscriptions if missing
+        let has_quiet_hours: bool = self.conn
+            .prepare("SELECT quiet_start FROM reminder_subscriptions LIMIT 1")
+            .is_ok();
+        if !has_quiet_hours {
+            let _ = self.conn.execute(
+                "ALTER TABLE reminder_subscriptions ADD COLUMN quiet_start INTEGER",
+                [],
+            );
+            let _ = self.conn.execute(
+                "ALTER TABLE reminder_subscriptions ADD COLUMN quiet_end INTEGER",
+                [],
+            );
+        }
+
         // Migration: add duration_secs column if missing
         let has_duration: bool = self.conn
             .prepare("SELECT duration_secs FROM trainings LIMIT 1")
@@ -119,6 +656,17 @@ impl Database {
             );
         }
 
+        // Migration: add rpe column if missing
+        let has_rpe: bool = self.conn
+            .prepare("SELECT rpe FROM trainings LIMIT 1")
+            .is_ok();
+        if !has_rpe {
+            let _ = self.conn.execute(
+                "ALTER TABLE trainings ADD COLUMN rpe INTEGER",
+                [],
+            );
+        }
+
         // Migration: add user_id column if missing
         let has_user_id: bool = self.conn
             .prepare("SELECT user_id FROM trainings LIMIT 1")
@@ -130,9 +678,72 @@ impl Database {
             );
         }
 
+        // Migration: add trainings.context column if missing
+        let has_context: bool = self.conn
+            .prepare("SELECT context FROM trainings LIMIT 1")
+            .is_ok();
+        if !has_context {
+            let _ = self.conn.execute(
+                "ALTER TABLE trainings ADD COLUMN context TEXT",
+                [],
+            );
+        }
+
+        // Migration: add trainings.deleted_at column if missing (soft delete, see purge_trash)
+        let has_deleted_at: bool = self.conn
+            .prepare("SELECT deleted_at FROM trainings LIMIT 1")
+            .is_ok();
+        if !has_deleted_at {
+            let _ = self.conn.execute(
+                "ALTER TABLE trainings ADD COLUMN deleted_at TEXT",
+                [],
+            );
+        }
+
+        // Migration: catalog snapshot on trainings, so history survives catalog edits
+        let has_category: bool = self.conn
+            .prepare("SELECT category FROM trainings LIMIT 1")
+            .is_ok();
+        if !has_category {
+            let _ = self.conn.execute("ALTER TABLE trainings ADD COLUMN category TEXT", []);
+            let _ = self.conn.execute("ALTER TABLE trainings ADD COLUMN is_timed INTEGER", []);
+        }
+
+        // Per-user history lookups; after the migrations since user_id may be added above
+        self.conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_trainings_user_date ON trainings(user_id, date)",
+            [],
+        )?;
+
+        // Migration: legacy "YYYY-MM-DD HH:MM:SS" dates don't sort against RFC3339 ones
+        self.migrate_date_formats()?;
+
         Ok(())
     }
 
+    /// Rewrite legacy training dates to RFC3339 UTC so string ordering and range queries hold.
+    /// Returns the number of rewritten rows (0 once migrated).
+    pub fn migrate_date_formats(&self) -> Result<usize> {
+        let legacy: Vec<(i64, String)> = {
+            let mut stmt = self.conn.prepare("SELECT id, date FROM trainings WHERE date NOT LIKE '%T%'")?;
+            stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<_, _>>()?
+        };
+        if legacy.is_empty() {
+            return Ok(0);
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        for (id, date) in &legacy {
+            tx.execute(
+                "UPDATE trainings SET date = ?1 WHERE id = ?2",
+                params![parse_date(date).to_rfc3339(), id],
+            )?;
+        }
+        tx.commit()?;
+        Ok(legacy.len())
+    }
+
     // ==================== USER METHODS ====================
 
     /// Get or create user by chat_id (first user becomes owner)
@@ -162,23 +773,11 @@ impl Database {
 
     /// Get user by chat_id
     pub fn get_user_by_chat_id(&self, chat_id: i64) -> Result<Option<User>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, chat_id, username, first_name, created_at, is_owner FROM users WHERE chat_id = ?1"
-        )?;
-
-        let user = stmt.query_row([chat_id], |row| {
-            let date_str: String = row.get(4)?;
-            Ok(User {
-                id: row.get(0)?,
-                chat_id: row.get(1)?,
-                username: row.get(2)?,
-                first_name: row.get(3)?,
-                created_at: DateTime::parse_from_rfc3339(&date_str)
-                    .map(|d| d.with_timezone(&Utc))
-                    .unwrap_or_else(|_| Utc::now()),
-                is_owner: row.get(5)?,
-            })
-        });
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {USER_COLUMNS} FROM users WHERE chat_id = ?1"
+        ))?;
+
+        let user = stmt.query_row([chat_id], user_from_row);
 
         match user {
             Ok(u) => Ok(Some(u)),
@@ -187,6 +786,16 @@ impl Database {
         }
     }
 
+    /// Count a user's trainings without loading them
+    pub fn count_trainings_for_user(&self, user_id: i64) -> Result<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM trainings WHERE user_id = ?1 AND deleted_at IS NULL",
+            [user_id],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
     /// Count total users
     pub fn count_users(&self) -> Result<usize> {
         let count: i64 = self.conn.query_row(
@@ -197,25 +806,78 @@ impl Database {
         Ok(count as usize)
     }
 
+    /// All users with their training counts (zero included), most active first
+    pub fn get_users_with_counts(&self) -> Result<Vec<(User, usize)>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {USER_COLUMNS}, COALESCE(counts.n, 0) FROM users
+             LEFT JOIN (
+                 SELECT user_id, COUNT(*) AS n FROM trainings
+                 WHERE deleted_at IS NULL GROUP BY user_id
+             ) AS counts ON counts.user_id = users.id
+             ORDER BY COALESCE(counts.n, 0) DESC, users.id"
+        ))?;
+        let users = stmt.query_map([], |row| {
+            let count: i64 = row.get(15)?;
+            Ok((user_from_row(row)?, count as usize))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+        Ok(users)
+    }
+
+    /// Move everything of `from_user_id` (trainings, goals, bodyweight, rest days,
+    /// soreness) to `into_user_id` and delete the source user. Returns trainings moved.
+    /// The source's settings and templates are dropped; on a date clash the target's
+    /// rest day/soreness entry wins. Refuses self-merges and merging the owner away.
+    pub fn merge_users(&self, from_user_id: i64, into_user_id: i64) -> Result<usize> {
+        if from_user_id == into_user_id {
+            anyhow::bail!("Cannot merge user {} into itself", from_user_id);
+        }
+        let is_owner = |id: i64| -> Result<Option<bool>> {
+            match self.conn.query_row("SELECT is_owner FROM users WHERE id = ?1", [id], |row| row.get(0)) {
+                Ok(is_owner) => Ok(Some(is_owner)),
+                Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+                Err(e) => Err(e.into()),
+            }
+        };
+        match (is_owner(from_user_id)?, is_owner(into_user_id)?) {
+            (None, _) => anyhow::bail!("User {} not found", from_user_id),
+            (_, None) => anyhow::bail!("User {} not found", into_user_id),
+            (Some(true), _) => anyhow::bail!("Cannot merge the owner into another user"),
+            _ => {}
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        let moved = tx.execute(
+            "UPDATE trainings SET user_id = ?2 WHERE user_id = ?1",
+            [from_user_id, into_user_id],
+        )?;
+        for table in ["goals", "bodyweight"] {
+            tx.execute(
+                &format!("UPDATE {table} SET user_id = ?2 WHERE user_id = ?1"),
+                [from_user_id, into_user_id],
+            )?;
+        }
+        for table in ["rest_days", "soreness"] {
+            tx.execute(
+                &format!("UPDATE OR IGNORE {table} SET user_id = ?2 WHERE user_id = ?1"),
+                [from_user_id, into_user_id],
+            )?;
+        }
+        for table in ["rest_days", "soreness", "user_settings", "weekday_templates"] {
+            tx.execute(&format!("DELETE FROM {table} WHERE user_id = ?1"), [from_user_id])?;
+        }
+        tx.execute("DELETE FROM users WHERE id = ?1", [from_user_id])?;
+        tx.commit()?;
+        Ok(moved)
+    }
+
     /// Get owner user
     pub fn get_owner(&self) -> Result<Option<User>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, chat_id, username, first_name, created_at, is_owner FROM users WHERE is_owner = 1"
-        )?;
-
-        let user = stmt.query_row([], |row| {
-            let date_str: String = row.get(4)?;
-            Ok(User {
-                id: row.get(0)?,
-                chat_id: row.get(1)?,
-                username: row.get(2)?,
-                first_name: row.get(3)?,
-                created_at: DateTime::parse_from_rfc3339(&date_str)
-                    .map(|d| d.with_timezone(&Utc))
-                    .unwrap_or_else(|_| Utc::now()),
-                is_owner: row.get(5)?,
-            })
-        });
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {USER_COLUMNS} FROM users WHERE is_owner = 1"
+        ))?;
+
+        let user = stmt.query_row([], user_from_row);
 
         match user {
             Ok(u) => Ok(Some(u)),
@@ -224,31 +886,611 @@ impl Database {
         }
     }
 
-    // ==================== TRAINING METHODS ====================
+    /// Set user's age (used for heart rate zones)
+    pub fn set_user_age(&self, user_id: i64, age: i32) -> Result<()> {
+        self.conn.execute(
+            "UPDATE users SET age = ?1 WHERE id = ?2",
+            params![age, user_id],
+        )?;
+        Ok(())
+    }
 
-    /// Add training record without user (CLI backward compatibility)
-    pub fn add_training_cli(&self, training: &Training) -> Result<i64> {
+    /// Set the share of the base program (percent) that unlocks bonus exercises
+    pub fn set_bonus_unlock_percent(&self, user_id: i64, percent: i32) -> Result<()> {
         self.conn.execute(
-            "INSERT INTO trainings (date, exercise, sets, reps, duration_secs, pulse_before, pulse_after, notes) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "UPDATE users SET bonus_unlock_percent = ?1 WHERE id = ?2",
+            params![percent, user_id],
+        )?;
+        Ok(())
+    }
+
+    /// Set the gear at hand (None = stop filtering by equipment)
+    pub fn set_user_equipment(&self, user_id: i64, equipment: Option<&[Equipment]>) -> Result<()> {
+        let ids = equipment.map(|eq| eq.iter().map(|e| e.id()).collect::<Vec<_>>().join(","));
+        self.conn.execute(
+            "UPDATE users SET equipment = ?1 WHERE id = ?2",
+            params![ids, user_id],
+        )?;
+        Ok(())
+    }
+
+    /// Cap the difficulty of suggested bonus exercises (None = no cap)
+    pub fn set_user_max_difficulty(&self, user_id: i64, max: Option<u8>) -> Result<()> {
+        if let Some(max) = max
+            && !DIFFICULTY_RANGE.contains(&max)
+        {
+            anyhow::bail!("Difficulty must be 1-5, got {}", max);
+        }
+        self.conn.execute(
+            "UPDATE users SET max_difficulty = ?1 WHERE id = ?2",
+            params![max, user_id],
+        )?;
+        Ok(())
+    }
+
+    /// Commit to a number of sessions a week (None = no target)
+    pub fn set_user_weekly_target(&self, user_id: i64, target: Option<u8>) -> Result<()> {
+        if let Some(target) = target
+            && !WEEKLY_TARGET_RANGE.contains(&target)
+        {
+            anyhow::bail!("Weekly target must be 1-7 sessions, got {}", target);
+        }
+        self.conn.execute(
+            "UPDATE users SET weekly_target = ?1 WHERE id = ?2",
+            params![target, user_id],
+        )?;
+        Ok(())
+    }
+
+    /// Leave the user out of the leaderboard (or bring them back)
+    pub fn set_user_hide_from_board(&self, user_id: i64, hide: bool) -> Result<()> {
+        self.conn.execute(
+            "UPDATE users SET hide_from_board = ?1 WHERE id = ?2",
+            params![hide, user_id],
+        )?;
+        Ok(())
+    }
+
+    /// Sets per user over the 7 days before `now` as (first name, sets), most first.
+    /// Users who opted out with `hide_from_board` are left out.
+    pub fn get_weekly_leaderboard(&self, now: DateTime<Utc>) -> Result<Vec<(Option<String>, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT users.first_name, SUM(trainings.sets) AS total
+             FROM trainings JOIN users ON users.id = trainings.user_id
+             WHERE trainings.deleted_at IS NULL
+               AND COALESCE(users.hide_from_board, 0) = 0
+               AND trainings.date >= ?1
+               AND trainings.date <= ?2
+             GROUP BY users.id
+             ORDER BY total DESC, users.id"
+        )?;
+        let board = stmt.query_map(
+            params![(now - Duration::days(7)).to_rfc3339(), now.to_rfc3339()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+        Ok(board)
+    }
+
+    /// Set the context stamped on the user's next trainings (None = clear)
+    pub fn set_current_context(&self, user_id: i64, context: Option<&str>) -> Result<()> {
+        self.conn.execute(
+            "UPDATE users SET current_context = ?1 WHERE id = ?2",
+            params![context, user_id],
+        )?;
+        Ok(())
+    }
+
+    /// Set the user's timezone; kept in sync with the setup wizard settings
+    pub fn set_user_tz(&self, user_id: i64, offset_minutes: i32) -> Result<()> {
+        if !TZ_OFFSET_RANGE_MINUTES.contains(&offset_minutes) {
+            anyhow::bail!("Timezone offset out of range: {} minutes", offset_minutes);
+        }
+        self.conn.execute(
+            "UPDATE users SET tz_offset_minutes = ?1 WHERE id = ?2",
+            params![offset_minutes, user_id],
+        )?;
+        self.conn.execute(
+            "UPDATE user_settings SET tz_offset_minutes = ?1 WHERE user_id = ?2",
+            params![offset_minutes, user_id],
+        )?;
+        Ok(())
+    }
+
+    /// User's timezone offset in minutes (default zone for unknown users)
+    pub fn user_tz_offset_minutes(&self, user_id: i64) -> Result<i32> {
+        let offset = self.conn.query_row(
+            "SELECT tz_offset_minutes FROM users WHERE id = ?1",
+            params![user_id],
+            |row| row.get::<_, Option<i32>>(0),
+        );
+
+        match offset {
+            Ok(offset) => Ok(offset.unwrap_or(DEFAULT_TZ_OFFSET_MINUTES)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(DEFAULT_TZ_OFFSET_MINUTES),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Set the user's bot interface language
+    pub fn set_user_lang(&self, user_id: i64, lang: Lang) -> Result<()> {
+        self.conn.execute(
+            "UPDATE users SET lang = ?1 WHERE id = ?2",
+            params![lang.code(), user_id],
+        )?;
+        Ok(())
+    }
+
+    // ==================== SETTINGS METHODS ====================
+
+    /// Get user settings (None if setup was never completed)
+    pub fn get_settings(&self, user_id: i64) -> Result<Option<UserSettings>> {
+        let settings = self.conn.query_row(
+            "SELECT user_id, tz_offset_minutes, base_exercises, reminders_enabled FROM user_settings WHERE user_id = ?1",
+            [user_id],
+            |row| {
+                let base: String = row.get(2)?;
+                Ok(UserSettings {
+                    user_id: row.get(0)?,
+                    tz_offset_minutes: row.get(1)?,
+                    base_exercises: base
+                        .split(',')
+                        .filter(|id| !id.is_empty())
+                        .map(String::from)
+                        .collect(),
+                    reminders_enabled: row.get(3)?,
+                })
+            },
+        );
+
+        match settings {
+            Ok(s) => Ok(Some(s)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Create or replace user settings (the timezone is mirrored to `users`)
+    pub fn save_settings(&self, settings: &UserSettings) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO user_settings (user_id, tz_offset_minutes, base_exercises, reminders_enabled) VALUES (?1, ?2, ?3, ?4)",
             params![
-                training.date.to_rfc3339(),
-                training.exercise,
-                training.sets,
-                training.reps,
-                training.duration_secs,
-                training.pulse_before,
-                training.pulse_after,
-                training.notes,
+                settings.user_id,
+                settings.tz_offset_minutes,
+                settings.base_exercises.join(","),
+                settings.reminders_enabled,
             ],
         )?;
+        self.conn.execute(
+            "UPDATE users SET tz_offset_minutes = ?1 WHERE id = ?2",
+            params![settings.tz_offset_minutes, settings.user_id],
+        )?;
+        Ok(())
+    }
+
+    // ==================== TEMPLATE METHODS ====================
+
+    /// Get user's weekday focus template (empty if never set)
+    pub fn get_weekday_template(&self, user_id: i64) -> Result<WeekdayTemplate> {
+        let json = self.conn.query_row(
+            "SELECT template FROM weekday_templates WHERE user_id = ?1",
+            [user_id],
+            |row| row.get::<_, String>(0),
+        );
+
+        match json {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(WeekdayTemplate::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Create or replace user's weekday focus template
+    pub fn save_weekday_template(&self, user_id: i64, template: &WeekdayTemplate) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO weekday_templates (user_id, template) VALUES (?1, ?2)",
+            params![user_id, serde_json::to_string(template)?],
+        )?;
+        Ok(())
+    }
+
+    // ==================== REST DAY METHODS ====================
+
+    /// Mark a local date as a planned rest day. Returns false if already marked.
+    pub fn add_rest_day(&self, user_id: i64, date: NaiveDate) -> Result<bool> {
+        let inserted = self.conn.execute(
+            "INSERT OR IGNORE INTO rest_days (user_id, date) VALUES (?1, ?2)",
+            params![user_id, date.to_string()],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// All planned rest days of a user
+    pub fn get_rest_days(&self, user_id: i64) -> Result<HashSet<NaiveDate>> {
+        let mut stmt = self.conn.prepare("SELECT date FROM rest_days WHERE user_id = ?1")?;
+        let days = stmt.query_map([user_id], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>, _>>()?
+            .iter()
+            .filter_map(|d| d.parse().ok())
+            .collect();
+        Ok(days)
+    }
+
+    // ==================== REMINDER SUBSCRIPTION METHODS ====================
+
+    /// Subscribe a chat to reminders. Returns false if already subscribed.
+    pub fn add_subscriber(&self, chat_id: i64) -> Result<bool> {
+        let inserted = self.conn.execute(
+            "INSERT OR IGNORE INTO reminder_subscriptions (chat_id, created_at) VALUES (?1, ?2)",
+            params![chat_id, Utc::now().to_rfc3339()],
+        )?;
+        Ok(inserted > 0)
+    }
+
+    /// Unsubscribe a chat. Returns false if it wasn't subscribed.
+    pub fn remove_subscriber(&self, chat_id: i64) -> Result<bool> {
+        let removed = self.conn.execute(
+            "DELETE FROM reminder_subscriptions WHERE chat_id = ?1",
+            [chat_id],
+        )?;
+        Ok(removed > 0)
+    }
+
+    /// All chats subscribed to reminders
+    pub fn get_subscribers(&self) -> Result<Vec<i64>> {
+        let mut stmt = self.conn.prepare("SELECT chat_id FROM reminder_subscriptions ORDER BY chat_id")?;
+        let chats = stmt.query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(chats)
+    }
+
+    /// Set (or clear with None) a subscriber's quiet hours as local
+    /// `(start, end)` hours. Returns false if the chat isn't subscribed.
+    pub fn set_quiet_hours(&self, chat_id: i64, hours: Option<(u32, u32)>) -> Result<bool> {
+        let updated = self.conn.execute(
+            "UPDATE reminder_subscriptions SET quiet_start = ?2, quiet_end = ?3 WHERE chat_id = ?1",
+            params![chat_id, hours.map(|h| h.0), hours.map(|h| h.1)],
+        )?;
+        Ok(updated > 0)
+    }
+
+    /// A subscriber's quiet hours, if set
+    pub fn get_quiet_hours(&self, chat_id: i64) -> Result<Option<(u32, u32)>> {
+        let hours = self.conn.query_row(
+            "SELECT quiet_start, quiet_end FROM reminder_subscriptions WHERE chat_id = ?1",
+            [chat_id],
+            |row| Ok((row.get::<_, Option<u32>>(0)?, row.get::<_, Option<u32>>(1)?)),
+        );
+        match hours {
+            Ok((Some(start), Some(end))) => Ok(Some((start, end))),
+            Ok(_) | Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // ==================== GOAL METHODS ====================
+
+    /// Add a target for an exercise. Returns the goal id.
+    pub fn add_goal(&self, user_id: i64, exercise: &str, target_value: i32, is_timed: bool) -> Result<i64> {
+        if target_value <= 0 {
+            anyhow::bail!("Goal target must be positive, got {}", target_value);
+        }
+        self.conn.execute(
+            "INSERT INTO goals (user_id, exercise, target_value, is_timed, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![user_id, exercise, target_value, is_timed, Utc::now().to_rfc3339()],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    /// A user's goals: open ones first, then achieved, oldest first within each
+    pub fn get_goals(&self, user_id: i64) -> Result<Vec<Goal>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {GOAL_COLUMNS} FROM goals WHERE user_id = ?1
+             ORDER BY achieved_at IS NOT NULL, created_at, id"
+        ))?;
+        let goals = stmt.query_map([user_id], goal_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(goals)
+    }
+
+    /// Delete a goal if it belongs to `user_id`. Returns whether a row was removed.
+    pub fn delete_goal(&self, id: i64, user_id: i64) -> Result<bool> {
+        let deleted = self.conn.execute(
+            "DELETE FROM goals WHERE id = ?1 AND user_id = ?2",
+            params![id, user_id],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// Mark open goals of `exercise` reached by `value` as achieved at `at`.
+    /// Returns the goals achieved just now.
+    pub fn achieve_goals(&self, user_id: i64, exercise: &str, value: i32, at: DateTime<Utc>) -> Result<Vec<Goal>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {GOAL_COLUMNS} FROM goals
+             WHERE user_id = ?1 AND exercise = ?2 AND achieved_at IS NULL AND target_value <= ?3
+             ORDER BY target_value"
+        ))?;
+        let mut achieved = stmt.query_map(params![user_id, exercise, value], goal_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        for goal in &mut achieved {
+            self.conn.execute(
+                "UPDATE goals SET achieved_at = ?1 WHERE id = ?2",
+                params![at.to_rfc3339(), goal.id],
+            )?;
+            goal.achieved_at = Some(at);
+        }
+        Ok(achieved)
+    }
+
+    // ==================== DAILY TIP METHODS ====================
+
+    /// Send a tip every day at local `hour`, or stop with None.
+    /// Returns false when turning off a chat that wasn't subscribed.
+    pub fn set_daily_tip(&self, chat_id: i64, hour: Option<u32>) -> Result<bool> {
+        let changed = match hour {
+            Some(hour) => self.conn.execute(
+                "INSERT INTO daily_tips (chat_id, hour) VALUES (?1, ?2)
+                 ON CONFLICT(chat_id) DO UPDATE SET hour = excluded.hour",
+                params![chat_id, hour],
+            )?,
+            None => self.conn.execute("DELETE FROM daily_tips WHERE chat_id = ?1", [chat_id])?,
+        };
+        Ok(changed > 0)
+    }
+
+    /// Local hour of a chat's daily tip, if enabled
+    pub fn get_daily_tip_hour(&self, chat_id: i64) -> Result<Option<u32>> {
+        let hour = self.conn.query_row(
+            "SELECT hour FROM daily_tips WHERE chat_id = ?1",
+            [chat_id],
+            |row| row.get(0),
+        );
+        match hour {
+            Ok(hour) => Ok(Some(hour)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// All daily tip subscriptions with their users' timezones
+    pub fn get_daily_tip_subscriptions(&self) -> Result<Vec<DailyTipSubscription>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT d.chat_id, d.hour, u.tz_offset_minutes, d.last_sent_date, d.last_category
+             FROM daily_tips d LEFT JOIN users u ON u.chat_id = d.chat_id
+             ORDER BY d.chat_id"
+        )?;
+        let subscriptions = stmt.query_map([], |row| {
+            Ok(DailyTipSubscription {
+                chat_id: row.get(0)?,
+                hour: row.get(1)?,
+                tz_offset_minutes: row.get::<_, Option<i32>>(2)?.unwrap_or(DEFAULT_TZ_OFFSET_MINUTES),
+                last_sent: row
+                    .get::<_, Option<String>>(3)?
+                    .and_then(|d| NaiveDate::parse_from_str(&d, "%Y-%m-%d").ok()),
+                last_category: row
+                    .get::<_, Option<String>>(4)?
+                    .and_then(|c| TipCategory::from_id(&c)),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+        Ok(subscriptions)
+    }
+
+    /// Remember the local date and category of the tip just sent
+    pub fn mark_daily_tip_sent(&self, chat_id: i64, date: NaiveDate, category: TipCategory) -> Result<()> {
+        self.conn.execute(
+            "UPDATE daily_tips SET last_sent_date = ?2, last_category = ?3 WHERE chat_id = ?1",
+            params![chat_id, date.format("%Y-%m-%d").to_string(), category.id()],
+        )?;
+        Ok(())
+    }
+
+    // ==================== ACCESS REQUEST METHODS ====================
+
+    /// Store (or refresh) a chat's access request; a new request clears an earlier denial
+    pub fn add_pending_request(
+        &self,
+        chat_id: i64,
+        username: Option<&str>,
+        first_name: Option<&str>,
+        message: &str,
+    ) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO pending_requests (chat_id, username, first_name, message, created_at, denied)
+             VALUES (?1, ?2, ?3, ?4, ?5, 0)",
+            params![chat_id, username, first_name, message, Utc::now().to_rfc3339()],
+        )?;
+        Ok(())
+    }
+
+    /// A chat's access request, pending or denied
+    pub fn get_pending_request(&self, chat_id: i64) -> Result<Option<PendingRequest>> {
+        let result = self.conn.query_row(
+            "SELECT chat_id, username, first_name, message, created_at, denied FROM pending_requests WHERE chat_id = ?1",
+            [chat_id],
+            |row| {
+                let date_str: String = row.get(4)?;
+                Ok(PendingRequest {
+                    chat_id: row.get(0)?,
+                    username: row.get(1)?,
+                    first_name: row.get(2)?,
+                    message: row.get(3)?,
+                    created_at: DateTime::parse_from_rfc3339(&date_str)
+                        .map(|d| d.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                    denied: row.get(5)?,
+                })
+            },
+        );
+        match result {
+            Ok(request) => Ok(Some(request)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Grant access: create the requesting user past the cap and drop the request.
+    /// None if the chat never asked for access.
+    pub fn approve_request(&self, chat_id: i64) -> Result<Option<User>> {
+        let Some(request) = self.get_pending_request(chat_id)? else {
+            return Ok(None);
+        };
+        let user = self.get_or_create_user(
+            chat_id,
+            request.username.as_deref(),
+            request.first_name.as_deref(),
+        )?;
+        self.conn.execute("DELETE FROM pending_requests WHERE chat_id = ?1", [chat_id])?;
+        Ok(Some(user))
+    }
+
+    /// Record a denial; false if the chat never asked for access
+    pub fn deny_request(&self, chat_id: i64) -> Result<bool> {
+        let updated = self.conn.execute(
+            "UPDATE pending_requests SET denied = 1 WHERE chat_id = ?1",
+            [chat_id],
+        )?;
+        Ok(updated > 0)
+    }
+
+    // ==================== BODYWEIGHT METHODS ====================
+
+    /// Record a bodyweight measurement; rejects values outside `BODYWEIGHT_RANGE_KG`
+    pub fn add_bodyweight(&self, user_id: i64, date: DateTime<Utc>, weight_kg: f64) -> Result<i64> {
+        if !BODYWEIGHT_RANGE_KG.contains(&weight_kg) {
+            anyhow::bail!(
+                "Weight {} kg is outside {}..={} kg",
+                weight_kg, BODYWEIGHT_RANGE_KG.start(), BODYWEIGHT_RANGE_KG.end()
+            );
+        }
+        self.conn.execute(
+            "INSERT INTO bodyweight (user_id, date, weight_kg) VALUES (?1, ?2, ?3)",
+            params![user_id, date.to_rfc3339(), weight_kg],
+        )?;
         Ok(self.conn.last_insert_rowid())
     }
 
+    /// A user's bodyweight measurements, newest first
+    pub fn get_bodyweights_for_user(&self, user_id: i64) -> Result<Vec<Bodyweight>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, user_id, date, weight_kg FROM bodyweight WHERE user_id = ?1 ORDER BY date DESC, id DESC"
+        )?;
+        let weights = stmt.query_map([user_id], bodyweight_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(weights)
+    }
+
+    /// A user's most recent bodyweight measurement
+    pub fn latest_bodyweight(&self, user_id: i64) -> Result<Option<Bodyweight>> {
+        let result = self.conn.query_row(
+            "SELECT id, user_id, date, weight_kg FROM bodyweight WHERE user_id = ?1 ORDER BY date DESC, id DESC LIMIT 1",
+            [user_id],
+            bodyweight_from_row,
+        );
+        match result {
+            Ok(weight) => Ok(Some(weight)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // ==================== SORENESS METHODS ====================
+
+    /// Record a sore muscle group (its Russian name) for a local date
+    pub fn add_soreness(&self, user_id: i64, date: NaiveDate, muscle_group: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO soreness (user_id, date, muscle_group) VALUES (?1, ?2, ?3)",
+            params![user_id, date.to_string(), muscle_group],
+        )?;
+        Ok(())
+    }
+
+    /// Muscle groups reported sore on or after `since`
+    pub fn get_sore_groups(&self, user_id: i64, since: NaiveDate) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT muscle_group FROM soreness WHERE user_id = ?1 AND date >= ?2 ORDER BY muscle_group"
+        )?;
+        let groups = stmt.query_map(params![user_id, since.to_string()], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(groups)
+    }
+
+    // ==================== TRAINING METHODS ====================
+
+    /// Add training record without user (CLI backward compatibility)
+    pub fn add_training_cli(&self, training: &Training) -> Result<i64> {
+        insert_training(&mut self.conn.prepare(TRAINING_INSERT)?, training, None)
+    }
+
     /// Add new training record for a user
     pub fn add_training(&self, training: &Training, user_id: i64) -> Result<i64> {
-        self.conn.execute(
-            "INSERT INTO trainings (date, exercise, sets, reps, duration_secs, pulse_before, pulse_after, notes, user_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        insert_training(&mut self.conn.prepare(TRAINING_INSERT)?, training, Some(user_id))
+    }
+
+    /// Add a training for a user and return it as stored (with `id` and `user_id` set)
+    pub fn add_training_returning(&self, training: &Training, user_id: i64) -> Result<Training> {
+        let id = self.add_training(training, user_id)?;
+        Ok(Training {
+            id: Some(id),
+            user_id: Some(user_id),
+            ..training.clone()
+        })
+    }
+
+    /// Move a training of `user_id` to the trash: hidden from every getter until
+    /// restored or purged. Returns whether a row was moved.
+    pub fn delete_training(&self, id: i64, user_id: i64) -> Result<bool> {
+        let deleted = self.conn.execute(
+            "UPDATE trainings SET deleted_at = ?3 WHERE id = ?1 AND user_id = ?2 AND deleted_at IS NULL",
+            params![id, user_id, Utc::now().to_rfc3339()],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// Take a training of `user_id` back out of the trash. Returns whether a row was restored.
+    pub fn restore_training(&self, id: i64, user_id: i64) -> Result<bool> {
+        let restored = self.conn.execute(
+            "UPDATE trainings SET deleted_at = NULL WHERE id = ?1 AND user_id = ?2 AND deleted_at IS NOT NULL",
+            params![id, user_id],
+        )?;
+        Ok(restored > 0)
+    }
+
+    /// Permanently remove trainings trashed before `older_than`. Returns how many were removed.
+    pub fn purge_trash(&self, older_than: DateTime<Utc>) -> Result<usize> {
+        let purged = self.conn.execute(
+            "DELETE FROM trainings WHERE deleted_at IS NOT NULL AND deleted_at < ?1",
+            [older_than.to_rfc3339()],
+        )?;
+        Ok(purged)
+    }
+
+    /// Delete a CLI training (one without a user) right away. Returns whether a row was removed.
+    pub fn delete_training_cli(&self, id: i64) -> Result<bool> {
+        let deleted = self.conn.execute(
+            "DELETE FROM trainings WHERE id = ?1 AND user_id IS NULL",
+            [id],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// Overwrite a stored training (matched by `id` and `user_id`).
+    /// Returns false if no such training belongs to the user.
+    pub fn update_training(&self, training: &Training) -> Result<bool> {
+        let id = training.id.ok_or_else(|| anyhow::anyhow!("update_training needs a training id"))?;
+        // Re-snapshot from the catalog: a loaded training still carries the category
+        // of its old exercise, which must not stick when the exercise is edited
+        let catalog = find_exercise_by_name(&training.exercise);
+        let category = catalog.map(|ex| ex.category).or(training.exercise_category());
+        let is_timed = catalog.map(|ex| ex.is_timed).or(training.exercise_is_timed());
+        let updated = self.conn.execute(
+            "UPDATE trainings SET date = ?3, exercise = ?4, sets = ?5, reps = ?6, duration_secs = ?7,
+                pulse_before = ?8, pulse_after = ?9, notes = ?10, context = ?11, rpe = ?12,
+                category = ?13, is_timed = ?14
+             WHERE id = ?1 AND user_id = ?2 AND deleted_at IS NULL",
             params![
+                id,
+                training.user_id,
                 training.date.to_rfc3339(),
                 training.exercise,
                 training.sets,
@@ -257,322 +1499,1690 @@ impl Database {
                 training.pulse_before,
                 training.pulse_after,
                 training.notes,
-                user_id,
+                training.context,
+                training.rpe,
+                category.map(|c| c.id()),
+                is_timed,
             ],
         )?;
-        Ok(self.conn.last_insert_rowid())
+        Ok(updated > 0)
+    }
+
+    /// Insert many trainings for a user in one transaction (for imports and seeding).
+    /// Either all rows are inserted or none: an invalid row (empty exercise name,
+    /// no sets, negative reps) or a failed insert rolls the whole batch back.
+    /// Takes `&self` like the rest of the API (the transaction is `unchecked`).
+    pub fn add_trainings_batch(&self, trainings: &[Training], user_id: i64) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare(TRAINING_INSERT)?;
+            for (index, training) in trainings.iter().enumerate() {
+                if training.exercise.trim().is_empty() || training.sets < 1 || training.reps < 0 {
+                    anyhow::bail!(
+                        "Invalid training in batch (row {}): exercise {:?}, {} sets, {} reps",
+                        index + 1, training.exercise, training.sets, training.reps
+                    );
+                }
+                insert_training(&mut stmt, training, Some(user_id))?;
+            }
+        }
+        tx.commit()?;
+        Ok(trainings.len())
+    }
+
+    /// Insert many trainings for a user in one transaction; same as `add_trainings_batch`
+    pub fn add_trainings_bulk(&self, trainings: &[Training], user_id: i64) -> Result<usize> {
+        self.add_trainings_batch(trainings, user_id)
+    }
+
+    /// Restore trainings from a JSON backup (a `Vec<Training>`). A row keeps
+    /// its `user_id` if that user exists here, otherwise it becomes a CLI
+    /// record. Rows without an exercise name are skipped and reported back.
+    pub fn import_json(&self, data: &str) -> Result<ImportReport> {
+        let trainings: Vec<Training> = serde_json::from_str(data)?;
+        let user_ids = self.conn.prepare("SELECT id FROM users")?
+            .query_map([], |row| row.get::<_, i64>(0))?
+            .collect::<Result<HashSet<_>, _>>()?;
+        let mut skipped = Vec::new();
+        let tx = self.conn.unchecked_transaction()?;
+        {
+            let mut stmt = tx.prepare(TRAINING_INSERT)?;
+            for (index, training) in trainings.iter().enumerate() {
+                if training.exercise.trim().is_empty() {
+                    skipped.push(format!("row {}: empty exercise", index + 1));
+                    continue;
+                }
+                let user_id = training.user_id.filter(|id| user_ids.contains(id));
+                insert_training(&mut stmt, training, user_id)?;
+            }
+        }
+        tx.commit()?;
+        Ok(ImportReport { inserted: trainings.len() - skipped.len(), skipped })
+    }
+
+    /// Get trainings for a specific user
+    pub fn get_trainings_for_user(&self, user_id: i64) -> Result<Vec<Training>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {TRAINING_COLUMNS} FROM trainings WHERE user_id = ?1 AND deleted_at IS NULL ORDER BY date DESC"
+        ))?;
+
+        let trainings = stmt.query_map([user_id], training_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(trainings)
+    }
+
+    /// Get all trainings (for CLI/backward compatibility)
+    pub fn get_trainings(&self) -> Result<Vec<Training>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {TRAINING_COLUMNS} FROM trainings WHERE deleted_at IS NULL ORDER BY date DESC"
+        ))?;
+
+        let trainings = stmt.query_map([], training_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(trainings)
+    }
+
+    /// One page of a user's trainings, newest first (ties broken by id so pages never overlap)
+    pub fn get_trainings_paged(&self, user_id: i64, limit: usize, offset: usize) -> Result<Vec<Training>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {TRAINING_COLUMNS} FROM trainings
+             WHERE user_id = ?1 AND deleted_at IS NULL
+             ORDER BY date DESC, id DESC
+             LIMIT ?2 OFFSET ?3"
+        ))?;
+
+        let trainings = stmt.query_map(params![user_id, limit as i64, offset as i64], training_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(trainings)
+    }
+
+    /// One page of all trainings, newest first (for the CLI)
+    pub fn get_all_trainings_paged(&self, limit: usize, offset: usize) -> Result<Vec<Training>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {TRAINING_COLUMNS} FROM trainings
+             WHERE deleted_at IS NULL
+             ORDER BY date DESC, id DESC
+             LIMIT ?1 OFFSET ?2"
+        ))?;
+
+        let trainings = stmt.query_map(params![limit as i64, offset as i64], training_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(trainings)
+    }
+
+    /// Count a user's trainings, the total behind `get_trainings_paged`
+    pub fn count_trainings(&self, user_id: i64) -> Result<usize> {
+        self.count_trainings_for_user(user_id)
+    }
+
+    /// Count all trainings (for the CLI)
+    pub fn count_all_trainings(&self) -> Result<usize> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM trainings WHERE deleted_at IS NULL",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
+    }
+
+    /// Write all trainings as CSV, newest first
+    pub fn export_csv<W: Write>(&self, mut writer: W) -> Result<()> {
+        writeln!(writer, "date,exercise,sets,reps,duration_secs,pulse_before,pulse_after,notes")?;
+        for t in self.get_trainings()? {
+            let opt = |v: Option<i32>| v.map(|v| v.to_string()).unwrap_or_default();
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{}",
+                t.date.to_rfc3339(),
+                csv_field(&t.exercise),
+                t.sets,
+                t.reps,
+                opt(t.duration_secs),
+                opt(t.pulse_before),
+                opt(t.pulse_after),
+                csv_field(t.notes.as_deref().unwrap_or("")),
+            )?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Get a user's trainings for the current calendar day in their timezone
+    pub fn get_trainings_today(&self, user_id: i64, tz_offset_secs: i32) -> Result<Vec<Training>> {
+        let (start, end) = day_bounds_utc(Utc::now(), tz_offset_secs);
+        self.get_trainings_between(Some(user_id), start, end)
+    }
+
+    /// `get_trainings_today` with the offset in minutes, as users store it (`/tz`)
+    pub fn get_trainings_today_minutes(&self, user_id: i64, tz_offset_minutes: i32) -> Result<Vec<Training>> {
+        self.get_trainings_today(user_id, tz_offset_minutes * 60)
+    }
+
+    /// A user's trainings with `from <= date < to`, newest first
+    pub fn get_trainings_in_range(
+        &self,
+        user_id: i64,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<Training>> {
+        self.get_trainings_between(Some(user_id), from, to)
+    }
+
+    /// Trainings on a local calendar date, newest first.
+    /// `user_id: None` covers all records (for the CLI).
+    pub fn get_trainings_on_day(
+        &self,
+        user_id: Option<i64>,
+        date: NaiveDate,
+        tz_offset_secs: i32,
+    ) -> Result<Vec<Training>> {
+        let (start, end) = date_bounds_utc(date, tz_offset_secs);
+        self.get_trainings_between(user_id, start, end)
+    }
+
+    /// Get trainings with `start <= date < end`, newest first (all users if `user_id` is None)
+    fn get_trainings_between(
+        &self,
+        user_id: Option<i64>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> Result<Vec<Training>> {
+        // Dates are stored as RFC3339 UTC (see migrate_date_formats), so plain string
+        // comparison is chronological and lets SQLite use idx_trainings_user_date
+        let (start, end) = (start.to_rfc3339(), end.to_rfc3339());
+        let trainings = match user_id {
+            Some(user_id) => self.conn.prepare(&format!(
+                "SELECT {TRAINING_COLUMNS} FROM trainings
+                 WHERE user_id = ?1 AND date >= ?2 AND date < ?3 AND deleted_at IS NULL
+                 ORDER BY date DESC"
+            ))?
+            .query_map(params![user_id, start, end], training_from_row)?
+            .collect::<Result<Vec<_>, _>>()?,
+            None => self.conn.prepare(&format!(
+                "SELECT {TRAINING_COLUMNS} FROM trainings
+                 WHERE date >= ?1 AND date < ?2 AND deleted_at IS NULL
+                 ORDER BY date DESC"
+            ))?
+            .query_map(params![start, end], training_from_row)?
+            .collect::<Result<Vec<_>, _>>()?,
+        };
+
+        Ok(trainings)
+    }
+
+    /// Get a user's trainings logged in a context, newest first
+    pub fn get_trainings_by_context(&self, user_id: i64, context: &str) -> Result<Vec<Training>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {TRAINING_COLUMNS} FROM trainings WHERE user_id = ?1 AND context = ?2 AND deleted_at IS NULL ORDER BY date DESC"
+        ))?;
+
+        let trainings = stmt.query_map(params![user_id, context], training_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(trainings)
+    }
+
+    /// Groups of a user's trainings that look double-logged: same exercise, reps and
+    /// duration, each row within `window_secs` of the previous one. Every group has
+    /// at least two rows, earliest first.
+    pub fn find_duplicate_trainings(&self, user_id: i64, window_secs: i64) -> Result<Vec<Vec<Training>>> {
+        // Only rows with a twin nearby; grouping into runs happens below
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {TRAINING_COLUMNS} FROM trainings AS a
+             WHERE a.user_id = ?1 AND a.deleted_at IS NULL
+               AND EXISTS (
+                   SELECT 1 FROM trainings AS b
+                   WHERE b.user_id = a.user_id AND b.id != a.id AND b.deleted_at IS NULL
+                     AND b.exercise = a.exercise AND b.reps = a.reps
+                     AND b.duration_secs IS a.duration_secs
+                     AND ABS(strftime('%s', b.date) - strftime('%s', a.date)) <= ?2
+               )
+             ORDER BY a.exercise, a.reps, a.duration_secs, datetime(a.date), a.id"
+        ))?;
+        let candidates = stmt.query_map(params![user_id, window_secs], training_from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut groups: Vec<Vec<Training>> = Vec::new();
+        for training in candidates {
+            let joins_last = groups.last().and_then(|group| group.last()).is_some_and(|prev| {
+                prev.exercise == training.exercise
+                    && prev.reps == training.reps
+                    && prev.duration_secs == training.duration_secs
+                    && (training.date - prev.date).num_seconds() <= window_secs
+            });
+            match groups.last_mut() {
+                Some(group) if joins_last => group.push(training),
+                _ => groups.push(vec![training]),
+            }
+        }
+        groups.retain(|group| group.len() > 1);
+        Ok(groups)
+    }
+
+    /// Distinct exercise names a user has logged, sorted by name
+    pub fn distinct_exercises(&self, user_id: i64) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT DISTINCT exercise FROM trainings WHERE user_id = ?1 AND deleted_at IS NULL ORDER BY exercise"
+        )?;
+        let names = stmt.query_map([user_id], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(names)
+    }
+
+    /// A user's per-exercise (name, entries, total reps as sets x reps, total seconds), sorted by name
+    pub fn exercise_summary(&self, user_id: i64) -> Result<Vec<(String, i64, i64, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT exercise, COUNT(*), COALESCE(SUM(sets * reps), 0), COALESCE(SUM(duration_secs), 0)
+             FROM trainings
+             WHERE user_id = ?1 AND deleted_at IS NULL
+             GROUP BY exercise
+             ORDER BY exercise"
+        )?;
+        let rows = stmt.query_map([user_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// `exercise_summary` over all records (for the CLI)
+    pub fn exercise_summary_all(&self) -> Result<Vec<(String, i64, i64, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT exercise, COUNT(*), COALESCE(SUM(sets * reps), 0), COALESCE(SUM(duration_secs), 0)
+             FROM trainings
+             WHERE deleted_at IS NULL
+             GROUP BY exercise
+             ORDER BY exercise"
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+        Ok(rows)
+    }
+
+    /// Migrate existing trainings to owner (call after first user registration)
+    pub fn migrate_trainings_to_owner(&self) -> Result<usize> {
+        if let Some(owner) = self.get_owner()? {
+            let affected = self.conn.execute(
+                "UPDATE trainings SET user_id = ?1 WHERE user_id IS NULL",
+                [owner.id],
+            )?;
+            Ok(affected)
+        } else {
+            Ok(0)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, TimeZone, Timelike};
+
+    fn create_test_db() -> Database {
+        Database::open(":memory:").unwrap()
+    }
+
+    fn create_test_training(exercise: &str, reps: i32) -> Training {
+        Training {
+            id: None,
+            date: Utc::now(),
+            exercise: exercise.to_string(),
+            sets: 1,
+            reps,
+            duration_secs: Some(30),
+            pulse_before: Some(80),
+            pulse_after: Some(120),
+            rpe: None,
+            notes: None,
+            user_id: None,
+            context: None,
+            category: None,
+            is_timed: None,
+        }
+    }
+
+    // ==================== Training::diff tests ====================
+
+    #[test]
+    fn test_diff_identical_is_empty() {
+        let t = create_test_training("пловец", 20);
+        assert!(t.diff(&t.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_reps_and_pulse() {
+        let before = create_test_training("пловец", 15);
+        let after = Training { reps: 18, pulse_after: Some(130), ..before.clone() };
+        assert_eq!(before.diff(&after), vec!["повторы: 15 → 18", "пульс после: 120 → 130"]);
+    }
+
+    #[test]
+    fn test_diff_optional_fields_set_and_cleared() {
+        let before = create_test_training("планка", 1);
+        let after = Training {
+            duration_secs: None,
+            rpe: Some(8),
+            notes: Some("на кулаках".to_string()),
+            ..before.clone()
+        };
+        assert_eq!(
+            before.diff(&after),
+            vec!["время: 30 → —", "тяжесть: — → 8", "заметка: — → на кулаках"]
+        );
+    }
+
+    #[test]
+    fn test_diff_exercise_and_sets() {
+        let before = create_test_training("пловец", 10);
+        let after = Training { exercise: "планка".to_string(), sets: 2, ..before.clone() };
+        assert_eq!(before.diff(&after), vec!["упражнение: пловец → планка", "подходы: 1 → 2"]);
+    }
+
+    // ==================== parse_date tests ====================
+
+    #[test]
+    fn test_parse_date_rfc3339() {
+        let date_str = "2026-01-06T12:30:00+00:00";
+        let parsed = parse_date(date_str);
+        assert_eq!(parsed.year(), 2026);
+        assert_eq!(parsed.month(), 1);
+        assert_eq!(parsed.day(), 6);
+    }
+
+    #[test]
+    fn test_parse_date_rfc3339_with_timezone() {
+        let date_str = "2026-01-06T15:30:00+03:00";
+        let parsed = parse_date(date_str);
+        // Should be converted to UTC: 15:30 + 03:00 = 12:30 UTC
+        assert_eq!(parsed.hour(), 12);
+    }
+
+    #[test]
+    fn test_parse_date_legacy_format() {
+        let date_str = "2026-01-05 14:12:29";
+        let parsed = parse_date(date_str);
+        assert_eq!(parsed.year(), 2026);
+        assert_eq!(parsed.month(), 1);
+        assert_eq!(parsed.day(), 5);
+        assert_eq!(parsed.hour(), 14);
+        assert_eq!(parsed.minute(), 12);
+    }
+
+    #[test]
+    fn test_parse_date_invalid_fallback_to_epoch() {
+        let date_str = "invalid-date";
+        let parsed = parse_date(date_str);
+        assert_eq!(parsed, DateTime::UNIX_EPOCH);
+    }
+
+    // ==================== Database tests ====================
+
+    #[test]
+    fn test_open_corrupt_file_starts_fresh() {
+        let dir = std::env::temp_dir().join(format!("majowuji-corrupt-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("majowuji.db");
+        std::fs::write(&path, vec![0x42u8; 4096]).unwrap();
+
+        let db = Database::open(path.to_str().unwrap()).unwrap();
+        assert_eq!(db.count_users().unwrap(), 0);
+
+        // Damaged file kept aside for manual inspection
+        let backups: Vec<_> = std::fs::read_dir(&dir).unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().starts_with("majowuji.db.corrupt-"))
+            .collect();
+        assert_eq!(backups.len(), 1);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_open_file_uses_wal() {
+        let dir = std::env::temp_dir().join(format!("majowuji-wal-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("majowuji.db");
+
+        let db = Database::open(path.to_str().unwrap()).unwrap();
+        let mode: String = db.conn.query_row("PRAGMA journal_mode", [], |row| row.get(0)).unwrap();
+        assert_eq!(mode, "wal");
+        drop(db);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_database_open_in_memory() {
+        let db = create_test_db();
+        assert_eq!(db.count_users().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_get_or_create_user_new() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, Some("test_user"), Some("Test")).unwrap();
+        assert_eq!(user.chat_id, 12345);
+        assert_eq!(user.username, Some("test_user".to_string()));
+        assert_eq!(user.first_name, Some("Test".to_string()));
+    }
+
+    #[test]
+    fn test_get_or_create_user_existing() {
+        let db = create_test_db();
+        let user1 = db.get_or_create_user(12345, Some("user1"), None).unwrap();
+        let user2 = db.get_or_create_user(12345, Some("user2"), None).unwrap();
+        // Should return same user
+        assert_eq!(user1.id, user2.id);
+        // Username should not change
+        assert_eq!(user2.username, Some("user1".to_string()));
+    }
+
+    #[test]
+    fn test_first_user_is_owner() {
+        let db = create_test_db();
+        let user1 = db.get_or_create_user(111, None, None).unwrap();
+        assert!(user1.is_owner, "First user should be owner");
+
+        let user2 = db.get_or_create_user(222, None, None).unwrap();
+        assert!(!user2.is_owner, "Second user should not be owner");
+    }
+
+    #[test]
+    fn test_get_user_by_chat_id_found() {
+        let db = create_test_db();
+        db.get_or_create_user(12345, Some("test"), None).unwrap();
+
+        let user = db.get_user_by_chat_id(12345).unwrap();
+        assert!(user.is_some());
+        assert_eq!(user.unwrap().chat_id, 12345);
+    }
+
+    #[test]
+    fn test_get_user_by_chat_id_not_found() {
+        let db = create_test_db();
+        let user = db.get_user_by_chat_id(99999).unwrap();
+        assert!(user.is_none());
+    }
+
+    #[test]
+    fn test_count_users() {
+        let db = create_test_db();
+        assert_eq!(db.count_users().unwrap(), 0);
+
+        db.get_or_create_user(111, None, None).unwrap();
+        assert_eq!(db.count_users().unwrap(), 1);
+
+        db.get_or_create_user(222, None, None).unwrap();
+        assert_eq!(db.count_users().unwrap(), 2);
+
+        // Same user again - should not increase count
+        db.get_or_create_user(111, None, None).unwrap();
+        assert_eq!(db.count_users().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_get_owner() {
+        let db = create_test_db();
+
+        // No owner initially
+        assert!(db.get_owner().unwrap().is_none());
+
+        // First user becomes owner
+        db.get_or_create_user(111, Some("owner"), None).unwrap();
+        let owner = db.get_owner().unwrap();
+        assert!(owner.is_some());
+        assert_eq!(owner.unwrap().chat_id, 111);
+    }
+
+    #[test]
+    fn test_set_bonus_unlock_percent() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        assert_eq!(user.bonus_unlock_percent, None);
+
+        db.set_bonus_unlock_percent(user.id, 80).unwrap();
+        let user = db.get_user_by_chat_id(12345).unwrap().unwrap();
+        assert_eq!(user.bonus_unlock_percent, Some(80));
+    }
+
+    #[test]
+    fn test_set_user_max_difficulty() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        assert_eq!(user.max_difficulty, None);
+
+        db.set_user_max_difficulty(user.id, Some(2)).unwrap();
+        assert_eq!(db.get_user_by_chat_id(12345).unwrap().unwrap().max_difficulty, Some(2));
+        assert!(db.set_user_max_difficulty(user.id, Some(6)).is_err());
+
+        db.set_user_max_difficulty(user.id, None).unwrap();
+        assert_eq!(db.get_user_by_chat_id(12345).unwrap().unwrap().max_difficulty, None);
+    }
+
+    #[test]
+    fn test_set_user_weekly_target() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        assert_eq!(user.weekly_target, None);
+
+        db.set_user_weekly_target(user.id, Some(4)).unwrap();
+        assert_eq!(db.get_user_by_chat_id(12345).unwrap().unwrap().weekly_target, Some(4));
+        assert!(db.set_user_weekly_target(user.id, Some(0)).is_err());
+        assert!(db.set_user_weekly_target(user.id, Some(8)).is_err());
+
+        db.set_user_weekly_target(user.id, None).unwrap();
+        assert_eq!(db.get_user_by_chat_id(12345).unwrap().unwrap().weekly_target, None);
+    }
+
+    #[test]
+    fn test_set_user_equipment() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        assert_eq!(user.equipment, None);
+
+        db.set_user_equipment(user.id, Some(&[Equipment::Handles, Equipment::Step])).unwrap();
+        let user = db.get_user_by_chat_id(12345).unwrap().unwrap();
+        assert_eq!(user.equipment, Some(vec![Equipment::Handles, Equipment::Step]));
+
+        // Empty gear list is kept distinct from "not restricted"
+        db.set_user_equipment(user.id, Some(&[])).unwrap();
+        let user = db.get_user_by_chat_id(12345).unwrap().unwrap();
+        assert_eq!(user.equipment, Some(vec![]));
+
+        db.set_user_equipment(user.id, None).unwrap();
+        let user = db.get_user_by_chat_id(12345).unwrap().unwrap();
+        assert_eq!(user.equipment, None);
+    }
+
+    #[test]
+    fn test_set_user_age() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        assert_eq!(user.age, None);
+
+        db.set_user_age(user.id, 35).unwrap();
+        let user = db.get_user_by_chat_id(12345).unwrap().unwrap();
+        assert_eq!(user.age, Some(35));
+    }
+
+    #[test]
+    fn test_get_settings_none_before_setup() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        assert!(db.get_settings(user.id).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_save_and_get_settings() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let settings = UserSettings {
+            user_id: user.id,
+            tz_offset_minutes: 420,
+            base_exercises: vec!["swimmer".to_string(), "pushups_fist".to_string()],
+            reminders_enabled: true,
+        };
+        db.save_settings(&settings).unwrap();
+        assert_eq!(db.get_settings(user.id).unwrap(), Some(settings.clone()));
+
+        // Saving again replaces the row
+        let updated = UserSettings { base_exercises: vec![], reminders_enabled: false, ..settings };
+        db.save_settings(&updated).unwrap();
+        assert_eq!(db.get_settings(user.id).unwrap(), Some(updated));
+    }
+
+    #[test]
+    fn test_weekday_template_roundtrip() {
+        use crate::exercises::Category;
+        use chrono::Weekday;
+
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        assert!(db.get_weekday_template(user.id).unwrap().is_empty());
+
+        let mut template = WeekdayTemplate::default();
+        template.set(Weekday::Thu, Some(Category::Push));
+        db.save_weekday_template(user.id, &template).unwrap();
+        assert_eq!(db.get_weekday_template(user.id).unwrap(), template);
+    }
+
+    #[test]
+    fn test_rest_days() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let day = NaiveDate::from_ymd_opt(2026, 1, 5).unwrap();
+
+        assert!(db.add_rest_day(user.id, day).unwrap());
+        assert!(!db.add_rest_day(user.id, day).unwrap(), "second mark is a no-op");
+
+        let days = db.get_rest_days(user.id).unwrap();
+        assert_eq!(days.len(), 1);
+        assert!(days.contains(&day));
+    }
+
+    #[test]
+    fn test_soreness_since_date() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let day = NaiveDate::from_ymd_opt(2026, 1, 5).unwrap();
+
+        db.add_soreness(user.id, day, "спина").unwrap();
+        db.add_soreness(user.id, day, "спина").unwrap(); // duplicate ignored
+        db.add_soreness(user.id, day.succ_opt().unwrap(), "плечи").unwrap();
+
+        assert_eq!(db.get_sore_groups(user.id, day).unwrap(), vec!["плечи", "спина"]);
+        assert_eq!(db.get_sore_groups(user.id, day.succ_opt().unwrap()).unwrap(), vec!["плечи"]);
+    }
+
+    #[test]
+    fn test_add_training_cli() {
+        let db = create_test_db();
+        let training = create_test_training("отжимания", 15);
+
+        let id = db.add_training_cli(&training).unwrap();
+        assert!(id > 0);
+
+        let trainings = db.get_trainings().unwrap();
+        assert_eq!(trainings.len(), 1);
+        assert_eq!(trainings[0].exercise, "отжимания");
+        assert_eq!(trainings[0].reps, 15);
+    }
+
+    #[test]
+    fn test_add_training_with_user() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let training = create_test_training("планка", 1);
+
+        let id = db.add_training(&training, user.id).unwrap();
+        assert!(id > 0);
+
+        let trainings = db.get_trainings_for_user(user.id).unwrap();
+        assert_eq!(trainings.len(), 1);
+        assert_eq!(trainings[0].user_id, Some(user.id));
+    }
+
+    #[test]
+    fn test_add_training_returning() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let training = create_test_training("пловец", 15);
+
+        let saved = db.add_training_returning(&training, user.id).unwrap();
+        assert_eq!(saved.id, Some(db.conn.last_insert_rowid()));
+        assert_eq!(saved.user_id, Some(user.id));
+        assert_eq!(saved.reps, 15);
+
+        let stored = db.get_trainings_for_user(user.id).unwrap();
+        assert_eq!(stored[0].id, saved.id);
+    }
+
+    #[test]
+    fn test_add_trainings_bulk() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let trainings: Vec<_> = (0..1000)
+            .map(|i| create_test_training("пловец", i % 30))
+            .collect();
+
+        let inserted = db.add_trainings_bulk(&trainings, user.id).unwrap();
+        assert_eq!(inserted, 1000);
+        assert_eq!(db.get_trainings_for_user(user.id).unwrap().len(), 1000);
+        // Committed: no transaction left open
+        assert!(db.conn.is_autocommit());
+    }
+
+    #[test]
+    fn test_add_trainings_batch_empty() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        assert_eq!(db.add_trainings_batch(&[], user.id).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_add_trainings_batch_invalid_row_rolls_back() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let trainings = vec![
+            create_test_training("пловец", 10),
+            create_test_training("", 10),
+            create_test_training("джекнайф", 12),
+        ];
+
+        let err = db.add_trainings_batch(&trainings, user.id).unwrap_err();
+        assert!(err.to_string().contains("row 2"));
+        assert!(db.get_trainings_for_user(user.id).unwrap().is_empty());
+        assert!(db.conn.is_autocommit());
+    }
+
+    #[test]
+    fn test_distinct_exercises_dedup_sorted() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let other = db.get_or_create_user(222, None, None).unwrap();
+
+        db.add_training(&create_test_training("пловец", 10), user.id).unwrap();
+        db.add_training(&create_test_training("джекнайф", 10), user.id).unwrap();
+        db.add_training(&create_test_training("пловец", 12), user.id).unwrap();
+        db.add_training(&create_test_training("чужое", 12), other.id).unwrap();
+
+        assert_eq!(db.distinct_exercises(user.id).unwrap(), vec!["джекнайф", "пловец"]);
+    }
+
+    #[test]
+    fn test_exercise_summary_groups_by_exercise() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let other = db.get_or_create_user(222, None, None).unwrap();
+
+        let mut swimmer = create_test_training("пловец", 10);
+        swimmer.sets = 3;
+        db.add_training(&swimmer, user.id).unwrap();
+        db.add_training(&create_test_training("пловец", 12), user.id).unwrap();
+        let mut plank = create_test_training("планка", 1);
+        plank.duration_secs = Some(60);
+        db.add_training(&plank, user.id).unwrap();
+        plank.duration_secs = None;
+        db.add_training(&plank, user.id).unwrap();
+        db.add_training(&create_test_training("пловец", 50), other.id).unwrap();
+
+        assert_eq!(
+            db.exercise_summary(user.id).unwrap(),
+            vec![
+                ("планка".to_string(), 2, 2, 60),
+                ("пловец".to_string(), 2, 42, 60),
+            ]
+        );
+
+        let all = db.exercise_summary_all().unwrap();
+        assert_eq!(all[1], ("пловец".to_string(), 3, 92, 90));
+    }
+
+    #[test]
+    fn test_get_users_with_counts() {
+        let db = create_test_db();
+        let quiet = db.get_or_create_user(111, Some("quiet"), None).unwrap();
+        let active = db.get_or_create_user(222, Some("active"), None).unwrap();
+        let idle = db.get_or_create_user(333, None, Some("Idle")).unwrap();
+
+        db.add_training(&create_test_training("пловец", 10), quiet.id).unwrap();
+        for reps in [10, 12, 14] {
+            db.add_training(&create_test_training("пловец", reps), active.id).unwrap();
+        }
+        db.add_training_cli(&create_test_training("пловец", 20)).unwrap();
+
+        let counts: Vec<(i64, usize)> = db.get_users_with_counts().unwrap()
+            .into_iter()
+            .map(|(user, count)| (user.id, count))
+            .collect();
+        assert_eq!(counts, vec![(active.id, 3), (quiet.id, 1), (idle.id, 0)]);
+    }
+
+    #[test]
+    fn test_weekly_leaderboard_ranks_and_respects_opt_out() {
+        let db = create_test_db();
+        let now = Utc::now();
+        let anna = db.get_or_create_user(111, None, Some("Анна")).unwrap();
+        let boris = db.get_or_create_user(222, None, Some("Борис")).unwrap();
+        let shy = db.get_or_create_user(333, None, Some("Тихий")).unwrap();
+
+        let mut set = create_test_training("пловец", 10);
+        set.sets = 2;
+        db.add_training(&set, anna.id).unwrap();
+        set.sets = 3;
+        db.add_training(&set, boris.id).unwrap();
+        db.add_training(&set, boris.id).unwrap();
+        db.add_training(&set, shy.id).unwrap();
+        let mut old = create_test_training("пловец", 10);
+        old.sets = 50;
+        old.date = now - Duration::days(8);
+        db.add_training(&old, anna.id).unwrap();
+
+        db.set_user_hide_from_board(shy.id, true).unwrap();
+        assert!(db.get_user_by_chat_id(333).unwrap().unwrap().hide_from_board);
+
+        // The sets above are stamped just after `now`
+        assert_eq!(
+            db.get_weekly_leaderboard(Utc::now()).unwrap(),
+            vec![(Some("Борис".to_string()), 6), (Some("Анна".to_string()), 2)]
+        );
+
+        db.set_user_hide_from_board(shy.id, false).unwrap();
+        assert_eq!(db.get_weekly_leaderboard(Utc::now()).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_find_duplicate_trainings_by_window() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let start = Utc::now() - Duration::hours(1);
+        let at = |secs: i64, reps: i32| {
+            let mut t = create_test_training("пловец", reps);
+            t.date = start + Duration::seconds(secs);
+            t
+        };
+
+        // A retry 3 s later: flagged
+        let first = db.add_training(&at(0, 15), user.id).unwrap();
+        let retry = db.add_training(&at(3, 15), user.id).unwrap();
+        // Same set repeated after a 2-minute rest: legitimate
+        db.add_training(&at(120, 15), user.id).unwrap();
+        // Different reps seconds later: legitimate
+        db.add_training(&at(5, 12), user.id).unwrap();
+
+        let groups = db.find_duplicate_trainings(user.id, 10).unwrap();
+        assert_eq!(groups.len(), 1);
+        let ids: Vec<_> = groups[0].iter().map(|t| t.id.unwrap()).collect();
+        assert_eq!(ids, vec![first, retry]);
+
+        // A wide window treats the rested set as a duplicate too
+        assert_eq!(db.find_duplicate_trainings(user.id, 300).unwrap()[0].len(), 3);
+
+        db.delete_training(retry, user.id).unwrap();
+        assert!(db.find_duplicate_trainings(user.id, 10).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_get_trainings_for_user_empty() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+
+        let trainings = db.get_trainings_for_user(user.id).unwrap();
+        assert!(trainings.is_empty());
+    }
+
+    #[test]
+    fn test_get_trainings_for_user_filters_by_user() {
+        let db = create_test_db();
+        let user1 = db.get_or_create_user(111, None, None).unwrap();
+        let user2 = db.get_or_create_user(222, None, None).unwrap();
+
+        db.add_training(&create_test_training("упр1", 10), user1.id).unwrap();
+        db.add_training(&create_test_training("упр2", 20), user2.id).unwrap();
+        db.add_training(&create_test_training("упр3", 30), user1.id).unwrap();
+
+        let user1_trainings = db.get_trainings_for_user(user1.id).unwrap();
+        assert_eq!(user1_trainings.len(), 2);
+
+        let user2_trainings = db.get_trainings_for_user(user2.id).unwrap();
+        assert_eq!(user2_trainings.len(), 1);
+    }
+
+    #[test]
+    fn test_trainings_ordered_desc() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+
+        // Add trainings (they get same timestamp in tests, but order should be by insert)
+        db.add_training(&create_test_training("first", 1), user.id).unwrap();
+        db.add_training(&create_test_training("second", 2), user.id).unwrap();
+
+        let trainings = db.get_trainings_for_user(user.id).unwrap();
+        // Last added should be first (DESC order)
+        assert_eq!(trainings[0].exercise, "second");
+    }
+
+    #[test]
+    fn test_migrate_trainings_to_owner() {
+        let db = create_test_db();
+
+        // Add CLI trainings (no user_id)
+        db.add_training_cli(&create_test_training("old1", 10)).unwrap();
+        db.add_training_cli(&create_test_training("old2", 20)).unwrap();
+
+        // Create owner
+        let owner = db.get_or_create_user(12345, None, None).unwrap();
+
+        // Migrate
+        let migrated = db.migrate_trainings_to_owner().unwrap();
+        assert_eq!(migrated, 2);
+
+        // Check owner now has those trainings
+        let trainings = db.get_trainings_for_user(owner.id).unwrap();
+        assert_eq!(trainings.len(), 2);
+    }
+
+    #[test]
+    fn test_trainings_user_date_index() {
+        let db = create_test_db();
+        let indexes: Vec<String> = {
+            let mut stmt = db.conn.prepare("PRAGMA index_list(trainings)").unwrap();
+            stmt.query_map([], |row| row.get(1)).unwrap().collect::<Result<_, _>>().unwrap()
+        };
+        assert!(indexes.iter().any(|name| name == "idx_trainings_user_date"));
+
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        for (exercise, days_ago) in [("old", 3), ("new", 0), ("mid", 1)] {
+            let mut training = create_test_training(exercise, 10);
+            training.date = Utc::now() - chrono::Duration::days(days_ago);
+            db.add_training(&training, user.id).unwrap();
+        }
+        let names: Vec<_> = db.get_trainings_for_user(user.id).unwrap()
+            .into_iter()
+            .map(|t| t.exercise)
+            .collect();
+        assert_eq!(names, ["new", "mid", "old"]);
+
+        // Range queries compare the raw date column, so the index still applies
+        let plan: Vec<String> = {
+            let mut stmt = db.conn.prepare(
+                "EXPLAIN QUERY PLAN SELECT id FROM trainings
+                 WHERE user_id = ?1 AND date >= ?2 AND date < ?3 AND deleted_at IS NULL
+                 ORDER BY date DESC"
+            ).unwrap();
+            stmt.query_map(params![user.id, "a", "b"], |row| row.get(3)).unwrap()
+                .collect::<Result<_, _>>().unwrap()
+        };
+        assert!(plan.iter().any(|step| step.contains("idx_trainings_user_date")), "{:?}", plan);
+    }
+
+    #[test]
+    fn test_migrate_date_formats() {
+        let db = create_test_db();
+        db.conn.execute(
+            "INSERT INTO trainings (date, exercise, sets, reps) VALUES ('2026-01-05 08:30:00', 'legacy', 1, 10)",
+            [],
+        ).unwrap();
+        db.conn.execute(
+            "INSERT INTO trainings (date, exercise, sets, reps) VALUES ('2026-01-04T09:00:00+03:00', 'modern', 1, 10)",
+            [],
+        ).unwrap();
+
+        assert_eq!(db.migrate_date_formats().unwrap(), 1);
+        assert_eq!(db.migrate_date_formats().unwrap(), 0);
+
+        let date_of = |exercise: &str| -> String {
+            db.conn.query_row("SELECT date FROM trainings WHERE exercise = ?1", [exercise], |row| row.get(0)).unwrap()
+        };
+        assert_eq!(date_of("legacy"), "2026-01-05T08:30:00+00:00");
+        assert_eq!(date_of("modern"), "2026-01-04T09:00:00+03:00");
+
+        // Newest first now holds across both former formats
+        let trainings = db.get_trainings().unwrap();
+        assert_eq!(trainings[0].exercise, "legacy");
     }
 
-    /// Get trainings for a specific user
-    pub fn get_trainings_for_user(&self, user_id: i64) -> Result<Vec<Training>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, date, exercise, sets, reps, duration_secs, pulse_before, pulse_after, notes, user_id FROM trainings WHERE user_id = ?1 ORDER BY date DESC"
-        )?;
-
-        let trainings = stmt.query_map([user_id], |row| {
-            let date_str: String = row.get(1)?;
-            Ok(Training {
-                id: Some(row.get(0)?),
-                date: parse_date(&date_str),
-                exercise: row.get(2)?,
-                sets: row.get(3)?,
-                reps: row.get(4)?,
-                duration_secs: row.get(5)?,
-                pulse_before: row.get(6)?,
-                pulse_after: row.get(7)?,
-                notes: row.get(8)?,
-                user_id: row.get(9)?,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
+    #[test]
+    fn test_migrate_trainings_no_owner() {
+        let db = create_test_db();
 
-        Ok(trainings)
+        // Add CLI trainings
+        db.add_training_cli(&create_test_training("old", 10)).unwrap();
+
+        // No owner yet
+        let migrated = db.migrate_trainings_to_owner().unwrap();
+        assert_eq!(migrated, 0);
     }
 
-    /// Get all trainings (for CLI/backward compatibility)
-    pub fn get_trainings(&self) -> Result<Vec<Training>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT id, date, exercise, sets, reps, duration_secs, pulse_before, pulse_after, notes, user_id FROM trainings ORDER BY date DESC"
-        )?;
-
-        let trainings = stmt.query_map([], |row| {
-            let date_str: String = row.get(1)?;
-            Ok(Training {
-                id: Some(row.get(0)?),
-                date: parse_date(&date_str),
-                exercise: row.get(2)?,
-                sets: row.get(3)?,
-                reps: row.get(4)?,
-                duration_secs: row.get(5)?,
-                pulse_before: row.get(6)?,
-                pulse_after: row.get(7)?,
-                notes: row.get(8)?,
-                user_id: row.get(9)?,
-            })
-        })?
-        .collect::<Result<Vec<_>, _>>()?;
+    #[test]
+    fn test_day_bounds_utc_moscow() {
+        // 22:30 UTC on Jan 5 is already Jan 6 in Moscow (UTC+3)
+        let now = DateTime::parse_from_rfc3339("2026-01-05T22:30:00+00:00").unwrap().with_timezone(&Utc);
+        let (start, end) = day_bounds_utc(now, 3 * 3600);
+        assert_eq!(start.to_rfc3339(), "2026-01-05T21:00:00+00:00");
+        assert_eq!(end.to_rfc3339(), "2026-01-06T21:00:00+00:00");
+    }
 
-        Ok(trainings)
+    #[test]
+    fn test_get_trainings_today() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let other = db.get_or_create_user(222, None, None).unwrap();
+
+        let mut old = create_test_training("вчера", 10);
+        old.date = Utc::now() - Duration::days(2);
+        db.add_training(&old, user.id).unwrap();
+        db.add_training(&create_test_training("сегодня", 10), user.id).unwrap();
+        db.add_training(&create_test_training("чужое", 10), other.id).unwrap();
+
+        let today = db.get_trainings_today(user.id, 3 * 3600).unwrap();
+        assert_eq!(today.len(), 1);
+        assert_eq!(today[0].exercise, "сегодня");
     }
 
-    /// Migrate existing trainings to owner (call after first user registration)
-    pub fn migrate_trainings_to_owner(&self) -> Result<usize> {
-        if let Some(owner) = self.get_owner()? {
-            let affected = self.conn.execute(
-                "UPDATE trainings SET user_id = ?1 WHERE user_id IS NULL",
-                [owner.id],
-            )?;
-            Ok(affected)
-        } else {
-            Ok(0)
+    #[test]
+    fn test_get_trainings_today_local_midnight() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        // UTC-5: local midnight is 05:00 UTC, so the UTC date alone would misplace these
+        let offset_minutes = -5 * 60;
+        let (start, end) = day_bounds_utc(Utc::now(), offset_minutes * 60);
+
+        for (exercise, date) in [
+            ("yesterday", start - Duration::seconds(1)),
+            ("midnight", start),
+            ("last_second", end - Duration::seconds(1)),
+        ] {
+            let mut training = create_test_training(exercise, 10);
+            training.date = date;
+            db.add_training(&training, user.id).unwrap();
         }
+
+        let today = db.get_trainings_today_minutes(user.id, offset_minutes).unwrap();
+        let names: Vec<&str> = today.iter().map(|t| t.exercise.as_str()).collect();
+        assert_eq!(names, vec!["last_second", "midnight"]);
+        assert_eq!(db.get_trainings_today(user.id, offset_minutes * 60).unwrap().len(), 2);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use chrono::{Datelike, Timelike};
+    #[test]
+    fn test_update_training_preserves_other_fields() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let original = Training {
+            notes: Some("легко".to_string()),
+            rpe: Some(6),
+            ..create_test_training("пловец", 10)
+        };
+        let id = db.add_training(&original, user.id).unwrap();
+
+        let mut stored = db.get_trainings_for_user(user.id).unwrap().remove(0);
+        stored.reps = 15;
+        stored.duration_secs = Some(45);
+        assert!(db.update_training(&stored).unwrap());
+
+        let updated = db.get_trainings_for_user(user.id).unwrap().remove(0);
+        assert_eq!(updated.id, Some(id));
+        assert_eq!(updated.reps, 15);
+        assert_eq!(updated.duration_secs, Some(45));
+        assert_eq!(updated.pulse_before, Some(80));
+        assert_eq!(updated.pulse_after, Some(120));
+        assert_eq!(updated.notes.as_deref(), Some("легко"));
+        assert_eq!(updated.rpe, Some(6));
+        assert_eq!(updated.exercise, "пловец");
+    }
 
-    fn create_test_db() -> Database {
-        Database::open(":memory:").unwrap()
+    #[test]
+    fn test_update_training_refreshes_snapshot() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        db.add_training(&create_test_training("стойка на локтях", 1), user.id).unwrap();
+
+        let mut stored = db.get_trainings_for_user(user.id).unwrap().remove(0);
+        assert_eq!(stored.category, Some(Category::Core));
+        stored.exercise = "отжимания на кулаках".to_string();
+        stored.reps = 20;
+        assert!(db.update_training(&stored).unwrap());
+
+        let (category, is_timed): (Option<String>, Option<bool>) = db.conn.query_row(
+            "SELECT category, is_timed FROM trainings WHERE id = ?1",
+            [stored.id.unwrap()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        ).unwrap();
+        assert_eq!(category.as_deref(), Some(Category::Push.id()));
+        assert_eq!(is_timed, Some(false));
+
+        // Off-catalog names keep the snapshot they already had
+        let mut renamed = db.get_trainings_for_user(user.id).unwrap().remove(0);
+        renamed.exercise = "old pushups".to_string();
+        assert!(db.update_training(&renamed).unwrap());
+        let renamed = db.get_trainings_for_user(user.id).unwrap().remove(0);
+        assert_eq!(renamed.exercise_category(), Some(Category::Push));
+        assert_eq!(renamed.exercise_is_timed(), Some(false));
     }
 
-    fn create_test_training(exercise: &str, reps: i32) -> Training {
-        Training {
-            id: None,
-            date: Utc::now(),
-            exercise: exercise.to_string(),
-            sets: 1,
-            reps,
-            duration_secs: Some(30),
-            pulse_before: Some(80),
-            pulse_after: Some(120),
-            notes: None,
-            user_id: None,
-        }
+    #[test]
+    fn test_update_training_unknown_or_foreign() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let other = db.get_or_create_user(222, None, None).unwrap();
+        db.add_training(&create_test_training("пловец", 10), user.id).unwrap();
+        let stored = db.get_trainings_for_user(user.id).unwrap().remove(0);
+
+        let foreign = Training { user_id: Some(other.id), reps: 99, ..stored.clone() };
+        assert!(!db.update_training(&foreign).unwrap());
+        let missing = Training { id: Some(9999), ..stored.clone() };
+        assert!(!db.update_training(&missing).unwrap());
+        let no_id = Training { id: None, ..stored };
+        assert!(db.update_training(&no_id).is_err());
+
+        assert_eq!(db.get_trainings_for_user(user.id).unwrap()[0].reps, 10);
     }
 
-    // ==================== parse_date tests ====================
+    #[test]
+    fn test_export_csv() {
+        let db = create_test_db();
+        let date = Utc.with_ymd_and_hms(2026, 3, 1, 9, 30, 0).unwrap();
+        let training = Training {
+            date,
+            notes: Some("тяжело, но \"норм\"".to_string()),
+            ..create_test_training("Пловец", 12)
+        };
+        db.add_training_cli(&training).unwrap();
+        db.add_training_cli(&Training {
+            date: date - Duration::days(1),
+            pulse_before: None,
+            ..create_test_training("Планка", 1)
+        }).unwrap();
+
+        let mut out = Vec::new();
+        db.export_csv(&mut out).unwrap();
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines[0], "date,exercise,sets,reps,duration_secs,pulse_before,pulse_after,notes");
+        assert_eq!(lines[1], "2026-03-01T09:30:00+00:00,Пловец,1,12,30,80,120,\"тяжело, но \"\"норм\"\"\"");
+        assert_eq!(lines[2], "2026-02-28T09:30:00+00:00,Планка,1,1,30,,120,");
+        assert_eq!(lines.len(), 3);
+    }
 
     #[test]
-    fn test_parse_date_rfc3339() {
-        let date_str = "2026-01-06T12:30:00+00:00";
-        let parsed = parse_date(date_str);
-        assert_eq!(parsed.year(), 2026);
-        assert_eq!(parsed.month(), 1);
-        assert_eq!(parsed.day(), 6);
+    fn test_import_json_round_trip() {
+        let source = create_test_db();
+        let user = source.get_or_create_user(12345, None, None).unwrap();
+        source.add_training(&create_test_training("Пловец", 10), user.id).unwrap();
+        source.add_training(&Training {
+            notes: Some("после работы".to_string()),
+            ..create_test_training("Планка", 1)
+        }, user.id).unwrap();
+        source.add_training_cli(&create_test_training("Выпады", 12)).unwrap();
+        let backup = serde_json::to_string(&source.get_trainings().unwrap()).unwrap();
+
+        let target = create_test_db();
+        target.get_or_create_user(12345, None, None).unwrap();
+        let report = target.import_json(&backup).unwrap();
+        assert_eq!(report, ImportReport { inserted: 3, skipped: vec![] });
+
+        let restored = target.get_trainings().unwrap();
+        assert_eq!(restored.len(), source.get_trainings().unwrap().len());
+        assert_eq!(target.get_trainings_for_user(user.id).unwrap().len(), 2);
+        assert!(restored.iter().any(|t| t.notes.as_deref() == Some("после работы")));
     }
 
     #[test]
-    fn test_parse_date_rfc3339_with_timezone() {
-        let date_str = "2026-01-06T15:30:00+03:00";
-        let parsed = parse_date(date_str);
-        // Should be converted to UTC: 15:30 + 03:00 = 12:30 UTC
-        assert_eq!(parsed.hour(), 12);
+    fn test_import_json_skips_empty_exercise() {
+        let db = create_test_db();
+        let rows = vec![
+            create_test_training("Пловец", 10),
+            create_test_training("  ", 5),
+        ];
+        let data = serde_json::to_string(&rows).unwrap();
+        let report = db.import_json(&data).unwrap();
+        assert_eq!(report.inserted, 1);
+        assert_eq!(report.skipped, vec!["row 2: empty exercise".to_string()]);
+        assert_eq!(db.get_trainings().unwrap().len(), 1);
+
+        // Unknown user ids fall back to CLI records
+        let foreign = vec![Training { user_id: Some(777), ..create_test_training("Планка", 1) }];
+        assert_eq!(db.import_json(&serde_json::to_string(&foreign).unwrap()).unwrap().inserted, 1);
+        assert!(db.get_trainings().unwrap().iter().all(|t| t.user_id.is_none()));
+
+        assert!(db.import_json("not json").is_err());
     }
 
     #[test]
-    fn test_parse_date_legacy_format() {
-        let date_str = "2026-01-05 14:12:29";
-        let parsed = parse_date(date_str);
-        assert_eq!(parsed.year(), 2026);
-        assert_eq!(parsed.month(), 1);
-        assert_eq!(parsed.day(), 5);
-        assert_eq!(parsed.hour(), 14);
-        assert_eq!(parsed.minute(), 12);
+    fn test_subscribers_are_unique() {
+        let db = create_test_db();
+        assert!(db.add_subscriber(100).unwrap());
+        assert!(!db.add_subscriber(100).unwrap(), "second subscribe is a no-op");
+        db.add_subscriber(200).unwrap();
+        assert_eq!(db.get_subscribers().unwrap(), vec![100, 200]);
+
+        assert!(db.remove_subscriber(100).unwrap());
+        assert!(!db.remove_subscriber(100).unwrap());
+        assert_eq!(db.get_subscribers().unwrap(), vec![200]);
     }
 
     #[test]
-    fn test_parse_date_invalid_fallback_to_epoch() {
-        let date_str = "invalid-date";
-        let parsed = parse_date(date_str);
-        assert_eq!(parsed, DateTime::UNIX_EPOCH);
+    fn test_approve_request_creates_user() {
+        let db = create_test_db();
+        db.get_or_create_user(1, Some("owner"), None).unwrap();
+        assert!(db.approve_request(500).unwrap().is_none(), "no request yet");
+
+        db.add_pending_request(500, Some("guest"), Some("Гость"), "Пусти потренироваться").unwrap();
+        let request = db.get_pending_request(500).unwrap().unwrap();
+        assert_eq!(request.message, "Пусти потренироваться");
+        assert!(!request.denied);
+
+        let user = db.approve_request(500).unwrap().unwrap();
+        assert_eq!(user.chat_id, 500);
+        assert_eq!(user.username.as_deref(), Some("guest"));
+        assert!(!user.is_owner);
+        assert!(db.get_pending_request(500).unwrap().is_none());
     }
 
-    // ==================== Database tests ====================
+    #[test]
+    fn test_deny_request_is_recorded() {
+        let db = create_test_db();
+        assert!(!db.deny_request(500).unwrap());
+
+        db.add_pending_request(500, None, None, "привет").unwrap();
+        assert!(db.deny_request(500).unwrap());
+        assert!(db.get_pending_request(500).unwrap().unwrap().denied);
+        assert!(db.get_user_by_chat_id(500).unwrap().is_none());
+
+        // Asking again reopens the request
+        db.add_pending_request(500, None, None, "ещё раз").unwrap();
+        assert!(!db.get_pending_request(500).unwrap().unwrap().denied);
+    }
 
     #[test]
-    fn test_database_open_in_memory() {
+    fn test_goals_crud() {
         let db = create_test_db();
-        assert_eq!(db.count_users().unwrap(), 0);
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let other = db.get_or_create_user(222, None, None).unwrap();
+
+        let plank = db.add_goal(user.id, "стойка на локтях", 180, true).unwrap();
+        let swim = db.add_goal(user.id, "пловец", 30, false).unwrap();
+        assert!(db.add_goal(user.id, "пловец", 0, false).is_err());
+
+        let goals = db.get_goals(user.id).unwrap();
+        assert_eq!(goals.iter().map(|g| g.id).collect::<Vec<_>>(), vec![plank, swim]);
+        assert!(goals[0].is_timed);
+        assert_eq!(goals[0].target_value, 180);
+        assert_eq!(goals[0].achieved_at, None);
+
+        assert!(!db.delete_goal(swim, other.id).unwrap());
+        assert!(db.delete_goal(swim, user.id).unwrap());
+        assert_eq!(db.get_goals(user.id).unwrap().len(), 1);
     }
 
     #[test]
-    fn test_get_or_create_user_new() {
+    fn test_achieve_goals() {
         let db = create_test_db();
-        let user = db.get_or_create_user(12345, Some("test_user"), Some("Test")).unwrap();
-        assert_eq!(user.chat_id, 12345);
-        assert_eq!(user.username, Some("test_user".to_string()));
-        assert_eq!(user.first_name, Some("Test".to_string()));
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let near = db.add_goal(user.id, "пловец", 20, false).unwrap();
+        let far = db.add_goal(user.id, "пловец", 30, false).unwrap();
+        db.add_goal(user.id, "джекнайф", 10, false).unwrap();
+        let at = Utc::now();
+
+        // Below every target
+        assert!(db.achieve_goals(user.id, "пловец", 19, at).unwrap().is_empty());
+
+        // Meets the nearer target exactly
+        let achieved = db.achieve_goals(user.id, "пловец", 20, at).unwrap();
+        assert_eq!(achieved.iter().map(|g| g.id).collect::<Vec<_>>(), vec![near]);
+        assert!(achieved[0].achieved_at.is_some());
+
+        // Already achieved goals are not reported again
+        let achieved = db.achieve_goals(user.id, "пловец", 35, at).unwrap();
+        assert_eq!(achieved.iter().map(|g| g.id).collect::<Vec<_>>(), vec![far]);
+
+        // Open goals are listed before achieved ones
+        let goals = db.get_goals(user.id).unwrap();
+        assert_eq!(goals[0].exercise, "джекнайф");
+        assert!(goals[1..].iter().all(|g| g.achieved_at.is_some()));
     }
 
     #[test]
-    fn test_get_or_create_user_existing() {
+    fn test_daily_tip_subscription() {
         let db = create_test_db();
-        let user1 = db.get_or_create_user(12345, Some("user1"), None).unwrap();
-        let user2 = db.get_or_create_user(12345, Some("user2"), None).unwrap();
-        // Should return same user
-        assert_eq!(user1.id, user2.id);
-        // Username should not change
-        assert_eq!(user2.username, Some("user1".to_string()));
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        db.set_user_tz(user.id, 60).unwrap();
+
+        assert!(!db.set_daily_tip(12345, None).unwrap());
+        assert_eq!(db.get_daily_tip_hour(12345).unwrap(), None);
+        assert!(db.set_daily_tip(12345, Some(8)).unwrap());
+        assert!(db.set_daily_tip(12345, Some(7)).unwrap());
+        assert_eq!(db.get_daily_tip_hour(12345).unwrap(), Some(7));
+
+        let date = NaiveDate::from_ymd_opt(2026, 1, 5).unwrap();
+        db.mark_daily_tip_sent(12345, date, TipCategory::Nutrition).unwrap();
+        // Changing the hour keeps the dedup date
+        db.set_daily_tip(12345, Some(9)).unwrap();
+
+        let subscriptions = db.get_daily_tip_subscriptions().unwrap();
+        assert_eq!(subscriptions, vec![DailyTipSubscription {
+            chat_id: 12345,
+            hour: 9,
+            tz_offset_minutes: 60,
+            last_sent: Some(date),
+            last_category: Some(TipCategory::Nutrition),
+        }]);
+
+        assert!(db.set_daily_tip(12345, None).unwrap());
+        assert!(db.get_daily_tip_subscriptions().unwrap().is_empty());
     }
 
     #[test]
-    fn test_first_user_is_owner() {
+    fn test_quiet_hours() {
         let db = create_test_db();
-        let user1 = db.get_or_create_user(111, None, None).unwrap();
-        assert!(user1.is_owner, "First user should be owner");
+        assert!(!db.set_quiet_hours(100, Some((22, 7))).unwrap(), "not subscribed");
 
-        let user2 = db.get_or_create_user(222, None, None).unwrap();
-        assert!(!user2.is_owner, "Second user should not be owner");
+        db.add_subscriber(100).unwrap();
+        assert_eq!(db.get_quiet_hours(100).unwrap(), None);
+        assert!(db.set_quiet_hours(100, Some((22, 7))).unwrap());
+        assert_eq!(db.get_quiet_hours(100).unwrap(), Some((22, 7)));
+
+        // Re-subscribing keeps the window
+        db.add_subscriber(100).unwrap();
+        assert_eq!(db.get_quiet_hours(100).unwrap(), Some((22, 7)));
+
+        db.set_quiet_hours(100, None).unwrap();
+        assert_eq!(db.get_quiet_hours(100).unwrap(), None);
+        assert_eq!(db.get_quiet_hours(999).unwrap(), None);
     }
 
     #[test]
-    fn test_get_user_by_chat_id_found() {
+    fn test_bodyweight() {
         let db = create_test_db();
-        db.get_or_create_user(12345, Some("test"), None).unwrap();
-
-        let user = db.get_user_by_chat_id(12345).unwrap();
-        assert!(user.is_some());
-        assert_eq!(user.unwrap().chat_id, 12345);
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let other = db.get_or_create_user(222, None, None).unwrap();
+        assert!(db.latest_bodyweight(user.id).unwrap().is_none());
+
+        let now = Utc::now();
+        db.add_bodyweight(user.id, now - Duration::days(7), 73.2).unwrap();
+        db.add_bodyweight(user.id, now, 72.5).unwrap();
+        db.add_bodyweight(other.id, now, 90.0).unwrap();
+
+        let weights = db.get_bodyweights_for_user(user.id).unwrap();
+        assert_eq!(weights.len(), 2);
+        assert_eq!(weights[0].weight_kg, 72.5);
+        assert_eq!(weights[1].weight_kg, 73.2);
+        assert_eq!(db.latest_bodyweight(user.id).unwrap().unwrap().weight_kg, 72.5);
     }
 
     #[test]
-    fn test_get_user_by_chat_id_not_found() {
+    fn test_bodyweight_range() {
         let db = create_test_db();
-        let user = db.get_user_by_chat_id(99999).unwrap();
-        assert!(user.is_none());
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        assert!(db.add_bodyweight(user.id, Utc::now(), 19.9).is_err());
+        assert!(db.add_bodyweight(user.id, Utc::now(), 300.1).is_err());
+        assert!(db.add_bodyweight(user.id, Utc::now(), f64::NAN).is_err());
+        db.add_bodyweight(user.id, Utc::now(), 20.0).unwrap();
+        db.add_bodyweight(user.id, Utc::now(), 300.0).unwrap();
+        assert_eq!(db.get_bodyweights_for_user(user.id).unwrap().len(), 2);
     }
 
     #[test]
-    fn test_count_users() {
+    fn test_delete_training_checks_owner() {
         let db = create_test_db();
-        assert_eq!(db.count_users().unwrap(), 0);
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let other = db.get_or_create_user(222, None, None).unwrap();
+        let id = db.add_training(&create_test_training("пловец", 10), user.id).unwrap();
 
-        db.get_or_create_user(111, None, None).unwrap();
-        assert_eq!(db.count_users().unwrap(), 1);
+        assert!(!db.delete_training(id, other.id).unwrap(), "someone else's record");
+        assert_eq!(db.get_trainings_for_user(user.id).unwrap().len(), 1);
 
-        db.get_or_create_user(222, None, None).unwrap();
-        assert_eq!(db.count_users().unwrap(), 2);
+        assert!(db.delete_training(id, user.id).unwrap());
+        assert!(db.get_trainings_for_user(user.id).unwrap().is_empty());
+        assert!(!db.delete_training(id, user.id).unwrap(), "already gone");
+    }
 
-        // Same user again - should not increase count
-        db.get_or_create_user(111, None, None).unwrap();
-        assert_eq!(db.count_users().unwrap(), 2);
+    #[test]
+    fn test_soft_deleted_training_hidden_and_restorable() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let other = db.get_or_create_user(222, None, None).unwrap();
+        let id = db.add_training(&create_test_training("пловец", 10), user.id).unwrap();
+        db.add_training(&create_test_training("планка", 1), user.id).unwrap();
+
+        assert!(!db.restore_training(id, user.id).unwrap(), "not in the trash");
+        assert!(db.delete_training(id, user.id).unwrap());
+        assert_eq!(db.get_trainings_for_user(user.id).unwrap().len(), 1);
+        assert_eq!(db.get_trainings().unwrap().len(), 1);
+        assert_eq!(db.count_trainings_for_user(user.id).unwrap(), 1);
+        assert_eq!(db.distinct_exercises(user.id).unwrap(), vec!["планка"]);
+        assert_eq!(db.get_trainings_today(user.id, 0).unwrap().len(), 1);
+
+        assert!(!db.restore_training(id, other.id).unwrap(), "someone else's record");
+        assert!(db.restore_training(id, user.id).unwrap());
+        assert_eq!(db.get_trainings_for_user(user.id).unwrap().len(), 2);
     }
 
     #[test]
-    fn test_get_owner() {
+    fn test_purge_trash_removes_only_old_deletions() {
         let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let id = db.add_training(&create_test_training("пловец", 10), user.id).unwrap();
+        let kept = db.add_training(&create_test_training("планка", 1), user.id).unwrap();
+        db.delete_training(id, user.id).unwrap();
 
-        // No owner initially
-        assert!(db.get_owner().unwrap().is_none());
+        assert_eq!(db.purge_trash(Utc::now() - Duration::hours(24)).unwrap(), 0);
+        assert!(db.restore_training(id, user.id).unwrap());
+        db.delete_training(id, user.id).unwrap();
 
-        // First user becomes owner
-        db.get_or_create_user(111, Some("owner"), None).unwrap();
-        let owner = db.get_owner().unwrap();
-        assert!(owner.is_some());
-        assert_eq!(owner.unwrap().chat_id, 111);
+        assert_eq!(db.purge_trash(Utc::now() + Duration::seconds(1)).unwrap(), 1);
+        assert!(!db.restore_training(id, user.id).unwrap(), "purged for good");
+        assert_eq!(db.get_trainings_for_user(user.id).unwrap()[0].id, Some(kept));
     }
 
     #[test]
-    fn test_add_training_cli() {
+    fn test_delete_training_cli_only_userless() {
         let db = create_test_db();
-        let training = create_test_training("отжимания", 15);
-
-        let id = db.add_training_cli(&training).unwrap();
-        assert!(id > 0);
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let cli_id = db.add_training_cli(&create_test_training("пловец", 10)).unwrap();
+        let user_id = db.add_training(&create_test_training("пловец", 12), user.id).unwrap();
 
-        let trainings = db.get_trainings().unwrap();
-        assert_eq!(trainings.len(), 1);
-        assert_eq!(trainings[0].exercise, "отжимания");
-        assert_eq!(trainings[0].reps, 15);
+        assert!(!db.delete_training_cli(user_id).unwrap(), "bot user's record");
+        assert!(db.delete_training_cli(cli_id).unwrap());
+        assert_eq!(db.get_trainings().unwrap().len(), 1);
     }
 
     #[test]
-    fn test_add_training_with_user() {
+    fn test_trainings_by_context() {
         let db = create_test_db();
         let user = db.get_or_create_user(12345, None, None).unwrap();
-        let training = create_test_training("планка", 1);
 
-        let id = db.add_training(&training, user.id).unwrap();
-        assert!(id > 0);
+        let home = Training { context: Some("дома".to_string()), ..create_test_training("пловец", 10) };
+        let gym = Training { context: Some("зал".to_string()), ..create_test_training("пловец", 15) };
+        db.add_training(&home, user.id).unwrap();
+        db.add_training(&gym, user.id).unwrap();
+        db.add_training(&create_test_training("пловец", 12), user.id).unwrap();
 
-        let trainings = db.get_trainings_for_user(user.id).unwrap();
-        assert_eq!(trainings.len(), 1);
-        assert_eq!(trainings[0].user_id, Some(user.id));
+        let at_gym = db.get_trainings_by_context(user.id, "зал").unwrap();
+        assert_eq!(at_gym.len(), 1);
+        assert_eq!(at_gym[0].reps, 15);
+        assert_eq!(at_gym[0].context.as_deref(), Some("зал"));
     }
 
     #[test]
-    fn test_get_trainings_for_user_empty() {
+    fn test_set_current_context() {
         let db = create_test_db();
         let user = db.get_or_create_user(12345, None, None).unwrap();
+        assert_eq!(user.current_context, None);
 
-        let trainings = db.get_trainings_for_user(user.id).unwrap();
-        assert!(trainings.is_empty());
+        db.set_current_context(user.id, Some("дома")).unwrap();
+        let user = db.get_user_by_chat_id(12345).unwrap().unwrap();
+        assert_eq!(user.current_context.as_deref(), Some("дома"));
+
+        db.set_current_context(user.id, None).unwrap();
+        let user = db.get_user_by_chat_id(12345).unwrap().unwrap();
+        assert_eq!(user.current_context, None);
     }
 
     #[test]
-    fn test_get_trainings_for_user_filters_by_user() {
+    fn test_user_tz_offset_defaults_and_updates() {
         let db = create_test_db();
-        let user1 = db.get_or_create_user(111, None, None).unwrap();
-        let user2 = db.get_or_create_user(222, None, None).unwrap();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        assert_eq!(user.tz_offset_minutes, DEFAULT_TZ_OFFSET_MINUTES);
+        assert_eq!(db.user_tz_offset_minutes(user.id).unwrap(), DEFAULT_TZ_OFFSET_MINUTES);
+        assert_eq!(db.user_tz_offset_minutes(9999).unwrap(), DEFAULT_TZ_OFFSET_MINUTES);
+
+        db.set_user_tz(user.id, 600).unwrap();
+        assert_eq!(db.user_tz_offset_minutes(user.id).unwrap(), 600);
+        assert!(db.set_user_tz(user.id, 900).is_err());
+
+        // Setup wizard choice and /tz stay in sync
+        db.save_settings(&UserSettings {
+            user_id: user.id,
+            tz_offset_minutes: 420,
+            base_exercises: vec![],
+            reminders_enabled: false,
+        }).unwrap();
+        assert_eq!(db.get_user_by_chat_id(12345).unwrap().unwrap().tz_offset_minutes, 420);
+        db.set_user_tz(user.id, -300).unwrap();
+        assert_eq!(db.get_settings(user.id).unwrap().unwrap().tz_offset_minutes, -300);
+    }
 
-        db.add_training(&create_test_training("упр1", 10), user1.id).unwrap();
-        db.add_training(&create_test_training("упр2", 20), user2.id).unwrap();
-        db.add_training(&create_test_training("упр3", 30), user1.id).unwrap();
+    #[test]
+    fn test_user_tz_helper() {
+        assert_eq!(user_tz(600).local_minus_utc(), 36000);
+        assert_eq!(user_tz(-300).local_minus_utc(), -18000);
+        assert_eq!(user_tz(100_000).local_minus_utc(), DEFAULT_TZ_OFFSET_MINUTES * 60);
+    }
 
-        let user1_trainings = db.get_trainings_for_user(user1.id).unwrap();
-        assert_eq!(user1_trainings.len(), 2);
+    #[test]
+    fn test_set_user_lang() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        assert_eq!(user.lang, Lang::Ru);
 
-        let user2_trainings = db.get_trainings_for_user(user2.id).unwrap();
-        assert_eq!(user2_trainings.len(), 1);
+        db.set_user_lang(user.id, Lang::En).unwrap();
+        let user = db.get_user_by_chat_id(12345).unwrap().unwrap();
+        assert_eq!(user.lang, Lang::En);
     }
 
     #[test]
-    fn test_trainings_ordered_desc() {
+    fn test_get_trainings_on_day() {
         let db = create_test_db();
         let user = db.get_or_create_user(12345, None, None).unwrap();
+        let at = |rfc: &str| DateTime::parse_from_rfc3339(rfc).unwrap().with_timezone(&Utc);
+
+        // 21:30 UTC on Jan 4 is Jan 5 in Moscow; 21:30 UTC on Jan 5 is Jan 6
+        let mut early = create_test_training("пловец", 10);
+        early.date = at("2026-01-04T21:30:00+00:00");
+        let mut late = create_test_training("планка", 10);
+        late.date = at("2026-01-05T21:30:00+00:00");
+        db.add_training(&early, user.id).unwrap();
+        db.add_training(&late, user.id).unwrap();
+        let mut cli = create_test_training("cli", 10);
+        cli.date = at("2026-01-05T12:00:00+00:00");
+        db.add_training_cli(&cli).unwrap();
+
+        let jan5 = NaiveDate::from_ymd_opt(2026, 1, 5).unwrap();
+        let mine = db.get_trainings_on_day(Some(user.id), jan5, 3 * 3600).unwrap();
+        assert_eq!(mine.len(), 1);
+        assert_eq!(mine[0].exercise, "пловец");
+
+        let all = db.get_trainings_on_day(None, jan5, 3 * 3600).unwrap();
+        assert_eq!(all.len(), 2, "CLI view includes records without a user");
+    }
 
-        // Add trainings (they get same timestamp in tests, but order should be by insert)
-        db.add_training(&create_test_training("first", 1), user.id).unwrap();
-        db.add_training(&create_test_training("second", 2), user.id).unwrap();
-
-        let trainings = db.get_trainings_for_user(user.id).unwrap();
-        // Last added should be first (DESC order)
-        assert_eq!(trainings[0].exercise, "second");
+    #[test]
+    fn test_get_trainings_in_range_bounds() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let other = db.get_or_create_user(222, None, None).unwrap();
+        let at = |rfc: &str| DateTime::parse_from_rfc3339(rfc).unwrap().with_timezone(&Utc);
+        let from = at("2026-02-01T00:00:00+00:00");
+        let to = at("2026-02-08T00:00:00+00:00");
+
+        for (exercise, date) in [
+            ("before", "2026-01-31T23:59:59+00:00"),
+            ("at_from", "2026-02-01T00:00:00+00:00"),
+            ("inside", "2026-02-05T12:00:00+00:00"),
+            ("at_to", "2026-02-08T00:00:00+00:00"),
+        ] {
+            let mut training = create_test_training(exercise, 10);
+            training.date = at(date);
+            db.add_training(&training, user.id).unwrap();
+        }
+        let mut foreign = create_test_training("foreign", 10);
+        foreign.date = at("2026-02-03T00:00:00+00:00");
+        db.add_training(&foreign, other.id).unwrap();
+
+        let found = db.get_trainings_in_range(user.id, from, to).unwrap();
+        let names: Vec<&str> = found.iter().map(|t| t.exercise.as_str()).collect();
+        assert_eq!(names, vec!["inside", "at_from"], "lower bound inclusive, upper exclusive");
+        assert_eq!(db.count_trainings_for_user(user.id).unwrap(), 4);
     }
 
     #[test]
-    fn test_migrate_trainings_to_owner() {
+    fn test_get_trainings_today_legacy_date() {
         let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        db.conn.execute(
+            "INSERT INTO trainings (date, exercise, sets, reps, user_id) VALUES (?1, 'legacy', 1, 5, ?2)",
+            params![Utc::now().format("%Y-%m-%d %H:%M:%S").to_string(), user.id],
+        ).unwrap();
+        // Runs on open; legacy rows must be normalized before range queries see them
+        db.migrate_date_formats().unwrap();
+
+        let today = db.get_trainings_today(user.id, 0).unwrap();
+        assert_eq!(today.len(), 1);
+    }
 
-        // Add CLI trainings (no user_id)
-        db.add_training_cli(&create_test_training("old1", 10)).unwrap();
-        db.add_training_cli(&create_test_training("old2", 20)).unwrap();
+    #[test]
+    fn test_merge_users() {
+        let db = create_test_db();
+        let owner = db.get_or_create_user(1, None, None).unwrap();
+        let old = db.get_or_create_user(222, None, None).unwrap();
+        let new = db.get_or_create_user(333, None, None).unwrap();
+        db.add_training(&create_test_training("old_a", 10), old.id).unwrap();
+        db.add_training(&create_test_training("old_b", 12), old.id).unwrap();
+        db.add_training(&create_test_training("new_a", 8), new.id).unwrap();
+        db.add_training(&create_test_training("owner_a", 5), owner.id).unwrap();
+        let day = NaiveDate::from_ymd_opt(2026, 1, 5).unwrap();
+        db.add_rest_day(old.id, day).unwrap();
+        db.add_rest_day(new.id, day).unwrap();
+        db.add_bodyweight(old.id, Utc::now(), 70.0).unwrap();
+
+        assert!(db.merge_users(old.id, old.id).is_err(), "self-merge");
+        assert!(db.merge_users(owner.id, new.id).is_err(), "owner can't be merged away");
+        assert_eq!(db.count_trainings_for_user(owner.id).unwrap(), 1);
+
+        assert_eq!(db.merge_users(old.id, new.id).unwrap(), 2);
+        assert!(db.get_user_by_chat_id(222).unwrap().is_none(), "source user deleted");
+        let mut names: Vec<String> = db.get_trainings_for_user(new.id).unwrap()
+            .into_iter().map(|t| t.exercise).collect();
+        names.sort();
+        assert_eq!(names, vec!["new_a", "old_a", "old_b"]);
+        assert_eq!(db.get_rest_days(new.id).unwrap().len(), 1);
+        assert_eq!(db.get_bodyweights_for_user(new.id).unwrap().len(), 1);
+        assert_eq!(db.count_trainings_for_user(owner.id).unwrap(), 1, "others untouched");
+        assert!(db.merge_users(old.id, new.id).is_err(), "source no longer exists");
+    }
 
-        // Create owner
-        let owner = db.get_or_create_user(12345, None, None).unwrap();
+    #[test]
+    fn test_get_trainings_paged_boundaries() {
+        let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        let other = db.get_or_create_user(222, None, None).unwrap();
+        let same_time = Utc::now();
+        for i in 0..7 {
+            let mut training = create_test_training(&format!("ex{}", i), 10);
+            // Equal timestamps must still page deterministically
+            training.date = same_time - chrono::Duration::minutes(i / 2);
+            db.add_training(&training, user.id).unwrap();
+        }
+        db.add_training(&create_test_training("foreign", 10), other.id).unwrap();
 
-        // Migrate
-        let migrated = db.migrate_trainings_to_owner().unwrap();
-        assert_eq!(migrated, 2);
+        assert_eq!(db.count_trainings(user.id).unwrap(), 7);
+        assert_eq!(db.count_all_trainings().unwrap(), 8);
 
-        // Check owner now has those trainings
-        let trainings = db.get_trainings_for_user(owner.id).unwrap();
-        assert_eq!(trainings.len(), 2);
+        let mut seen = Vec::new();
+        for page in 0..4 {
+            let rows = db.get_trainings_paged(user.id, 3, page * 3).unwrap();
+            assert!(rows.len() <= 3);
+            seen.extend(rows.into_iter().map(|t| t.exercise));
+        }
+        let mut sorted = seen.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(seen.len(), 7, "no row dropped: {:?}", seen);
+        assert_eq!(sorted.len(), 7, "no row repeated: {:?}", seen);
+        assert!(db.get_trainings_paged(user.id, 3, 6).unwrap().len() == 1);
+        assert_eq!(db.get_all_trainings_paged(100, 0).unwrap().len(), 8);
     }
 
     #[test]
-    fn test_migrate_trainings_no_owner() {
+    fn test_training_snapshot_survives_catalog_change() {
         let db = create_test_db();
+        let user = db.get_or_create_user(12345, None, None).unwrap();
+        db.add_training(&create_test_training("стойка на локтях", 1), user.id).unwrap();
+        // Simulate the exercise being renamed out of the catalog
+        db.conn.execute("UPDATE trainings SET exercise = 'old plank'", []).unwrap();
+        db.conn.execute(
+            "INSERT INTO trainings (date, exercise, sets, reps, user_id) VALUES (?1, 'legacy', 1, 5, ?2)",
+            params![Utc::now().to_rfc3339(), user.id],
+        ).unwrap();
 
-        // Add CLI trainings
-        db.add_training_cli(&create_test_training("old", 10)).unwrap();
-
-        // No owner yet
-        let migrated = db.migrate_trainings_to_owner().unwrap();
-        assert_eq!(migrated, 0);
+        let trainings = db.get_trainings_for_user(user.id).unwrap();
+        let renamed = trainings.iter().find(|t| t.exercise == "old plank").unwrap();
+        assert_eq!(renamed.exercise_category(), Some(Category::Core));
+        assert_eq!(renamed.exercise_is_timed(), Some(true));
+        assert_eq!(renamed.muscle_groups(), &[MuscleGroup::Core]);
+
+        let legacy = trainings.iter().find(|t| t.exercise == "legacy").unwrap();
+        assert_eq!(legacy.exercise_category(), None);
+        assert_eq!(legacy.exercise_is_timed(), None);
+        assert!(legacy.muscle_groups().is_empty());
     }
 
     #[test]
@@ -589,8 +3199,12 @@ mod tests {
             duration_secs: Some(45),
             pulse_before: Some(75),
             pulse_after: Some(130),
+            rpe: Some(7),
             notes: Some("test note".to_string()),
             user_id: None,
+            context: None,
+            category: None,
+            is_timed: None,
         };
 
         db.add_training(&training, user.id).unwrap();
@@ -598,6 +3212,7 @@ mod tests {
         let trainings = db.get_trainings_for_user(user.id).unwrap();
         assert_eq!(trainings[0].pulse_before, Some(75));
         assert_eq!(trainings[0].pulse_after, Some(130));
+        assert_eq!(trainings[0].rpe, Some(7));
         assert_eq!(trainings[0].duration_secs, Some(45));
         assert_eq!(trainings[0].notes, Some("test note".to_string()));
     }
@@ -1,5 +1,10 @@
 //! Exercise definitions - база упражнений
 
+use std::collections::HashSet;
+use std::path::Path;
+
+use anyhow::{bail, Context, Result};
+use chrono::Weekday;
 use serde::{Deserialize, Serialize};
 
 /// Muscle groups for tracking training balance
@@ -35,6 +40,33 @@ impl MuscleGroup {
         }
     }
 
+    /// Resolve a Russian name (as in `name_ru`, plus common synonyms)
+    pub fn from_ru(name: &str) -> Option<MuscleGroup> {
+        let name = name.trim().to_lowercase().replace('ё', "е");
+        match name.as_str() {
+            "грудь" => return Some(MuscleGroup::Chest),
+            "пресс" => return Some(MuscleGroup::Core),
+            _ => {}
+        }
+        Self::all()
+            .iter()
+            .copied()
+            .find(|mg| mg.name_ru().replace('ё', "е") == name)
+    }
+
+    /// Body region for the /body map (None for full-body work)
+    pub fn region(&self) -> Option<BodyRegion> {
+        match self {
+            MuscleGroup::Chest | MuscleGroup::Shoulders | MuscleGroup::Triceps => Some(BodyRegion::UpperPush),
+            MuscleGroup::Back | MuscleGroup::Biceps => Some(BodyRegion::UpperPull),
+            MuscleGroup::Core => Some(BodyRegion::Core),
+            MuscleGroup::Glutes | MuscleGroup::Quads | MuscleGroup::Hamstrings | MuscleGroup::Calves => {
+                Some(BodyRegion::Legs)
+            }
+            MuscleGroup::FullBody => None,
+        }
+    }
+
     /// All muscle groups for iteration
     pub fn all() -> &'static [MuscleGroup] {
         &[
@@ -53,16 +85,191 @@ impl MuscleGroup {
     }
 }
 
+/// Coarse body regions grouping muscle groups
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BodyRegion {
+    UpperPush,
+    UpperPull,
+    Core,
+    Legs,
+}
+
+impl BodyRegion {
+    pub fn name_ru(&self) -> &'static str {
+        match self {
+            BodyRegion::UpperPush => "верх, жим",
+            BodyRegion::UpperPull => "верх, тяга",
+            BodyRegion::Core => "кор",
+            BodyRegion::Legs => "ноги",
+        }
+    }
+
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            BodyRegion::UpperPush => "💪",
+            BodyRegion::UpperPull => "🦾",
+            BodyRegion::Core => "🎯",
+            BodyRegion::Legs => "🦵",
+        }
+    }
+
+    /// Regions from head to toe
+    pub fn all() -> &'static [BodyRegion] {
+        &[BodyRegion::UpperPush, BodyRegion::UpperPull, BodyRegion::Core, BodyRegion::Legs]
+    }
+}
+
+/// Gear an exercise needs; exercises with none work anywhere
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Equipment {
+    Handles, // Упоры для отжиманий
+    Bar,     // Перекладина / полка / перила
+    Step,    // Ступенька
+    Wall,    // Стена
+}
+
+impl Equipment {
+    /// Stable id for storage
+    pub fn id(&self) -> &'static str {
+        match self {
+            Equipment::Handles => "handles",
+            Equipment::Bar => "bar",
+            Equipment::Step => "step",
+            Equipment::Wall => "wall",
+        }
+    }
+
+    pub fn name_ru(&self) -> &'static str {
+        match self {
+            Equipment::Handles => "ручки",
+            Equipment::Bar => "перекладина",
+            Equipment::Step => "ступенька",
+            Equipment::Wall => "стена",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Equipment> {
+        Self::all().iter().copied().find(|e| e.id() == id)
+    }
+
+    /// Resolve a Russian name as in `name_ru`
+    pub fn from_ru(name: &str) -> Option<Equipment> {
+        let name = name.trim().to_lowercase();
+        Self::all().iter().copied().find(|e| e.name_ru() == name)
+    }
+
+    pub fn all() -> &'static [Equipment] {
+        &[Equipment::Handles, Equipment::Bar, Equipment::Step, Equipment::Wall]
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Exercise {
     pub id: &'static str,
     pub name: &'static str,
+    pub aliases: &'static [&'static str], // Short names for lookup (/log отжимания)
     pub category: Category,
     pub muscle_groups: &'static [MuscleGroup],
     pub is_base: bool,
     pub is_timed: bool, // true = на время (планка), false = на повторы (отжимания)
+    pub difficulty: u8, // 1 (новичок) – 5 (продвинутый)
+    pub equipment: &'static [Equipment], // Нужный инвентарь (пусто = без инвентаря)
     pub description: Option<&'static str>,
     pub focus_cues: Option<&'static str>, // На что концентрироваться при выполнении
+    pub easier: Option<&'static str>, // id более лёгкого варианта
+    pub harder: Option<&'static str>, // id более сложного варианта
+}
+
+impl Exercise {
+    /// Whether all gear this exercise needs is available
+    pub fn doable_with(&self, available: &[Equipment]) -> bool {
+        self.equipment.iter().all(|e| available.contains(e))
+    }
+}
+
+/// Owned exercise definition, loaded from a TOML catalog.
+/// Same fields as `Exercise`, which stays `&'static` for the built-in consts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExerciseDef {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub category: Category,
+    pub muscle_groups: Vec<MuscleGroup>,
+    #[serde(default)]
+    pub is_base: bool,
+    #[serde(default)]
+    pub is_timed: bool,
+    #[serde(default = "default_difficulty")]
+    pub difficulty: u8,
+    #[serde(default)]
+    pub equipment: Vec<Equipment>,
+    pub description: Option<String>,
+    pub focus_cues: Option<String>,
+    #[serde(default)]
+    pub easier: Option<String>,
+    #[serde(default)]
+    pub harder: Option<String>,
+}
+
+impl From<&Exercise> for ExerciseDef {
+    fn from(ex: &Exercise) -> Self {
+        ExerciseDef {
+            id: ex.id.to_string(),
+            name: ex.name.to_string(),
+            aliases: ex.aliases.iter().map(|a| a.to_string()).collect(),
+            category: ex.category,
+            muscle_groups: ex.muscle_groups.to_vec(),
+            is_base: ex.is_base,
+            is_timed: ex.is_timed,
+            difficulty: ex.difficulty,
+            equipment: ex.equipment.to_vec(),
+            description: ex.description.map(str::to_string),
+            focus_cues: ex.focus_cues.map(str::to_string),
+            easier: ex.easier.map(str::to_string),
+            harder: ex.harder.map(str::to_string),
+        }
+    }
+}
+
+/// Difficulty for catalog entries that don't set one (middle of the 1–5 scale)
+fn default_difficulty() -> u8 {
+    3
+}
+
+/// Valid `difficulty` values
+pub const DIFFICULTY_RANGE: std::ops::RangeInclusive<u8> = 1..=5;
+
+/// Accepted reps per set
+pub const REPS_RANGE: std::ops::RangeInclusive<i32> = 1..=1000;
+
+/// Accepted sets per entry
+pub const SETS_RANGE: std::ops::RangeInclusive<i32> = 1..=100;
+
+/// Reps within `REPS_RANGE`, or a message for the user
+pub fn validate_reps(reps: i32) -> Result<i32, String> {
+    if REPS_RANGE.contains(&reps) {
+        Ok(reps)
+    } else {
+        Err(format!("Повторов должно быть от {} до {}", REPS_RANGE.start(), REPS_RANGE.end()))
+    }
+}
+
+/// Sets within `SETS_RANGE`, or a message for the user
+pub fn validate_sets(sets: i32) -> Result<i32, String> {
+    if SETS_RANGE.contains(&sets) {
+        Ok(sets)
+    } else {
+        Err(format!("Подходов должно быть от {} до {}", SETS_RANGE.start(), SETS_RANGE.end()))
+    }
+}
+
+/// TOML catalog layout: a list of `[[exercise]]` tables
+#[derive(Debug, Deserialize)]
+struct CatalogFile {
+    #[serde(default)]
+    exercise: Vec<ExerciseDef>,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
@@ -88,6 +295,100 @@ impl Category {
             Category::Stretch => "🧘",
         }
     }
+
+    pub fn name_ru(&self) -> &'static str {
+        match self {
+            Category::Push => "жим",
+            Category::Pull => "тяга",
+            Category::Core => "кор",
+            Category::Legs => "ноги",
+            Category::Taiji => "тайцзи",
+            Category::Strikes => "удары",
+            Category::Stretch => "растяжка",
+        }
+    }
+
+    /// Resolve a Russian name as in `name_ru`
+    pub fn from_ru(name: &str) -> Option<Category> {
+        let name = name.trim().to_lowercase();
+        Self::all().iter().copied().find(|c| c.name_ru() == name)
+    }
+
+    /// Stable id stored with trainings
+    pub fn id(&self) -> &'static str {
+        match self {
+            Category::Push => "push",
+            Category::Pull => "pull",
+            Category::Core => "core",
+            Category::Legs => "legs",
+            Category::Taiji => "taiji",
+            Category::Strikes => "strikes",
+            Category::Stretch => "stretch",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Category> {
+        Self::all().iter().copied().find(|c| c.id() == id)
+    }
+
+    /// Typical muscle groups of the category, for trainings whose exercise
+    /// is no longer in the catalog. Stretching doesn't count as load.
+    pub fn muscle_groups(&self) -> &'static [MuscleGroup] {
+        match self {
+            Category::Push => &[MuscleGroup::Chest, MuscleGroup::Triceps, MuscleGroup::Shoulders],
+            Category::Pull => &[MuscleGroup::Back, MuscleGroup::Biceps],
+            Category::Core => &[MuscleGroup::Core],
+            Category::Legs => &[MuscleGroup::Quads, MuscleGroup::Glutes, MuscleGroup::Hamstrings],
+            Category::Taiji => &[MuscleGroup::FullBody],
+            Category::Strikes => &[MuscleGroup::Shoulders, MuscleGroup::Core],
+            Category::Stretch => &[],
+        }
+    }
+
+    /// All categories for iteration
+    pub fn all() -> &'static [Category] {
+        &[
+            Category::Push,
+            Category::Pull,
+            Category::Core,
+            Category::Legs,
+            Category::Taiji,
+            Category::Strikes,
+            Category::Stretch,
+        ]
+    }
+}
+
+/// Rest between sets in seconds (min, max), after the book's recovery tip:
+/// 30-60 s for endurance, 90-120 s for strength, 2.5-5 min for power
+pub fn recommended_rest(category: Category) -> (u32, u32) {
+    match category {
+        Category::Push | Category::Pull | Category::Legs => (90, 120),
+        Category::Strikes => (150, 300),
+        Category::Core | Category::Taiji | Category::Stretch => (30, 60),
+    }
+}
+
+/// Focus category per weekday (index 0 = Monday), set with /template
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WeekdayTemplate {
+    pub days: [Option<Category>; 7],
+}
+
+impl WeekdayTemplate {
+    /// Focus category for a weekday, if any
+    pub fn focus(&self, weekday: Weekday) -> Option<Category> {
+        self.days[weekday.num_days_from_monday() as usize]
+    }
+
+    /// Set or clear the focus for a weekday
+    pub fn set(&mut self, weekday: Weekday, category: Option<Category>) {
+        self.days[weekday.num_days_from_monday() as usize] = category;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.days.iter().all(|d| d.is_none())
+    }
 }
 
 /// Базовые упражнения (ежечасные)
@@ -95,82 +396,122 @@ pub const BASE_EXERCISES: &[Exercise] = &[
     Exercise {
         id: "pushups_fist",
         name: "отжимания на кулаках",
+        aliases: &["отжимания", "кулаки"],
         category: Category::Push,
         muscle_groups: &[MuscleGroup::Chest, MuscleGroup::Triceps, MuscleGroup::Shoulders, MuscleGroup::Core],
         is_base: true,
         is_timed: false,
+        difficulty: 3,
+        equipment: &[],
         description: None,
         focus_cues: None,
+        easier: None,
+        harder: Some("pushups_handles"),
     },
     Exercise {
         id: "pushups_handles",
         name: "отжимания с ручками",
+        aliases: &["отжимания на ручках"],
         category: Category::Push,
         muscle_groups: &[MuscleGroup::Chest, MuscleGroup::Triceps, MuscleGroup::Shoulders, MuscleGroup::Core],
         is_base: true,
         is_timed: false,
+        difficulty: 4,
+        equipment: &[Equipment::Handles],
         description: None,
         focus_cues: None,
+        easier: Some("pushups_fist"),
+        harder: None,
     },
     Exercise {
         id: "jackknife",
         name: "пресс складной нож",
+        aliases: &["складной нож", "нож"],
         category: Category::Core,
         muscle_groups: &[MuscleGroup::Core],
         is_base: true,
         is_timed: false,
+        difficulty: 3,
+        equipment: &[],
         description: None,
         focus_cues: None,
+        easier: None,
+        harder: None,
     },
     Exercise {
         id: "plank_elbows",
         name: "стойка на локтях",
+        aliases: &["планка"],
         category: Category::Core,
         muscle_groups: &[MuscleGroup::Core, MuscleGroup::Shoulders],
         is_base: true,
         is_timed: true,
+        difficulty: 2,
+        equipment: &[],
         description: None,
         focus_cues: None,
+        easier: None,
+        harder: Some("side_plank"),
     },
     Exercise {
         id: "squats_strikes",
         name: "приседания с ударами",
+        aliases: &["приседания"],
         category: Category::Legs,
         muscle_groups: &[MuscleGroup::Quads, MuscleGroup::Glutes, MuscleGroup::Core, MuscleGroup::Shoulders],
         is_base: true,
         is_timed: false,
+        difficulty: 2,
+        equipment: &[],
         description: None,
         focus_cues: None,
+        easier: None,
+        harder: None,
     },
     Exercise {
         id: "taiji_shadow",
         name: "тайцзи бой с тенью",
+        aliases: &["тайцзи"],
         category: Category::Taiji,
         muscle_groups: &[MuscleGroup::FullBody],
         is_base: true,
         is_timed: true,
+        difficulty: 2,
+        equipment: &[],
         description: Some("Разминка. Выполняется в начале комплекса"),
         focus_cues: None,
+        easier: None,
+        harder: None,
     },
     Exercise {
         id: "swimmer",
         name: "пловец",
+        aliases: &[],
         category: Category::Core,
         muscle_groups: &[MuscleGroup::Back, MuscleGroup::Shoulders],
         is_base: true,
         is_timed: false,
+        difficulty: 2,
+        equipment: &[],
         description: Some("Лёжа на животе, попеременно поднимай противоположные руку и ногу, имитируя плавание"),
         focus_cues: Some("Контролируй движение, не раскачивайся. Напрягай спину при каждом подъёме. Дыши ровно"),
+        easier: None,
+        harder: None,
     },
     Exercise {
         id: "taiji_shadow_weapon",
         name: "тайцзи бой с тенью с оружием",
+        aliases: &[],
         category: Category::Taiji,
         muscle_groups: &[MuscleGroup::FullBody],
         is_base: true,
         is_timed: true,
+        difficulty: 3,
+        equipment: &[],
         description: Some("Завершение комплекса. Выполняется после всех базовых упражнений"),
         focus_cues: None,
+        easier: None,
+        harder: None,
     },
 ];
 
@@ -180,206 +521,306 @@ pub const EXTRA_EXERCISES: &[Exercise] = &[
     Exercise {
         id: "let_me_in",
         name: "впусти меня",
+        aliases: &[],
         category: Category::Pull,
         muscle_groups: &[MuscleGroup::Back, MuscleGroup::Biceps, MuscleGroup::Shoulders],
         is_base: false,
         is_timed: false,
+        difficulty: 2,
+        equipment: &[],
         description: Some("Стоя лицом к двери, держась за ручки с двух сторон. Ноги по бокам двери. Подтягивайся к двери, сгибая локти"),
         focus_cues: Some("Своди лопатки в конце движения. Чувствуй растяжение широчайших при опускании. Не помогай корпусом"),
+        easier: None,
+        harder: None,
     },
     Exercise {
         id: "shelf_pullup",
         name: "подтягивание у полки",
+        aliases: &["подтягивания"],
         category: Category::Pull,
         muscle_groups: &[MuscleGroup::Biceps, MuscleGroup::Back],
         is_base: false,
         is_timed: false,
+        difficulty: 2,
+        equipment: &[Equipment::Bar],
         description: Some("Встань у полки/перил на уровне пояса. Руки ладонями вверх под выступ. Тяни вверх, наклоняясь вперёд"),
         focus_cues: Some("Напрягай бицепсы в верхней точке. Контролируй опускание 2-3 секунды. Держи локти прижатыми к корпусу"),
+        easier: None,
+        harder: None,
     },
     // Ноги
     Exercise {
         id: "calf_raises",
         name: "подъём на носки",
+        aliases: &[],
         category: Category::Legs,
         muscle_groups: &[MuscleGroup::Calves],
         is_base: false,
         is_timed: false,
+        difficulty: 1,
+        equipment: &[Equipment::Step],
         description: Some("Встань на край ступеньки носками. Поднимайся на носки и опускайся ниже уровня ступени"),
         focus_cues: Some("Максимально поднимайся на носки. Пауза 1 сек в верхней точке. Полностью растягивай икры внизу"),
+        easier: None,
+        harder: None,
     },
     Exercise {
         id: "romanian_deadlift",
         name: "румынская тяга на одной ноге",
+        aliases: &[],
         category: Category::Legs,
         muscle_groups: &[MuscleGroup::Hamstrings, MuscleGroup::Glutes, MuscleGroup::Core],
         is_base: false,
         is_timed: false,
+        difficulty: 4,
+        equipment: &[],
         description: Some("Стоя на одной ноге, наклоняйся вперёд, отводя другую ногу назад. Спина прямая"),
         focus_cues: Some("Чувствуй растяжение задней поверхности бедра. Сжимай ягодицу при подъёме. Держи спину идеально ровной"),
+        easier: None,
+        harder: None,
     },
     // === Силовые из книги (для баланса мышц) ===
     Exercise {
         id: "side_lunges",
         name: "выпады в сторону",
+        aliases: &[],
         category: Category::Legs,
         muscle_groups: &[MuscleGroup::Quads, MuscleGroup::Glutes, MuscleGroup::Hamstrings],
         is_base: false,
         is_timed: false,
+        difficulty: 3,
+        equipment: &[],
         description: Some("Шагни в сторону, согни опорную ногу до параллели бедра с полом. Вторая нога прямая. Оттолкнись и вернись"),
         focus_cues: Some("Толкайся пяткой опорной ноги. Держи колено над стопой. Чувствуй внутреннюю поверхность бедра"),
+        easier: None,
+        harder: None,
     },
     Exercise {
         id: "star_jump",
         name: "прыжок-звезда",
+        aliases: &[],
         category: Category::Legs,
         muscle_groups: &[MuscleGroup::Quads, MuscleGroup::Glutes, MuscleGroup::Hamstrings, MuscleGroup::Calves],
         is_base: false,
         is_timed: false,
+        difficulty: 4,
+        equipment: &[],
         description: Some("Из глубокого приседа сумо выпрыгни вверх, раскинув руки и ноги звездой. Приземлись мягко на носки"),
         focus_cues: Some("Взрывное отталкивание от пола. Полное раскрытие в воздухе. Мягкое приземление с амортизацией"),
+        easier: None,
+        harder: None,
     },
     Exercise {
         id: "pogo_jumps",
         name: "пого-прыжки",
+        aliases: &[],
         category: Category::Legs,
         muscle_groups: &[MuscleGroup::Calves],
         is_base: false,
         is_timed: false,
+        difficulty: 4,
+        equipment: &[],
         description: Some("Прыгай на месте на носках, не сгибая колени. Пятки не касаются пола. Прыгай как можно выше и чаще"),
         focus_cues: Some("Ноги как пружины - только голеностоп. Держи пресс напряжённым. Минимальное время контакта с полом"),
+        easier: None,
+        harder: None,
     },
     Exercise {
         id: "superman",
         name: "супермен",
+        aliases: &[],
         category: Category::Core,
         muscle_groups: &[MuscleGroup::Back, MuscleGroup::Glutes, MuscleGroup::Hamstrings],
         is_base: false,
         is_timed: true,
+        difficulty: 1,
+        equipment: &[],
         description: Some("Лёжа на животе, одновременно подними руки и ноги от пола. Держи позицию. Тренирует разгибатели спины"),
         focus_cues: Some("Сжимай ягодицы. Напрягай поясницу. Тянись макушкой и пятками в разные стороны. Шея нейтральна"),
+        easier: None,
+        harder: None,
     },
     Exercise {
         id: "russian_twist",
         name: "русские скручивания",
+        aliases: &["скручивания"],
         category: Category::Core,
         muscle_groups: &[MuscleGroup::Core],
         is_base: false,
         is_timed: false,
+        difficulty: 3,
+        equipment: &[],
         description: Some("Сидя с поднятыми ногами, скручивай корпус из стороны в сторону, касаясь локтями коленей"),
         focus_cues: Some("Скручивай именно корпус, не просто руки. Напрягай косые мышцы живота. Держи ноги неподвижно"),
+        easier: None,
+        harder: None,
     },
     Exercise {
         id: "side_plank",
         name: "боковая планка",
+        aliases: &[],
         category: Category::Core,
         muscle_groups: &[MuscleGroup::Core, MuscleGroup::Shoulders],
         is_base: false,
         is_timed: true,
+        difficulty: 3,
+        equipment: &[],
         description: Some("На боку на локте, тело прямое от головы до пяток. Держи позицию"),
         focus_cues: Some("Не проваливай таз. Напрягай боковые мышцы живота. Плечо над локтем. Дыши спокойно"),
+        easier: Some("plank_elbows"),
+        harder: None,
     },
     // === Растяжка (научно обоснованная для 40+) ===
     Exercise {
         id: "t_spine_rotation",
         name: "вращение грудного отдела",
+        aliases: &[],
         category: Category::Stretch,
         muscle_groups: &[MuscleGroup::Back],
         is_base: false,
         is_timed: true,
+        difficulty: 1,
+        equipment: &[],
         description: Some("На четвереньках, поверни корпус и подними руку к потолку. Держи 20-30 сек на каждую сторону"),
         focus_cues: Some("Чувствуй вращение между лопатками. Взгляд за рукой. Таз неподвижен. Дыши глубоко"),
+        easier: None,
+        harder: None,
     },
     Exercise {
         id: "thread_needle",
         name: "нить в иголку",
+        aliases: &[],
         category: Category::Stretch,
         muscle_groups: &[MuscleGroup::Shoulders, MuscleGroup::Back],
         is_base: false,
         is_timed: true,
+        difficulty: 1,
+        equipment: &[],
         description: Some("На четвереньках, проведи руку под корпусом, опустив плечо на пол. Держи 20-30 сек"),
         focus_cues: Some("Расслабь плечо к полу. Чувствуй растяжение между лопаткой и позвоночником. Дыши в натяжение"),
+        easier: None,
+        harder: None,
     },
     Exercise {
         id: "child_pose",
         name: "поза ребёнка",
+        aliases: &[],
         category: Category::Stretch,
         muscle_groups: &[MuscleGroup::Back, MuscleGroup::Glutes],
         is_base: false,
         is_timed: true,
+        difficulty: 1,
+        equipment: &[],
         description: Some("Сидя на пятках, вытяни руки вперёд, лоб на пол. Расслабься и дыши 30 сек"),
         focus_cues: Some("Расслабь поясницу. Тянись руками вперёд. Отпусти напряжение с каждым выдохом"),
+        easier: None,
+        harder: None,
     },
     Exercise {
         id: "pigeon_pose",
         name: "поза голубя",
+        aliases: &[],
         category: Category::Stretch,
         muscle_groups: &[MuscleGroup::Glutes, MuscleGroup::Hamstrings],
         is_base: false,
         is_timed: true,
+        difficulty: 2,
+        equipment: &[],
         description: Some("Одна нога согнута впереди, другая вытянута назад. Наклонись вперёд. Держи 30 сек на каждую ногу"),
         focus_cues: Some("Чувствуй глубокое растяжение в ягодице. Опускай таз к полу. Не зажимай поясницу"),
+        easier: None,
+        harder: None,
     },
     Exercise {
         id: "figure_four_twist",
         name: "четвёрка с поворотом",
+        aliases: &[],
         category: Category::Stretch,
         muscle_groups: &[MuscleGroup::Glutes, MuscleGroup::Core],
         is_base: false,
         is_timed: true,
+        difficulty: 1,
+        equipment: &[],
         description: Some("Лёжа на спине, положи лодыжку на колено другой ноги. Опусти обе ноги в сторону. Держи 20-30 сек"),
         focus_cues: Some("Расслабь поясницу в пол. Чувствуй растяжение в грушевидной мышце. Плечи прижаты"),
+        easier: None,
+        harder: None,
     },
     Exercise {
         id: "hip_flexor_stretch",
         name: "растяжка сгибателей бедра",
+        aliases: &[],
         category: Category::Stretch,
         muscle_groups: &[MuscleGroup::Quads, MuscleGroup::Core],
         is_base: false,
         is_timed: true,
+        difficulty: 1,
+        equipment: &[],
         description: Some("Лёжа на спине, подтяни одно колено к груди, другую ногу держи прямой. Прижми поясницу к полу"),
         focus_cues: Some("Поясница прижата к полу - это ключ. Чувствуй растяжение передней поверхности бедра прямой ноги"),
+        easier: None,
+        harder: None,
     },
     Exercise {
         id: "seated_forward_fold",
         name: "складка сидя",
+        aliases: &[],
         category: Category::Stretch,
         muscle_groups: &[MuscleGroup::Hamstrings, MuscleGroup::Back],
         is_base: false,
         is_timed: true,
+        difficulty: 1,
+        equipment: &[],
         description: Some("Сидя с прямыми ногами, тянись руками к носкам. Не округляй спину. Держи 30 сек"),
         focus_cues: Some("Наклоняйся от бёдер, не от поясницы. Тяни живот к бёдрам. Расслабь шею"),
+        easier: None,
+        harder: None,
     },
     Exercise {
         id: "happy_baby",
         name: "счастливый малыш",
+        aliases: &[],
         category: Category::Stretch,
         muscle_groups: &[MuscleGroup::Glutes, MuscleGroup::Hamstrings],
         is_base: false,
         is_timed: true,
+        difficulty: 1,
+        equipment: &[],
         description: Some("Лёжа на спине, возьмись за внешние стороны стоп, колени к подмышкам. Покачивайся 30 сек"),
         focus_cues: Some("Расслабь поясницу. Колени тяни к подмышкам. Мягко покачивайся для массажа позвоночника"),
+        easier: None,
+        harder: None,
     },
     Exercise {
         id: "cobra",
         name: "кобра",
+        aliases: &[],
         category: Category::Stretch,
         muscle_groups: &[MuscleGroup::Core, MuscleGroup::Back],
         is_base: false,
         is_timed: true,
+        difficulty: 1,
+        equipment: &[],
         description: Some("Лёжа на животе, подними грудь, упираясь ладонями. Бёдра на полу. Держи 15-20 сек"),
         focus_cues: Some("Отталкивайся руками, раскрывай грудь. Плечи от ушей. Взгляд вперёд, шея длинная"),
+        easier: None,
+        harder: None,
     },
     // Кардио / Full Body
     Exercise {
         id: "shadow_boxing",
         name: "бой с тенью",
+        aliases: &[],
         category: Category::Taiji,
         muscle_groups: &[MuscleGroup::FullBody],
         is_base: false,
         is_timed: true,
+        difficulty: 3,
+        equipment: &[],
         description: Some("Имитация боя с невидимым противником. Удары, блоки, уклоны в свободном темпе"),
         focus_cues: Some("Работай всем телом. Вращай корпус при ударах. Держи руки у подбородка. Дыши ритмично"),
+        easier: None,
+        harder: None,
     },
 ];
 
@@ -391,15 +832,107 @@ pub fn get_all_exercises() -> Vec<&'static Exercise> {
     BASE_EXERCISES.iter().chain(EXTRA_EXERCISES.iter()).collect()
 }
 
+/// Exercises no harder than `max` (1–5)
+pub fn get_all_exercises_by_difficulty(max: u8) -> Vec<&'static Exercise> {
+    get_all_exercises().into_iter().filter(|e| e.difficulty <= max).collect()
+}
+
+/// Built-in catalog (base + extra) as owned definitions
+pub fn builtin_catalog() -> Vec<ExerciseDef> {
+    get_all_exercises().into_iter().map(ExerciseDef::from).collect()
+}
+
+/// Parse a TOML exercise catalog (`[[exercise]]` tables)
+pub fn parse_catalog(text: &str) -> Result<Vec<ExerciseDef>> {
+    let file: CatalogFile = toml::from_str(text).context("Invalid exercise catalog")?;
+    let mut ids = HashSet::new();
+    for ex in &file.exercise {
+        if ex.id.trim().is_empty() || ex.name.trim().is_empty() {
+            bail!("Exercise with empty id or name in catalog");
+        }
+        if !ids.insert(ex.id.as_str()) {
+            bail!("Duplicate exercise id in catalog: {}", ex.id);
+        }
+        if !DIFFICULTY_RANGE.contains(&ex.difficulty) {
+            bail!("Exercise {} has difficulty {} (expected 1-5)", ex.id, ex.difficulty);
+        }
+    }
+    Ok(file.exercise)
+}
+
+/// Load the exercise catalog from a TOML file; built-ins if the file is absent
+pub fn load_from_toml(path: impl AsRef<Path>) -> Result<Vec<ExerciseDef>> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Ok(builtin_catalog());
+    }
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    parse_catalog(&text).with_context(|| format!("Failed to load {}", path.display()))
+}
+
+/// How many catalog exercises target each muscle group, least covered first.
+/// Groups with few exercises are hard for the recommender to balance.
+pub fn coverage_gaps() -> Vec<(MuscleGroup, usize)> {
+    catalog_coverage(&builtin_catalog())
+}
+
+/// Muscle group coverage of an arbitrary catalog, least covered first
+pub fn catalog_coverage(exercises: &[ExerciseDef]) -> Vec<(MuscleGroup, usize)> {
+    let mut coverage: Vec<_> = MuscleGroup::all()
+        .iter()
+        .map(|mg| {
+            let count = exercises.iter().filter(|e| e.muscle_groups.contains(mg)).count();
+            (*mg, count)
+        })
+        .collect();
+    coverage.sort_by_key(|(_, count)| *count);
+    coverage
+}
+
 pub fn find_exercise(id: &str) -> Option<&'static Exercise> {
     get_all_exercises().into_iter().find(|e| e.id == id)
 }
 
+/// Easier and harder variants of an exercise (progression chain)
+pub fn progression(id: &str) -> (Option<&'static Exercise>, Option<&'static Exercise>) {
+    match find_exercise(id) {
+        Some(ex) => (ex.easier.and_then(find_exercise), ex.harder.and_then(find_exercise)),
+        None => (None, None),
+    }
+}
+
 /// Find exercise by name (for matching DB records)
 pub fn find_exercise_by_name(name: &str) -> Option<&'static Exercise> {
     get_all_exercises().into_iter().find(|e| e.name == name)
 }
 
+/// Resolve a typed exercise name: exact name or alias (case-insensitive),
+/// then prefix, then substring. Several results mean the query is ambiguous.
+pub fn find_exercise_by_alias(query: &str) -> Vec<&'static Exercise> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let names = |ex: &Exercise| std::iter::once(ex.name).chain(ex.aliases.iter().copied());
+
+    let matchers: [&dyn Fn(&str) -> bool; 3] = [
+        &|name| name == query,
+        &|name| name.starts_with(&query),
+        &|name| name.contains(&query),
+    ];
+    for matches in matchers {
+        let found: Vec<_> = get_all_exercises()
+            .into_iter()
+            .filter(|ex| names(ex).any(|name| matches(&name.to_lowercase())))
+            .collect();
+        if !found.is_empty() {
+            return found;
+        }
+    }
+    Vec::new()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -420,6 +953,229 @@ mod tests {
         assert_eq!(MuscleGroup::FullBody.name_ru(), "всё тело");
     }
 
+    const SAMPLE_CATALOG: &str = r#"
+[[exercise]]
+id = "pushups"
+name = "отжимания"
+category = "Push"
+muscle_groups = ["Chest", "Triceps"]
+is_base = true
+
+[[exercise]]
+id = "plank"
+name = "планка"
+category = "Core"
+muscle_groups = ["Core"]
+is_timed = true
+equipment = ["Wall"]
+focus_cues = "Тело в линию"
+"#;
+
+    #[test]
+    fn test_parse_catalog_sample() {
+        let catalog = parse_catalog(SAMPLE_CATALOG).unwrap();
+        assert_eq!(catalog.len(), 2);
+
+        assert_eq!(catalog[0].id, "pushups");
+        assert_eq!(catalog[0].category, Category::Push);
+        assert_eq!(catalog[0].muscle_groups, vec![MuscleGroup::Chest, MuscleGroup::Triceps]);
+        assert!(catalog[0].is_base);
+        assert!(catalog[0].equipment.is_empty());
+        assert_eq!(catalog[0].description, None);
+        assert_eq!(catalog[0].difficulty, 3);
+
+        assert!(catalog[1].is_timed);
+        assert!(!catalog[1].is_base);
+        assert_eq!(catalog[1].equipment, vec![Equipment::Wall]);
+        assert_eq!(catalog[1].focus_cues.as_deref(), Some("Тело в линию"));
+    }
+
+    #[test]
+    fn test_parse_catalog_rejects_bad_entries() {
+        let duplicate = format!("{SAMPLE_CATALOG}\n[[exercise]]\nid = \"plank\"\nname = \"ещё планка\"\ncategory = \"Core\"\nmuscle_groups = []\n");
+        assert!(parse_catalog(&duplicate).is_err());
+        assert!(parse_catalog("[[exercise]]\nid = \"x\"\nname = \"x\"\ncategory = \"Dance\"\nmuscle_groups = []").is_err());
+        assert!(parse_catalog("[[exercise]]\nid = \"x\"\nname = \"x\"\ncategory = \"Core\"\nmuscle_groups = []\ndifficulty = 6").is_err());
+    }
+
+    #[test]
+    fn test_difficulty_filled_and_filtered() {
+        assert!(get_all_exercises().iter().all(|e| DIFFICULTY_RANGE.contains(&e.difficulty)));
+
+        let beginner = get_all_exercises_by_difficulty(1);
+        assert!(!beginner.is_empty());
+        assert!(beginner.iter().all(|e| e.difficulty == 1));
+        assert_eq!(get_all_exercises_by_difficulty(5).len(), get_all_exercises().len());
+    }
+
+    #[test]
+    fn test_load_from_toml_falls_back_to_builtins() {
+        let catalog = load_from_toml("/nonexistent/exercises.toml").unwrap();
+        assert_eq!(catalog.len(), get_all_exercises().len());
+        assert_eq!(catalog[0], ExerciseDef::from(&BASE_EXERCISES[0]));
+    }
+
+    #[test]
+    fn test_load_from_toml_reads_file() {
+        let path = std::env::temp_dir().join(format!("majowuji_catalog_{}.toml", std::process::id()));
+        std::fs::write(&path, SAMPLE_CATALOG).unwrap();
+        let catalog = load_from_toml(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(catalog.len(), 2);
+        assert_eq!(catalog_coverage(&catalog)[0].1, 0);
+    }
+
+    #[test]
+    fn test_find_exercise_by_alias_exact_name() {
+        let found = find_exercise_by_alias("Отжимания на кулаках");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, "pushups_fist");
+    }
+
+    #[test]
+    fn test_find_exercise_by_alias_alias() {
+        // Exact alias wins over the prefix match on "отжимания с ручками"
+        let found = find_exercise_by_alias("отжимания");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "отжимания на кулаках");
+
+        let found = find_exercise_by_alias(" ПЛАНКА ");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "стойка на локтях");
+    }
+
+    #[test]
+    fn test_find_exercise_by_alias_fallbacks() {
+        // Prefix, then substring
+        let found = find_exercise_by_alias("плове");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "пловец");
+
+        let found = find_exercise_by_alias("голуб");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "поза голубя");
+
+        assert!(find_exercise_by_alias("жонглирование").is_empty());
+        assert!(find_exercise_by_alias("  ").is_empty());
+    }
+
+    #[test]
+    fn test_find_exercise_by_alias_ambiguous() {
+        let names: Vec<_> = find_exercise_by_alias("отжим").iter().map(|ex| ex.name).collect();
+        assert_eq!(names, vec!["отжимания на кулаках", "отжимания с ручками"]);
+    }
+
+    #[test]
+    fn test_progression_chain_both_directions() {
+        let (easier, harder) = progression("pushups_fist");
+        assert!(easier.is_none());
+        let harder = harder.unwrap();
+        assert_eq!(harder.id, "pushups_handles");
+        assert_eq!(progression(harder.id).0.unwrap().id, "pushups_fist");
+
+        let (_, harder) = progression("plank_elbows");
+        assert_eq!(progression(harder.unwrap().id).0.unwrap().id, "plank_elbows");
+
+        assert_eq!(progression("unknown").0.map(|ex| ex.id), None);
+    }
+
+    #[test]
+    fn test_progression_ids_resolve() {
+        for ex in get_all_exercises() {
+            if let Some(id) = ex.easier {
+                let easier = find_exercise(id).unwrap_or_else(|| panic!("Unknown easier id: {}", id));
+                assert_eq!(easier.harder, Some(ex.id));
+            }
+            if let Some(id) = ex.harder {
+                let harder = find_exercise(id).unwrap_or_else(|| panic!("Unknown harder id: {}", id));
+                assert_eq!(harder.easier, Some(ex.id));
+            }
+        }
+    }
+
+    #[test]
+    fn test_aliases_are_unique() {
+        let mut seen = HashSet::new();
+        for ex in get_all_exercises() {
+            for name in std::iter::once(ex.name).chain(ex.aliases.iter().copied()) {
+                assert!(seen.insert(name), "Duplicate exercise name or alias: {}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_coverage_gaps_sorted_ascending() {
+        let gaps = coverage_gaps();
+        assert_eq!(gaps.len(), MuscleGroup::all().len());
+        assert!(gaps.windows(2).all(|w| w[0].1 <= w[1].1));
+
+        let core = gaps.iter().find(|(mg, _)| *mg == MuscleGroup::Core).unwrap();
+        let expected = get_all_exercises().iter()
+            .filter(|e| e.muscle_groups.contains(&MuscleGroup::Core))
+            .count();
+        assert_eq!(core.1, expected);
+    }
+
+    #[test]
+    fn test_category_from_ru_roundtrip() {
+        for c in Category::all() {
+            assert_eq!(Category::from_ru(c.name_ru()), Some(*c));
+        }
+        assert_eq!(Category::from_ru("Ноги"), Some(Category::Legs));
+        assert_eq!(Category::from_ru("бег"), None);
+    }
+
+    #[test]
+    fn test_weekday_template() {
+        let mut template = WeekdayTemplate::default();
+        assert!(template.is_empty());
+
+        template.set(Weekday::Mon, Some(Category::Push));
+        template.set(Weekday::Tue, Some(Category::Legs));
+        assert_eq!(template.focus(Weekday::Mon), Some(Category::Push));
+        assert_eq!(template.focus(Weekday::Wed), None);
+
+        template.set(Weekday::Mon, None);
+        assert_eq!(template.focus(Weekday::Mon), None);
+        assert!(!template.is_empty());
+    }
+
+    #[test]
+    fn test_equipment_ids_roundtrip() {
+        for eq in Equipment::all() {
+            assert_eq!(Equipment::from_id(eq.id()), Some(*eq));
+            assert_eq!(Equipment::from_ru(eq.name_ru()), Some(*eq));
+        }
+    }
+
+    #[test]
+    fn test_doable_with() {
+        let handles = find_exercise("pushups_handles").unwrap();
+        assert!(!handles.doable_with(&[]));
+        assert!(handles.doable_with(&[Equipment::Handles]));
+        assert!(find_exercise("pushups_fist").unwrap().doable_with(&[]));
+    }
+
+    #[test]
+    fn test_muscle_group_region() {
+        assert_eq!(MuscleGroup::Triceps.region(), Some(BodyRegion::UpperPush));
+        assert_eq!(MuscleGroup::Biceps.region(), Some(BodyRegion::UpperPull));
+        assert_eq!(MuscleGroup::Core.region(), Some(BodyRegion::Core));
+        assert_eq!(MuscleGroup::Calves.region(), Some(BodyRegion::Legs));
+        assert_eq!(MuscleGroup::FullBody.region(), None);
+    }
+
+    #[test]
+    fn test_muscle_group_from_ru() {
+        for mg in MuscleGroup::all() {
+            assert_eq!(MuscleGroup::from_ru(mg.name_ru()), Some(*mg));
+        }
+        assert_eq!(MuscleGroup::from_ru("  Спина "), Some(MuscleGroup::Back));
+        assert_eq!(MuscleGroup::from_ru("все тело"), Some(MuscleGroup::FullBody));
+        assert_eq!(MuscleGroup::from_ru("пресс"), Some(MuscleGroup::Core));
+        assert_eq!(MuscleGroup::from_ru("хвост"), None);
+    }
+
     #[test]
     fn test_muscle_group_all_returns_11_groups() {
         let groups = MuscleGroup::all();
@@ -537,4 +1293,42 @@ mod tests {
                 "Extra exercise {} should have description", ex.id);
         }
     }
+
+    #[test]
+    fn test_validate_reps_bounds() {
+        assert_eq!(validate_reps(1), Ok(1));
+        assert_eq!(validate_reps(1000), Ok(1000));
+        assert!(validate_reps(0).is_err());
+        assert!(validate_reps(-5).is_err());
+        assert!(validate_reps(1001).unwrap_err().contains("1000"));
+    }
+
+    #[test]
+    fn test_validate_sets_bounds() {
+        assert_eq!(validate_sets(1), Ok(1));
+        assert_eq!(validate_sets(100), Ok(100));
+        assert!(validate_sets(0).is_err());
+        assert!(validate_sets(-1).is_err());
+        assert!(validate_sets(101).unwrap_err().contains("100"));
+    }
+
+    #[test]
+    fn test_recommended_rest() {
+        assert_eq!(recommended_rest(Category::Legs), (90, 120));
+        assert_eq!(recommended_rest(Category::Strikes), (150, 300));
+        assert_eq!(recommended_rest(Category::Taiji), (30, 60));
+        for category in Category::all() {
+            let (min, max) = recommended_rest(*category);
+            assert!(0 < min && min < max, "{:?}", category);
+        }
+    }
+
+    #[test]
+    fn test_category_ids_round_trip() {
+        for category in Category::all() {
+            assert_eq!(Category::from_id(category.id()), Some(*category));
+        }
+        assert_eq!(Category::from_id("unknown"), None);
+        assert_eq!(Category::Pull.muscle_groups(), &[MuscleGroup::Back, MuscleGroup::Biceps]);
+    }
 }
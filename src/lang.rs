@@ -0,0 +1,203 @@
+//! Localization of bot messages: Russian (default) and English
+
+use serde::{Deserialize, Serialize};
+
+/// Interface language chosen by a user (`/lang`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Lang {
+    #[default]
+    Ru,
+    En,
+}
+
+impl Lang {
+    /// Code stored in the database and typed in `/lang`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Lang::Ru => "ru",
+            Lang::En => "en",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        match code.trim().to_lowercase().as_str() {
+            "ru" => Some(Lang::Ru),
+            "en" => Some(Lang::En),
+            _ => None,
+        }
+    }
+}
+
+/// Translatable message fragments
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Welcome,
+    HelpTitle,
+    SectionTraining,
+    SectionStats,
+    SectionSettings,
+    StatsTitle,
+    StatsTotal,
+    StatsToday,
+    StatsWeek,
+    StatsMonth,
+    StatsTodayBreakdown,
+    Sets,
+    Reps,
+    Max,
+    Seconds,
+    Total,
+    LangSet,
+    LangUsage,
+}
+
+/// Text for `key` in `lang`
+pub fn t(key: Key, lang: Lang) -> &'static str {
+    match lang {
+        Lang::Ru => match key {
+            Key::Welcome => "🥋 无极 majowuji\n\n\
+                Трекер тренировок боевых искусств\n\n\
+                /train - выбрать упражнение\n\
+                /today - сегодняшние тренировки\n\
+                /stats - статистика\n\
+                /balance - баланс мышц\n\
+                /remind - напоминания раз в час\n\
+                /stop - выключить напоминания",
+            Key::HelpTitle => "🥋 无极 majowuji — команды",
+            Key::SectionTraining => "🎯 Тренировка",
+            Key::SectionStats => "📈 Статистика",
+            Key::SectionSettings => "⚙️ Настройки",
+            Key::StatsTitle => "📈 Статистика",
+            Key::StatsTotal => "Всего",
+            Key::StatsToday => "Сегодня",
+            Key::StatsWeek => "Неделя",
+            Key::StatsMonth => "Месяц",
+            Key::StatsTodayBreakdown => "📊 Сегодня:",
+            Key::Sets => "подх.",
+            Key::Reps => "повт.",
+            Key::Max => "макс.",
+            Key::Seconds => "с",
+            Key::Total => "всего",
+            Key::LangSet => "✅ Язык: русский",
+            Key::LangUsage => "Язык: /lang ru или /lang en",
+        },
+        Lang::En => match key {
+            Key::Welcome => "🥋 无极 majowuji\n\n\
+                Martial arts training tracker\n\n\
+                /train - pick an exercise\n\
+                /today - today's trainings\n\
+                /stats - statistics\n\
+                /balance - muscle balance\n\
+                /remind - hourly reminders\n\
+                /stop - turn reminders off",
+            Key::HelpTitle => "🥋 无极 majowuji — commands",
+            Key::SectionTraining => "🎯 Training",
+            Key::SectionStats => "📈 Statistics",
+            Key::SectionSettings => "⚙️ Settings",
+            Key::StatsTitle => "📈 Statistics",
+            Key::StatsTotal => "Total",
+            Key::StatsToday => "Today",
+            Key::StatsWeek => "Week",
+            Key::StatsMonth => "Month",
+            Key::StatsTodayBreakdown => "📊 Today:",
+            Key::Sets => "sets",
+            Key::Reps => "reps",
+            Key::Max => "max",
+            Key::Seconds => "s",
+            Key::Total => "total",
+            Key::LangSet => "✅ Language: English",
+            Key::LangUsage => "Language: /lang en or /lang ru",
+        },
+    }
+}
+
+/// English bot command descriptions (the Russian ones are on the bot's `Command`)
+const COMMAND_DESCRIPTIONS_EN: &[(&str, &str)] = &[
+    ("start", "Get started"),
+    ("help", "Show help"),
+    ("train", "Pick an exercise"),
+    ("today", "Today's trainings"),
+    ("stats", "Statistics (or per exercise: /stats swimmer)"),
+    ("s", "One-line statistics"),
+    ("streak", "Days in a row and best run"),
+    ("records", "Personal records per exercise"),
+    ("balance", "Load balance across muscle groups"),
+    ("balance_trend", "Balance by week"),
+    ("muscle", "Weekly volume for a muscle group: /muscle спина"),
+    ("goal", "When will I reach a target: /goal pushups 50"),
+    ("goalreps", "Same as /goal: /goalreps pushups 30"),
+    ("setgoal", "Set a target: /setgoal plank 3m or /setgoal swimmer 30"),
+    ("goals", "My goals and progress"),
+    ("delgoal", "Delete a goal: /delgoal 3"),
+    ("graph", "Reps chart with trend: /graph swimmer"),
+    ("pulse", "Pulse rise per set as a sparkline: /pulse swimmer"),
+    ("day", "A day's trainings as a JSON file: /day 2026-01-05"),
+    ("body", "Today's body load map"),
+    ("compare", "This week vs last week"),
+    ("leaderboard", "Ranking by sets over 7 days"),
+    ("private", "Hide yourself from the ranking or come back"),
+    ("age", "Age for heart rate zones: /age 35"),
+    ("weight", "Log bodyweight: /weight 72.5"),
+    ("remind", "Hourly reminders on"),
+    ("stop", "Reminders off"),
+    ("quiet", "Quiet hours without reminders: /quiet 22 7 or /quiet off"),
+    ("stopall", "Turn off all background notifications"),
+    ("sore", "Mark a sore muscle group: /sore back"),
+    ("template", "Focus per weekday: /template mon push"),
+    ("where", "Where I train: /where home"),
+    ("gear", "Equipment at hand: /gear handles step"),
+    ("bonusrule", "Bonus threshold in % of base: /bonusrule 80"),
+    ("level", "Bonus difficulty cap 1-5: /level 2 (/level off for no cap)"),
+    ("weekly", "Sessions a week goal: /weekly 4 (/weekly off for no goal)"),
+    ("rest", "Rest timer between sets: /rest 60 (90 s by default)"),
+    ("restday", "Mark a rest day"),
+    ("undo", "Delete the last set"),
+    ("cancel", "Cancel the current set or setup"),
+    ("repeat", "Repeat a day's workout: /repeat 2026-01-05"),
+    ("checklist", "Today's plan with checkmarks"),
+    ("lang", "Interface language: /lang en or /lang ru"),
+    ("tz", "Timezone in minutes from UTC: /tz +180"),
+    ("tip", "Tip from the book"),
+    ("dailytip", "Morning tip every day: /dailytip on 8 or /dailytip off"),
+    ("approve", "Grant access on request (owner): /approve 123456"),
+    ("deny", "Deny an access request (owner): /deny 123456"),
+    ("users", "Users and their activity (owner)"),
+    ("merge", "Move trainings to another account (owner): /merge 111 222"),
+];
+
+/// English description of a bot command (name without `/`)
+pub fn command_description_en(command: &str) -> Option<&'static str> {
+    COMMAND_DESCRIPTIONS_EN
+        .iter()
+        .find(|(name, _)| *name == command.trim_start_matches('/'))
+        .map(|(_, description)| *description)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lang_codes_round_trip() {
+        for lang in [Lang::Ru, Lang::En] {
+            assert_eq!(Lang::from_code(lang.code()), Some(lang));
+        }
+        assert_eq!(Lang::from_code(" EN "), Some(Lang::En));
+        assert_eq!(Lang::from_code("de"), None);
+        assert_eq!(Lang::default(), Lang::Ru);
+    }
+
+    #[test]
+    fn test_t_differs_by_language() {
+        assert_eq!(t(Key::StatsTotal, Lang::Ru), "Всего");
+        assert_eq!(t(Key::StatsTotal, Lang::En), "Total");
+        assert!(t(Key::Welcome, Lang::En).contains("/train"));
+    }
+
+    #[test]
+    fn test_command_description_en() {
+        assert_eq!(command_description_en("/train"), Some("Pick an exercise"));
+        assert_eq!(command_description_en("balance_trend"), Some("Balance by week"));
+        assert_eq!(command_description_en("unknown"), None);
+    }
+}
@@ -5,7 +5,9 @@
 pub mod bot;
 pub mod db;
 pub mod exercises;
+pub mod lang;
 pub mod ml;
+pub mod reporting;
 pub mod tips;
 pub mod tui;
 
@@ -2,20 +2,37 @@
 //!
 //! 无极 (wuji) - "limitless", the state of infinite potential
 
-use anyhow::Result;
-use chrono::Utc;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, NaiveDate, Utc};
 use clap::{Parser, Subcommand};
 
-use majowuji::db::{Database, Training};
+use majowuji::db::{Database, Training, DEFAULT_TZ_OFFSET_MINUTES};
+use majowuji::exercises::{
+    catalog_coverage, coverage_gaps, find_exercise_by_alias, load_from_toml, validate_reps,
+    validate_sets,
+};
 use majowuji::ml::Analytics;
+use majowuji::reporting::{format_secs, monthly_report};
 use majowuji::tui::App;
 
-const DB_PATH: &str = "majowuji.db";
+/// Database file used when neither `--db` nor `MAJOWUJI_DB` is set
+const DEFAULT_DB_PATH: &str = "majowuji.db";
+
+/// Default TUI auto-refresh interval, so bot writes show up without `r`
+const TUI_REFRESH_SECS: u64 = 30;
 
 #[derive(Parser)]
 #[command(name = "majowuji")]
 #[command(author, version, about = "无极 - Personal martial arts training tracker")]
 struct Cli {
+    /// SQLite database file, shared by all subcommands and the bot
+    #[arg(long, global = true, env = "MAJOWUJI_DB", default_value = DEFAULT_DB_PATH)]
+    db: String,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -23,7 +40,11 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Open TUI dashboard
-    Tui,
+    Tui {
+        /// Auto-refresh after this many idle seconds (0 = only on `r`)
+        #[arg(long, default_value_t = TUI_REFRESH_SECS)]
+        refresh_secs: u64,
+    },
 
     /// Log a training session
     Log {
@@ -48,12 +69,74 @@ enum Commands {
         /// Number of records to show
         #[arg(short, long, default_value = "10")]
         limit: usize,
+
+        /// Page number, starting at 1
+        #[arg(long, default_value = "1")]
+        page: usize,
     },
 
     /// Show training statistics
     Stats {
         /// Filter by exercise name
         exercise: Option<String>,
+
+        /// Per-exercise table: entries, reps, time
+        #[arg(long)]
+        grouped: bool,
+    },
+
+    /// Print one day's trainings as JSON (for integrations)
+    Day {
+        /// Local date, YYYY-MM-DD
+        date: String,
+
+        /// Timezone offset east of UTC in minutes (default: owner's /tz or Moscow)
+        #[arg(long)]
+        tz_minutes: Option<i32>,
+    },
+
+    /// Record the owner's bodyweight, or show recent entries without a value
+    Weight {
+        /// Weight in kg (20-300)
+        kg: Option<f64>,
+    },
+
+    /// Print a month's markdown report (totals, records, balance, days)
+    Report {
+        /// Month as YYYY-MM
+        #[arg(long)]
+        month: String,
+    },
+
+    /// Move the owner's double-logged sets to the trash, keeping the earliest of each
+    Dedupe {
+        /// Sets this close together (seconds) with the same exercise, reps and time count as one
+        #[arg(long, default_value = "10")]
+        window_secs: i64,
+    },
+
+    /// Export all trainings to a file (or stdout)
+    Export {
+        /// Output format (only `csv` for now)
+        #[arg(long, default_value = "csv")]
+        format: String,
+
+        /// Output path; prints to stdout when omitted
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Import trainings from a JSON backup
+    Import {
+        /// Path to a JSON array of trainings
+        file: PathBuf,
+    },
+
+    /// Check the exercise catalog for under-covered muscle groups
+    Doctor {
+        /// TOML catalog to check instead of the built-in one
+        #[arg(long)]
+        catalog: Option<PathBuf>,
     },
 
     /// Start Telegram bot
@@ -72,15 +155,36 @@ async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
 
     let cli = Cli::parse();
-    let db = Database::open(DB_PATH)?;
+    let db_path = cli.db;
+
+    // The bot opens (and retries) the database itself, with a clear error
+    if let Some(Commands::Bot { token }) = cli.command {
+        println!("Starting Telegram bot...");
+        println!("База данных: {}", db_path);
+        return majowuji::bot::run_bot(token, &db_path).await;
+    }
+    let db = Database::open(&db_path)?;
 
     match cli.command {
-        Some(Commands::Tui) => {
-            let mut app = App::new(db)?;
+        Some(Commands::Tui { refresh_secs }) => {
+            let mut app = App::new(db)?
+                .with_auto_refresh(Some(std::time::Duration::from_secs(refresh_secs)));
             app.run()?;
         }
 
         Some(Commands::Log { exercise, sets, reps, notes }) => {
+            // Same bounds and messages as the bot
+            validate_sets(sets).map_err(anyhow::Error::msg)?;
+            validate_reps(reps).map_err(anyhow::Error::msg)?;
+            // Short names resolve to the catalog name; unknown names are logged as typed
+            let exercise = match find_exercise_by_alias(&exercise).as_slice() {
+                [] => exercise,
+                [found] => found.name.to_string(),
+                several => {
+                    let names: Vec<_> = several.iter().map(|ex| ex.name).collect();
+                    bail!("Ambiguous exercise '{}': {}", exercise, names.join(", "));
+                }
+            };
             let training = Training {
                 id: None,
                 date: Utc::now(),
@@ -90,18 +194,27 @@ async fn main() -> Result<()> {
                 duration_secs: None,
                 pulse_before: None,
                 pulse_after: None,
+                rpe: None,
                 notes,
                 user_id: None,
+                context: None,
+                category: None,
+                is_timed: None,
             };
             let id = db.add_training_cli(&training)?;
             println!("Logged: {} - {}x{} (id: {})", exercise, sets, reps, id);
         }
 
-        Some(Commands::List { limit }) => {
-            let trainings = db.get_trainings()?;
-            println!("Recent trainings:");
+        Some(Commands::List { limit, page }) => {
+            if page == 0 {
+                bail!("--page starts at 1");
+            }
+            let total = db.count_all_trainings()?;
+            let pages = total.div_ceil(limit.max(1)).max(1);
+            let trainings = db.get_all_trainings_paged(limit, (page - 1) * limit)?;
+            println!("Recent trainings (page {} of {}, {} total):", page, pages, total);
             println!("{:-<60}", "");
-            for t in trainings.iter().take(limit) {
+            for t in &trainings {
                 println!(
                     "{} | {:20} | {}x{} | {}",
                     t.date.format("%Y-%m-%d %H:%M"),
@@ -113,7 +226,15 @@ async fn main() -> Result<()> {
             }
         }
 
-        Some(Commands::Stats { exercise }) => {
+        Some(Commands::Stats { grouped: true, .. }) => {
+            println!("{:20} | {:>7} | {:>7} | {:>8}", "Exercise", "Entries", "Reps", "Time");
+            println!("{:-<52}", "");
+            for (exercise, count, reps, secs) in db.exercise_summary_all()? {
+                println!("{:20} | {:>7} | {:>7} | {:>8}", exercise, count, reps, format_secs(secs));
+            }
+        }
+
+        Some(Commands::Stats { exercise, .. }) => {
             let trainings = db.get_trainings()?;
             let analytics = Analytics::new(trainings);
 
@@ -121,28 +242,137 @@ async fn main() -> Result<()> {
             println!("{:-<40}", "");
 
             if let Some(ex) = exercise {
-                let volume = analytics.total_volume(&ex);
                 println!("Exercise: {}", ex);
-                println!("Total volume: {} reps", volume);
+                println!("Total volume: {} reps", analytics.total_reps(Some(&ex)));
+                println!("Total time: {}", format_secs(analytics.total_duration_secs(Some(&ex))));
 
                 if let Some((sets, reps)) = analytics.predict_next_load(&ex) {
                     println!("Suggested next: {}x{}", sets, reps);
                 }
             } else {
+                println!("Total sets: {}", analytics.total_sets());
+                println!("Total reps: {}", analytics.total_reps(None));
+                println!("Total time: {}", format_secs(analytics.total_duration_secs(None)));
                 let freq = analytics.weekly_frequency();
                 println!("Weekly frequency: {:.1} sessions/week", freq);
             }
         }
 
-        Some(Commands::Bot { token }) => {
-            println!("Starting Telegram bot...");
-            println!("База данных: {}", DB_PATH);
-            majowuji::bot::run_bot(token, DB_PATH).await?;
+        Some(Commands::Day { date, tz_minutes }) => {
+            let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                .with_context(|| format!("Invalid date '{}', expected YYYY-MM-DD", date))?;
+            let tz_minutes = match tz_minutes {
+                Some(minutes) => minutes,
+                None => match db.get_owner()? {
+                    Some(owner) => db.user_tz_offset_minutes(owner.id)?,
+                    None => DEFAULT_TZ_OFFSET_MINUTES,
+                },
+            };
+            let trainings = db.get_trainings_on_day(None, date, tz_minutes * 60)?;
+            println!("{}", serde_json::to_string_pretty(&trainings)?);
+        }
+
+        Some(Commands::Report { month }) => {
+            let first_day = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+                .with_context(|| format!("Invalid month '{}', expected YYYY-MM", month))?;
+            let trainings = db.get_trainings()?;
+            print!("{}", monthly_report(&trainings, first_day.year(), first_day.month()));
+        }
+
+        Some(Commands::Dedupe { window_secs }) => {
+            let owner = db.get_owner()?
+                .context("No owner yet: start the bot and message it first")?;
+            let mut removed = 0;
+            for group in db.find_duplicate_trainings(owner.id, window_secs)? {
+                let (kept, duplicates) = group.split_first().expect("duplicate groups have 2+ rows");
+                println!(
+                    "{} | {} | {}x{}: keeping id {}, {} duplicate(s)",
+                    kept.date.format("%Y-%m-%d %H:%M:%S"),
+                    kept.exercise,
+                    kept.sets,
+                    kept.reps,
+                    kept.id.unwrap_or_default(),
+                    duplicates.len()
+                );
+                for duplicate in duplicates {
+                    if let Some(id) = duplicate.id
+                        && db.delete_training(id, owner.id)?
+                    {
+                        removed += 1;
+                    }
+                }
+            }
+            println!("Moved {} duplicate(s) to the trash (purged after 24 h)", removed);
+        }
+
+        Some(Commands::Weight { kg }) => {
+            let owner = db.get_owner()?
+                .context("No owner yet: start the bot and message it first")?;
+            match kg {
+                Some(kg) => {
+                    db.add_bodyweight(owner.id, Utc::now(), kg)?;
+                    println!("Logged weight: {:.1} kg", kg);
+                }
+                None => {
+                    println!("Recent weights:");
+                    println!("{:-<30}", "");
+                    for w in db.get_bodyweights_for_user(owner.id)?.iter().take(10) {
+                        println!("{} | {:.1} kg", w.date.format("%Y-%m-%d %H:%M"), w.weight_kg);
+                    }
+                }
+            }
         }
 
+        Some(Commands::Export { format, out }) => {
+            if format != "csv" {
+                anyhow::bail!("Unsupported export format '{}', expected csv", format);
+            }
+            match out {
+                Some(path) => {
+                    let file = File::create(&path)
+                        .with_context(|| format!("Cannot create {}", path.display()))?;
+                    db.export_csv(BufWriter::new(file))?;
+                    println!("Exported to {}", path.display());
+                }
+                None => db.export_csv(std::io::stdout().lock())?,
+            }
+        }
+
+        Some(Commands::Import { file }) => {
+            let data = std::fs::read_to_string(&file)
+                .with_context(|| format!("Cannot read {}", file.display()))?;
+            let report = db.import_json(&data)?;
+            println!("Imported {} trainings from {}", report.inserted, file.display());
+            if !report.skipped.is_empty() {
+                println!("Skipped {} rows:", report.skipped.len());
+                for reason in &report.skipped {
+                    println!("  {}", reason);
+                }
+            }
+        }
+
+        Some(Commands::Doctor { catalog }) => {
+            // Groups targeted by fewer exercises than this are flagged
+            const MIN_COVERAGE: usize = 3;
+
+            println!("Catalog muscle coverage");
+            println!("{:-<40}", "");
+            let coverage = match catalog {
+                Some(path) => catalog_coverage(&load_from_toml(path)?),
+                None => coverage_gaps(),
+            };
+            for (group, count) in coverage {
+                let flag = if count < MIN_COVERAGE { "  <- gap" } else { "" };
+                println!("{:15} {:>3} exercises{}", group.name_ru(), count, flag);
+            }
+        }
+
+        Some(Commands::Bot { .. }) => unreachable!("the bot is started before opening the database"),
+
         None => {
             // Default: show TUI
-            let mut app = App::new(db)?;
+            let mut app = App::new(db)?
+                .with_auto_refresh(Some(std::time::Duration::from_secs(TUI_REFRESH_SECS)));
             app.run()?;
         }
     }
@@ -0,0 +1,115 @@
+//! Progress chart rendering (PNG for the bot's /graph)
+//!
+//! Plotters is built without font support, so the image carries no text:
+//! the caption sent with the photo describes the axes.
+
+use std::io::Cursor;
+
+use anyhow::{anyhow, Result};
+use image::{ImageFormat, RgbImage};
+use plotters::prelude::*;
+
+use super::ProgressPredictor;
+
+/// Chart size in pixels
+const WIDTH: u32 = 800;
+const HEIGHT: u32 = 500;
+
+/// Samples along the trend curve (quadratic fits are not straight)
+const TREND_SAMPLES: usize = 50;
+
+/// Gridline step in reps
+const GRID_STEP: i32 = 5;
+
+/// Render reps over time with the fitted trend as a PNG
+pub fn render_progress_png(predictor: &ProgressPredictor) -> Result<Vec<u8>> {
+    let points = predictor.points();
+    let last_day = points.last().map(|(day, _)| *day).unwrap_or(0.0).max(1.0);
+    let max_reps = points.iter().map(|(_, reps)| *reps).max().unwrap_or(0);
+    let trend: Vec<(f64, f64)> = (0..=TREND_SAMPLES)
+        .map(|i| {
+            let day = last_day * i as f64 / TREND_SAMPLES as f64;
+            (day, predictor.trend_at(day))
+        })
+        .collect();
+    let y_max = trend
+        .iter()
+        .map(|(_, reps)| *reps)
+        .fold(max_reps as f64, f64::max)
+        * 1.1
+        + 1.0;
+
+    let mut buffer = vec![0u8; (WIDTH * HEIGHT * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut buffer, (WIDTH, HEIGHT)).into_drawing_area();
+        root.fill(&WHITE).map_err(|e| anyhow!("Chart drawing failed: {e}"))?;
+        let area = root.margin(20, 20, 20, 20);
+        let mut chart = ChartBuilder::on(&area)
+            .build_cartesian_2d(0.0..last_day, 0.0..y_max)
+            .map_err(|e| anyhow!("Chart drawing failed: {e}"))?;
+
+        let grid_style = RGBColor(225, 225, 225);
+        for reps in (0..=y_max as i32).step_by(GRID_STEP as usize) {
+            chart
+                .draw_series(LineSeries::new([(0.0, reps as f64), (last_day, reps as f64)], grid_style))
+                .map_err(|e| anyhow!("Chart drawing failed: {e}"))?;
+        }
+
+        chart
+            .draw_series(LineSeries::new(trend, RED.stroke_width(3)))
+            .map_err(|e| anyhow!("Chart drawing failed: {e}"))?;
+        chart
+            .draw_series(
+                points
+                    .iter()
+                    .map(|(day, reps)| Circle::new((*day, *reps as f64), 5, BLUE.filled())),
+            )
+            .map_err(|e| anyhow!("Chart drawing failed: {e}"))?;
+
+        root.present().map_err(|e| anyhow!("Chart drawing failed: {e}"))?;
+    }
+
+    let image = RgbImage::from_raw(WIDTH, HEIGHT, buffer)
+        .ok_or_else(|| anyhow!("Chart buffer has the wrong size"))?;
+    let mut png = Cursor::new(Vec::new());
+    image.write_to(&mut png, ImageFormat::Png)?;
+    Ok(png.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Training;
+    use chrono::{Duration, Utc};
+
+    fn training(reps: i32, days_ago: i64) -> Training {
+        Training {
+            id: None,
+            date: Utc::now() - Duration::days(days_ago),
+            exercise: "пловец".to_string(),
+            sets: 1,
+            reps,
+            duration_secs: None,
+            pulse_before: None,
+            pulse_after: None,
+            rpe: None,
+            notes: None,
+            user_id: None,
+            context: None,
+            category: None,
+            is_timed: None,
+        }
+    }
+
+    #[test]
+    fn test_render_progress_png() {
+        let trainings: Vec<_> = (0..6).map(|i| training(10 + i as i32 * 2, 30 - i * 5)).collect();
+        let predictor = ProgressPredictor::best_fit(&trainings, "пловец").unwrap();
+
+        let png = render_progress_png(&predictor).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+        let decoded = image::load_from_memory(&png).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (WIDTH, HEIGHT));
+    }
+}
@@ -0,0 +1,98 @@
+//! Heart rate zones - Intensity of a set from pulse and estimated max HR
+
+/// Intensity zone by share of max heart rate: `Rest` below 50%, then the standard five zones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Zone {
+    Rest,      // < 50% max HR
+    VeryLight, // 50-60%
+    Light,     // 60-70%
+    Moderate,  // 70-80%
+    High,      // 80-90%
+    Maximum,   // >= 90%
+}
+
+impl Zone {
+    pub fn name_ru(&self) -> &'static str {
+        match self {
+            Zone::Rest => "покой",
+            Zone::VeryLight => "очень лёгкая",
+            Zone::Light => "лёгкая",
+            Zone::Moderate => "средняя",
+            Zone::High => "высокая",
+            Zone::Maximum => "максимальная",
+        }
+    }
+}
+
+/// Estimated max heart rate (220 - age)
+pub fn max_hr(age: i32) -> i32 {
+    220 - age
+}
+
+/// Classify a pulse reading into an intensity zone
+pub fn hr_zone(pulse: i32, max_hr: i32) -> Zone {
+    let percent = pulse as f64 / max_hr.max(1) as f64 * 100.0;
+    if percent >= 90.0 {
+        Zone::Maximum
+    } else if percent >= 80.0 {
+        Zone::High
+    } else if percent >= 70.0 {
+        Zone::Moderate
+    } else if percent >= 60.0 {
+        Zone::Light
+    } else if percent >= 50.0 {
+        Zone::VeryLight
+    } else {
+        Zone::Rest
+    }
+}
+
+/// Zone of a pulse reading for a user of the given age
+pub fn zone(pulse: i32, age: i32) -> Zone {
+    hr_zone(pulse, max_hr(age))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_hr() {
+        assert_eq!(max_hr(40), 180);
+    }
+
+    #[test]
+    fn test_hr_zone_boundaries() {
+        // max 200: 50% = 100, 60% = 120, 70% = 140, 80% = 160, 90% = 180
+        assert_eq!(hr_zone(99, 200), Zone::Rest);
+        assert_eq!(hr_zone(100, 200), Zone::VeryLight);
+        assert_eq!(hr_zone(119, 200), Zone::VeryLight);
+        assert_eq!(hr_zone(120, 200), Zone::Light);
+        assert_eq!(hr_zone(140, 200), Zone::Moderate);
+        assert_eq!(hr_zone(160, 200), Zone::High);
+        assert_eq!(hr_zone(180, 200), Zone::Maximum);
+        assert_eq!(hr_zone(210, 200), Zone::Maximum);
+    }
+
+    #[test]
+    fn test_zone_by_age_boundaries() {
+        // age 40 -> max 180: 50% = 90, 60% = 108, 70% = 126, 80% = 144, 90% = 162
+        assert_eq!(zone(89, 40), Zone::Rest);
+        assert_eq!(zone(90, 40), Zone::VeryLight);
+        assert_eq!(zone(107, 40), Zone::VeryLight);
+        assert_eq!(zone(108, 40), Zone::Light);
+        assert_eq!(zone(125, 40), Zone::Light);
+        assert_eq!(zone(126, 40), Zone::Moderate);
+        assert_eq!(zone(143, 40), Zone::Moderate);
+        assert_eq!(zone(144, 40), Zone::High);
+        assert_eq!(zone(161, 40), Zone::High);
+        assert_eq!(zone(162, 40), Zone::Maximum);
+    }
+
+    #[test]
+    fn test_zone_names() {
+        assert_eq!(Zone::Rest.name_ru(), "покой");
+        assert_eq!(Zone::Light.name_ru(), "лёгкая");
+        assert_eq!(Zone::Maximum.name_ru(), "максимальная");
+    }
+}
@@ -4,18 +4,120 @@
 //! - Muscle group load tracking
 //! - Exercise recommendations based on balance
 //! - Progress prediction using linear regression (linfa)
+//! - Heart rate intensity zones
+//! - Pulse trend per exercise
+//! - Aggregated per-exercise progress reports
+//! - Progress charts (PNG)
 
 pub mod muscle_tracker;
 pub mod recommender;
 pub mod predictor;
 pub mod progress_goal;
+pub mod heart_rate;
+pub mod report;
+pub mod pulse_trend;
+pub mod chart;
 
 pub use muscle_tracker::MuscleTracker;
-pub use recommender::{Recommender, Recommendation};
-pub use predictor::ProgressPredictor;
-pub use progress_goal::{GoalCalculator, ProgressGoal, GoalConfidence};
+pub use recommender::{PlanItem, Recommender, Recommendation};
+pub use predictor::{Prediction, ProgressPredictor};
+pub use progress_goal::{FatigueParams, GoalCalculator, ProgressGoal, GoalConfidence};
+pub use heart_rate::{hr_zone, max_hr, zone, Zone};
+pub use report::{progress_report, ProgressReport};
+pub use pulse_trend::pulse_trend;
+pub use chart::render_progress_png;
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::ops::Range;
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Utc};
 
 use crate::db::Training;
+use crate::exercises::MuscleGroup;
+
+/// Aggregated training stats for a time period
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PeriodStats {
+    pub sessions: usize,              // Distinct training days
+    pub sets: i32,
+    pub volume: i32,                  // sets * reps
+    pub top_exercise: Option<String>, // Exercise with the most sets
+}
+
+/// Stats of a period next to the period it is compared against
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeriodComparison {
+    pub current: PeriodStats,
+    pub previous: PeriodStats,
+}
+
+/// Consecutive training days ending at `today`, or at yesterday if today
+/// has no training yet (the streak isn't broken until the day is over).
+pub fn streak_days(training_days: &HashSet<NaiveDate>, today: NaiveDate) -> u32 {
+    streak_days_with_rest(training_days, &HashSet::new(), today)
+}
+
+/// Like `streak_days`, but planned rest days keep the streak alive
+/// (they don't break it and don't add to it)
+pub fn streak_days_with_rest(
+    training_days: &HashSet<NaiveDate>,
+    rest_days: &HashSet<NaiveDate>,
+    today: NaiveDate,
+) -> u32 {
+    let active = |d: &NaiveDate| training_days.contains(d) || rest_days.contains(d);
+
+    let mut day = if active(&today) {
+        today
+    } else {
+        match today.pred_opt() {
+            Some(yesterday) => yesterday,
+            None => return 0,
+        }
+    };
+
+    let mut streak = 0;
+    while active(&day) {
+        if training_days.contains(&day) {
+            streak += 1;
+        }
+        match day.pred_opt() {
+            Some(prev) => day = prev,
+            None => break,
+        }
+    }
+    streak
+}
+
+/// Sessions still needed this week to reach `target` (0 once it's met)
+pub fn sessions_remaining(target: u8, done: usize) -> usize {
+    usize::from(target).saturating_sub(done)
+}
+
+/// Valid pulse pairs needed for a recovery trend
+const MIN_PULSE_PAIRS: usize = 4;
+
+/// Most recent pulse pairs considered for the recovery trend
+const RECENT_PULSE_PAIRS: usize = 20;
+
+/// Readings needed before a resting pulse baseline is trusted
+const MIN_PULSE_READINGS: usize = 5;
+
+/// Resting pulse this much above baseline suggests under-recovery
+const ELEVATED_PULSE_RATIO: f64 = 1.15;
+
+/// Days with a pre-session pulse needed for a resting pulse trend
+const MIN_PULSE_DAYS: usize = 5;
+
+/// Window of the resting pulse trend
+const RESTING_PULSE_TREND_DAYS: i64 = 14;
+
+/// Resting pulse rise (bpm per day) that signals accumulating fatigue
+pub const RESTING_PULSE_DRIFT_BPM_PER_DAY: f64 = 0.5;
+
+/// Whether a pre-session pulse is notably above the resting baseline
+pub fn resting_pulse_elevated(pulse: i32, baseline: f64) -> bool {
+    pulse as f64 >= baseline * ELEVATED_PULSE_RATIO
+}
 
 /// Training analytics
 pub struct Analytics {
@@ -27,35 +129,347 @@ impl Analytics {
         Self { trainings }
     }
 
-    /// Calculate total volume (sets * reps) for an exercise
-    pub fn total_volume(&self, exercise: &str) -> i32 {
+    /// Trainings of `exercise` (case-insensitive substring), or all for None
+    fn matching<'a>(&'a self, exercise: Option<&str>) -> impl Iterator<Item = &'a Training> {
+        let exercise = exercise.map(str::to_lowercase);
         self.trainings
             .iter()
-            .filter(|t| t.exercise.to_lowercase().contains(&exercise.to_lowercase()))
-            .map(|t| t.sets * t.reps)
-            .sum()
+            .filter(move |t| exercise.as_ref().is_none_or(|ex| t.exercise.to_lowercase().contains(ex)))
+    }
+
+    /// Calculate total volume (sets * reps) for an exercise
+    pub fn total_volume(&self, exercise: &str) -> i32 {
+        self.matching(Some(exercise)).map(|t| t.sets * t.reps).sum()
+    }
+
+    /// Total time in seconds across all trainings, or those of `exercise`
+    pub fn total_duration_secs(&self, exercise: Option<&str>) -> i64 {
+        self.matching(exercise).filter_map(|t| t.duration_secs).map(i64::from).sum()
     }
 
-    /// Get training frequency (sessions per week)
+    /// Total sets across all trainings
+    pub fn total_sets(&self) -> i64 {
+        self.trainings.iter().map(|t| i64::from(t.sets)).sum()
+    }
+
+    /// Total reps (sets * reps) across all trainings, or those of `exercise`
+    pub fn total_reps(&self, exercise: Option<&str>) -> i64 {
+        self.matching(exercise).map(|t| i64::from(t.sets) * i64::from(t.reps)).sum()
+    }
+
+    /// Get training frequency (sessions per week) over the span of training days.
+    /// 0.0 until trainings cover at least two distinct days.
     pub fn weekly_frequency(&self) -> f64 {
-        if self.trainings.is_empty() {
+        let dates: Vec<_> = self.trainings.iter().map(|t| t.date.date_naive()).collect();
+        let (Some(first), Some(last)) = (dates.iter().min(), dates.iter().max()) else {
+            return 0.0;
+        };
+        if first == last {
             return 0.0;
         }
 
-        let dates: Vec<_> = self.trainings.iter().map(|t| t.date.date_naive()).collect();
-        if dates.len() < 2 {
-            return 0.0;
+        let days = (*last - *first).num_days().max(1) as f64;
+        (self.trainings.len() as f64 / days) * 7.0
+    }
+
+    /// Median pulse_before across trainings (None until enough readings)
+    pub fn resting_pulse_baseline(&self) -> Option<f64> {
+        let mut pulses: Vec<i32> = self.trainings.iter().filter_map(|t| t.pulse_before).collect();
+        if pulses.len() < MIN_PULSE_READINGS {
+            return None;
         }
+        pulses.sort_unstable();
+        let mid = pulses.len() / 2;
+        Some(if pulses.len().is_multiple_of(2) {
+            (pulses[mid - 1] + pulses[mid]) as f64 / 2.0
+        } else {
+            pulses[mid] as f64
+        })
+    }
 
-        let first = dates.last().unwrap();
-        let last = dates.first().unwrap();
-        let days = (*last - *first).num_days() as f64;
+    /// Slope of the resting pulse over the last two weeks (Moscow days), bpm per day.
+    /// Positive means the pulse before training keeps climbing (fatigue).
+    pub fn resting_pulse_trend(&self) -> Option<f64> {
+        self.resting_pulse_trend_at(Utc::now(), progress_goal::moscow_tz())
+    }
 
-        if days == 0.0 {
-            return self.trainings.len() as f64;
+    /// Resting pulse trend as of `now`, one reading per local day in `tz`:
+    /// `pulse_before` of the day's first set. None below `MIN_PULSE_DAYS` days.
+    pub fn resting_pulse_trend_at(&self, now: DateTime<Utc>, tz: FixedOffset) -> Option<f64> {
+        let since = now - chrono::Duration::days(RESTING_PULSE_TREND_DAYS);
+        let mut first_of_day: BTreeMap<NaiveDate, (DateTime<Utc>, i32)> = BTreeMap::new();
+        for t in self.trainings.iter().filter(|t| t.date >= since && t.date <= now) {
+            let Some(pulse) = t.pulse_before else {
+                continue;
+            };
+            let day = t.date.with_timezone(&tz).date_naive();
+            let entry = first_of_day.entry(day).or_insert((t.date, pulse));
+            if t.date < entry.0 {
+                *entry = (t.date, pulse);
+            }
+        }
+        if first_of_day.len() < MIN_PULSE_DAYS {
+            return None;
         }
 
-        (self.trainings.len() as f64 / days) * 7.0
+        // Least-squares slope of pulse over day number
+        let first_day = *first_of_day.keys().next()?;
+        let points: Vec<(f64, f64)> = first_of_day
+            .iter()
+            .map(|(day, (_, pulse))| ((*day - first_day).num_days() as f64, *pulse as f64))
+            .collect();
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+        let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+        let covariance: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+        let variance: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+        Some(covariance / variance)
+    }
+
+    /// Distinct calendar days (in `tz`) with at least one training
+    pub fn training_days(&self, tz: FixedOffset) -> HashSet<NaiveDate> {
+        self.trainings
+            .iter()
+            .map(|t| t.date.with_timezone(&tz).date_naive())
+            .collect()
+    }
+
+    /// All-time bests as (exercise, best, is_timed), sorted by exercise:
+    /// max reps, or max duration in seconds for timed exercises.
+    /// Exercises with neither a catalog entry nor a stored snapshot are skipped.
+    pub fn personal_records(&self) -> Vec<(String, i32, bool)> {
+        let mut best: BTreeMap<&str, (i32, bool)> = BTreeMap::new();
+        for t in &self.trainings {
+            let Some(is_timed) = t.exercise_is_timed() else {
+                continue;
+            };
+            let value = if is_timed { t.duration_secs } else { Some(t.reps) };
+            if let Some(value) = value {
+                let entry = best.entry(&t.exercise).or_insert((value, is_timed));
+                entry.0 = entry.0.max(value);
+            }
+        }
+        best.into_iter()
+            .map(|(name, (value, is_timed))| (name.to_string(), value, is_timed))
+            .collect()
+    }
+
+    /// Change in the average pulse rise (`pulse_after - pulse_before`) from
+    /// the older to the newer half of recent sets of `exercise`.
+    /// Negative means the heart works less for the same set (better conditioning).
+    /// Sets without both pulses are ignored; None below `MIN_PULSE_PAIRS`.
+    pub fn pulse_recovery_trend(&self, exercise: &str) -> Option<f64> {
+        let recent = self.pulse_deltas(exercise, RECENT_PULSE_PAIRS);
+        if recent.len() < MIN_PULSE_PAIRS {
+            return None;
+        }
+
+        let (older, newer) = recent.split_at(recent.len() / 2);
+        let avg = |half: &[i32]| half.iter().map(|delta| *delta as f64).sum::<f64>() / half.len() as f64;
+        Some(avg(newer) - avg(older))
+    }
+
+    /// Pulse rises (`pulse_after - pulse_before`) of the last `limit` sets of
+    /// `exercise` that have both pulses, oldest first
+    pub fn pulse_deltas(&self, exercise: &str, limit: usize) -> Vec<i32> {
+        let mut pairs: Vec<_> = self.trainings
+            .iter()
+            .filter(|t| t.exercise == exercise)
+            .filter_map(|t| Some((t.date, t.pulse_after? - t.pulse_before?)))
+            .collect();
+        pairs.sort_by_key(|(date, _)| *date);
+        pairs[pairs.len().saturating_sub(limit)..]
+            .iter()
+            .map(|(_, delta)| *delta)
+            .collect()
+    }
+
+    /// Balance score of each of the last `weeks` ISO weeks (Moscow time)
+    /// that has trainings, oldest first, as (Monday, score)
+    pub fn balance_score_by_week(&self, weeks: usize) -> Vec<(NaiveDate, f32)> {
+        let tz = progress_goal::moscow_tz();
+        self.balance_score_by_week_at(weeks, Utc::now().with_timezone(&tz).date_naive(), tz)
+    }
+
+    /// `balance_score_by_week` for weeks up to the one containing `today`
+    pub fn balance_score_by_week_at(&self, weeks: usize, today: NaiveDate, tz: FixedOffset) -> Vec<(NaiveDate, f32)> {
+        let monday = |date: NaiveDate| date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64);
+        let current = monday(today);
+        let Some(first) = current.checked_sub_signed(chrono::Duration::weeks(weeks as i64 - 1)) else {
+            return Vec::new();
+        };
+
+        let mut by_week: BTreeMap<NaiveDate, Vec<Training>> = BTreeMap::new();
+        for training in &self.trainings {
+            let week = monday(training.date.with_timezone(&tz).date_naive());
+            if weeks > 0 && (first..=current).contains(&week) {
+                by_week.entry(week).or_default().push(training.clone());
+            }
+        }
+
+        by_week
+            .into_iter()
+            .map(|(week, trainings)| {
+                let sunday = week + chrono::Duration::days(6);
+                (week, MuscleTracker::from_trainings_at(&trainings, sunday).get_balance_score())
+            })
+            .collect()
+    }
+
+    /// Reps (sets x reps) that hit `group` in each of the last `weeks` ISO weeks
+    /// (Moscow time), oldest first as (Monday, reps); weeks without load are zero.
+    /// An exercise counts only for the groups it lists, so whole-body forms feed
+    /// `FullBody` and not every specific group.
+    pub fn muscle_volume_by_week(&self, group: MuscleGroup, weeks: usize) -> Vec<(NaiveDate, i32)> {
+        let tz = progress_goal::moscow_tz();
+        self.muscle_volume_by_week_at(group, weeks, Utc::now().with_timezone(&tz).date_naive(), tz)
+    }
+
+    /// `muscle_volume_by_week` for weeks up to the one containing `today`
+    pub fn muscle_volume_by_week_at(
+        &self,
+        group: MuscleGroup,
+        weeks: usize,
+        today: NaiveDate,
+        tz: FixedOffset,
+    ) -> Vec<(NaiveDate, i32)> {
+        let monday = |date: NaiveDate| date - chrono::Duration::days(date.weekday().num_days_from_monday() as i64);
+        let current = monday(today);
+        let mut by_week: BTreeMap<NaiveDate, i32> = (0..weeks as i64)
+            .rev()
+            .map(|ago| (current - chrono::Duration::weeks(ago), 0))
+            .collect();
+
+        for training in &self.trainings {
+            let hits_group = training.muscle_groups().contains(&group);
+            let week = monday(training.date.with_timezone(&tz).date_naive());
+            if hits_group && let Some(volume) = by_week.get_mut(&week) {
+                *volume += training.sets * training.reps;
+            }
+        }
+        by_week.into_iter().collect()
+    }
+
+    /// Sessions (distinct training days in `tz`) so far this Monday-start week
+    pub fn sessions_this_week(&self, tz: FixedOffset) -> usize {
+        self.sessions_this_week_at(Utc::now().with_timezone(&tz).date_naive(), tz)
+    }
+
+    /// `sessions_this_week` for the week containing local `today`
+    pub fn sessions_this_week_at(&self, today: NaiveDate, tz: FixedOffset) -> usize {
+        let monday = today - chrono::Duration::days(today.weekday().num_days_from_monday() as i64);
+        self.training_days(tz)
+            .into_iter()
+            .filter(|day| (monday..=today).contains(day))
+            .count()
+    }
+
+    /// Current run of consecutive training days (Moscow time); today
+    /// without a training yet doesn't break it
+    pub fn current_streak(&self) -> u32 {
+        let tz = progress_goal::moscow_tz();
+        self.current_streak_at(Utc::now().with_timezone(&tz).date_naive(), tz)
+    }
+
+    /// Current streak ending at local `today` in `tz`
+    pub fn current_streak_at(&self, today: NaiveDate, tz: FixedOffset) -> u32 {
+        streak_days(&self.training_days(tz), today)
+    }
+
+    /// Longest run of consecutive training days ever (Moscow time)
+    pub fn longest_streak(&self) -> u32 {
+        self.longest_streak_in(progress_goal::moscow_tz())
+    }
+
+    /// Longest run of consecutive training days in `tz`
+    pub fn longest_streak_in(&self, tz: FixedOffset) -> u32 {
+        let mut days: Vec<_> = self.training_days(tz).into_iter().collect();
+        days.sort();
+
+        let (mut longest, mut run) = (0, 0);
+        let mut prev: Option<NaiveDate> = None;
+        for day in days {
+            run = match prev {
+                Some(p) if p.succ_opt() == Some(day) => run + 1,
+                _ => 1,
+            };
+            longest = longest.max(run);
+            prev = Some(day);
+        }
+        longest
+    }
+
+    /// Acute:chronic workload ratio: volume of the last 7 days over the
+    /// average weekly volume of the last 28. None until history spans 28 days.
+    pub fn acwr(&self) -> Option<f64> {
+        self.acwr_at(Utc::now())
+    }
+
+    /// ACWR as of `now`
+    pub fn acwr_at(&self, now: DateTime<Utc>) -> Option<f64> {
+        let chronic_start = now - chrono::Duration::days(28);
+        let acute_start = now - chrono::Duration::days(7);
+
+        let earliest = self.trainings.iter().map(|t| t.date).min()?;
+        if earliest > chronic_start {
+            return None;
+        }
+
+        let volume_since = |start: DateTime<Utc>| -> i32 {
+            self.trainings
+                .iter()
+                .filter(|t| t.date >= start && t.date < now)
+                .map(|t| t.sets * t.reps)
+                .sum()
+        };
+        let chronic_weekly = volume_since(chronic_start) as f64 / 4.0;
+        if chronic_weekly <= 0.0 {
+            return None;
+        }
+        Some(volume_since(acute_start) as f64 / chronic_weekly)
+    }
+
+    /// Aggregate trainings with `period.start <= date < period.end`.
+    /// Sessions are counted as distinct calendar days in `tz`.
+    pub fn period_stats(&self, period: Range<DateTime<Utc>>, tz: FixedOffset) -> PeriodStats {
+        let in_period: Vec<_> = self.trainings
+            .iter()
+            .filter(|t| period.contains(&t.date))
+            .collect();
+
+        let days: HashSet<_> = in_period.iter()
+            .map(|t| t.date.with_timezone(&tz).date_naive())
+            .collect();
+
+        let mut sets_by_exercise: HashMap<&str, i32> = HashMap::new();
+        for t in &in_period {
+            *sets_by_exercise.entry(&t.exercise).or_default() += t.sets;
+        }
+        // Ties broken by name so the result is stable
+        let top_exercise = sets_by_exercise
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(name, _)| name.to_string());
+
+        PeriodStats {
+            sessions: days.len(),
+            sets: in_period.iter().map(|t| t.sets).sum(),
+            volume: in_period.iter().map(|t| t.sets * t.reps).sum(),
+            top_exercise,
+        }
+    }
+
+    /// Compare two periods (e.g. this week vs last week)
+    pub fn compare_periods(
+        &self,
+        current: Range<DateTime<Utc>>,
+        previous: Range<DateTime<Utc>>,
+        tz: FixedOffset,
+    ) -> PeriodComparison {
+        PeriodComparison {
+            current: self.period_stats(current, tz),
+            previous: self.period_stats(previous, tz),
+        }
     }
 
     /// Predict next training load (simple moving average)
@@ -82,7 +496,7 @@ impl Analytics {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Utc;
+    use chrono::{TimeZone, Utc};
 
     fn create_training(exercise: &str, sets: i32, reps: i32) -> Training {
         Training {
@@ -94,8 +508,12 @@ mod tests {
             duration_secs: None,
             pulse_before: None,
             pulse_after: None,
+            rpe: None,
             notes: None,
             user_id: None,
+            context: None,
+            category: None,
+            is_timed: None,
         }
     }
 
@@ -109,8 +527,12 @@ mod tests {
             duration_secs: None,
             pulse_before: None,
             pulse_after: None,
+            rpe: None,
             notes: None,
             user_id: None,
+            context: None,
+            category: None,
+            is_timed: None,
         }
     }
 
@@ -163,6 +585,78 @@ mod tests {
         assert_eq!(analytics.total_volume("отжимания"), 0);
     }
 
+    fn pulse_day(days_ago: i64, pulse_before: Option<i32>) -> Training {
+        Training {
+            date: Utc.with_ymd_and_hms(2026, 1, 20, 9, 0, 0).unwrap() - chrono::Duration::days(days_ago),
+            pulse_before,
+            ..create_training("пловец", 1, 10)
+        }
+    }
+
+    fn pulse_trend_now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 1, 20, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_resting_pulse_trend_rising() {
+        // One bpm more every day; later sets of the day don't count
+        let mut trainings: Vec<_> = (0..7).map(|i| pulse_day(i, Some(70 - i as i32))).collect();
+        trainings.push(Training {
+            date: pulse_day(0, None).date + chrono::Duration::hours(1),
+            ..pulse_day(0, Some(110))
+        });
+        let trend = Analytics::new(trainings).resting_pulse_trend_at(pulse_trend_now(), progress_goal::moscow_tz()).unwrap();
+        assert!((trend - 1.0).abs() < 1e-9, "got {}", trend);
+        assert!(trend > RESTING_PULSE_DRIFT_BPM_PER_DAY);
+    }
+
+    #[test]
+    fn test_resting_pulse_trend_flat() {
+        let trainings: Vec<_> = (0..6).map(|i| pulse_day(i * 2, Some(62))).collect();
+        let trend = Analytics::new(trainings).resting_pulse_trend_at(pulse_trend_now(), progress_goal::moscow_tz());
+        assert_eq!(trend, Some(0.0));
+    }
+
+    #[test]
+    fn test_resting_pulse_trend_insufficient_data() {
+        // Four days with pulse, days without pulse and older days ignored
+        let mut trainings: Vec<_> = (0..4).map(|i| pulse_day(i, Some(60 + i as i32))).collect();
+        trainings.extend((4..8).map(|i| pulse_day(i, None)));
+        trainings.extend((20..25).map(|i| pulse_day(i, Some(60))));
+        let analytics = Analytics::new(trainings);
+        assert_eq!(analytics.resting_pulse_trend_at(pulse_trend_now(), progress_goal::moscow_tz()), None);
+        assert_eq!(Analytics::new(vec![]).resting_pulse_trend(), None);
+    }
+
+    #[test]
+    fn test_totals_all_and_by_exercise() {
+        let trainings = vec![
+            Training { duration_secs: Some(40), ..create_training("отжимания", 2, 10) },
+            Training { duration_secs: Some(30), ..create_training("Отжимания на кулаках", 1, 15) },
+            Training { duration_secs: None, ..create_training("приседания", 3, 20) },
+        ];
+        let analytics = Analytics::new(trainings);
+
+        assert_eq!(analytics.total_sets(), 6);
+        assert_eq!(analytics.total_reps(None), 20 + 15 + 60);
+        assert_eq!(analytics.total_duration_secs(None), 70);
+
+        assert_eq!(analytics.total_reps(Some("отжимания")), 35);
+        assert_eq!(analytics.total_duration_secs(Some("отжимания")), 70);
+        assert_eq!(analytics.total_reps(Some("приседания")), 60);
+        assert_eq!(analytics.total_duration_secs(Some("приседания")), 0);
+        assert_eq!(analytics.total_reps(Some("планка")), 0);
+    }
+
+    #[test]
+    fn test_totals_empty() {
+        let analytics = Analytics::new(vec![]);
+        assert_eq!(analytics.total_sets(), 0);
+        assert_eq!(analytics.total_reps(None), 0);
+        assert_eq!(analytics.total_duration_secs(None), 0);
+        assert_eq!(analytics.total_duration_secs(Some("отжимания")), 0);
+    }
+
     #[test]
     fn test_weekly_frequency_empty() {
         let analytics = Analytics::new(vec![]);
@@ -185,8 +679,32 @@ mod tests {
             create_training("приседания", 3, 20),
         ];
         let analytics = Analytics::new(trainings);
-        // Both on same day - should return count
-        assert_eq!(analytics.weekly_frequency(), 2.0);
+        // One training day says nothing about weekly frequency yet
+        assert_eq!(analytics.weekly_frequency(), 0.0);
+    }
+
+    #[test]
+    fn test_weekly_frequency_burst_in_one_hour() {
+        let base = Utc.with_ymd_and_hms(2026, 1, 5, 10, 0, 0).unwrap();
+        let trainings: Vec<_> = (0..30)
+            .map(|i| Training {
+                date: base + chrono::Duration::minutes(i * 2),
+                ..create_training("отжимания", 1, 10)
+            })
+            .collect();
+        let analytics = Analytics::new(trainings);
+        assert_eq!(analytics.weekly_frequency(), 0.0);
+    }
+
+    #[test]
+    fn test_weekly_frequency_adjacent_days() {
+        let trainings = vec![
+            create_training("отжимания", 3, 10),
+            create_training_days_ago("приседания", 3, 20, 1),
+        ];
+        let analytics = Analytics::new(trainings);
+        // 2 trainings over a 1-day span
+        assert!((analytics.weekly_frequency() - 14.0).abs() < 0.01);
     }
 
     #[test]
@@ -252,4 +770,340 @@ mod tests {
         assert!(prediction.is_some());
         assert_eq!(prediction.unwrap(), (2, 21));
     }
+
+    #[test]
+    fn test_period_stats() {
+        let trainings = vec![
+            create_training_days_ago("отжимания", 2, 10, 1),
+            create_training_days_ago("отжимания", 1, 12, 1),
+            create_training_days_ago("пловец", 1, 20, 2),
+            create_training_days_ago("пловец", 1, 20, 20), // outside the period
+        ];
+        let analytics = Analytics::new(trainings);
+        let now = Utc::now();
+        let stats = analytics.period_stats(now - chrono::Duration::days(7)..now, FixedOffset::east_opt(0).unwrap());
+
+        assert_eq!(stats.sessions, 2);
+        assert_eq!(stats.sets, 4);
+        assert_eq!(stats.volume, 2 * 10 + 12 + 20);
+        assert_eq!(stats.top_exercise.as_deref(), Some("отжимания"));
+    }
+
+    #[test]
+    fn test_compare_periods() {
+        let trainings = vec![
+            create_training_days_ago("пловец", 1, 20, 1),
+            create_training_days_ago("пловец", 1, 15, 8),
+            create_training_days_ago("пловец", 1, 15, 9),
+        ];
+        let analytics = Analytics::new(trainings);
+        let now = Utc::now();
+        let week = chrono::Duration::days(7);
+        let cmp = analytics.compare_periods(now - week..now, now - week * 2..now - week, FixedOffset::east_opt(0).unwrap());
+
+        assert_eq!(cmp.current.sessions, 1);
+        assert_eq!(cmp.previous.sessions, 2);
+        assert_eq!(cmp.current.volume, 20);
+        assert_eq!(cmp.previous.volume, 30);
+    }
+
+    #[test]
+    fn test_period_stats_empty() {
+        let analytics = Analytics::new(vec![]);
+        let now = Utc::now();
+        let stats = analytics.period_stats(now - chrono::Duration::days(7)..now, FixedOffset::east_opt(0).unwrap());
+        assert_eq!(stats, PeriodStats::default());
+    }
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 1, d).unwrap()
+    }
+
+    #[test]
+    fn test_streak_days_includes_today() {
+        let days: HashSet<_> = [day(3), day(4), day(5)].into();
+        assert_eq!(streak_days(&days, day(5)), 3);
+    }
+
+    #[test]
+    fn test_streak_days_alive_until_day_ends() {
+        let days: HashSet<_> = [day(3), day(4)].into();
+        assert_eq!(streak_days(&days, day(5)), 2);
+    }
+
+    #[test]
+    fn test_streak_days_broken_by_gap() {
+        let days: HashSet<_> = [day(1), day(3)].into();
+        assert_eq!(streak_days(&days, day(5)), 0);
+        assert_eq!(streak_days(&HashSet::new(), day(5)), 0);
+    }
+
+    #[test]
+    fn test_streak_rest_day_does_not_break() {
+        let trained: HashSet<_> = [day(2), day(3), day(5)].into();
+        let rest: HashSet<_> = [day(4)].into();
+        assert_eq!(streak_days(&trained, day(5)), 1);
+        assert_eq!(streak_days_with_rest(&trained, &rest, day(5)), 3);
+    }
+
+    #[test]
+    fn test_streak_rest_today_keeps_streak() {
+        let trained: HashSet<_> = [day(3), day(4)].into();
+        let rest: HashSet<_> = [day(5)].into();
+        assert_eq!(streak_days_with_rest(&trained, &rest, day(5)), 2);
+        assert_eq!(streak_days_with_rest(&trained, &rest, day(6)), 2);
+    }
+
+    fn pulse_pair(days_ago: i64, before: Option<i32>, after: Option<i32>) -> Training {
+        Training {
+            pulse_before: before,
+            pulse_after: after,
+            ..create_training_days_ago("пловец", 1, 10, days_ago)
+        }
+    }
+
+    #[test]
+    fn test_pulse_recovery_trend_improving() {
+        // Rise shrinks from 50 to 30 bpm
+        let trainings = vec![
+            pulse_pair(4, Some(70), Some(120)),
+            pulse_pair(3, Some(70), Some(120)),
+            pulse_pair(2, Some(70), Some(100)),
+            pulse_pair(1, Some(70), Some(100)),
+        ];
+        let trend = Analytics::new(trainings).pulse_recovery_trend("пловец").unwrap();
+        assert!((trend - (-20.0)).abs() < 1e-9, "trend = {}", trend);
+    }
+
+    #[test]
+    fn test_pulse_recovery_trend_ignores_missing_pulses() {
+        let mut trainings = vec![
+            pulse_pair(5, Some(70), Some(110)),
+            pulse_pair(4, None, Some(150)),
+            pulse_pair(3, Some(70), None),
+            pulse_pair(2, Some(70), Some(120)),
+            pulse_pair(1, Some(70), Some(130)),
+        ];
+        // Only 3 valid pairs
+        assert!(Analytics::new(trainings.clone()).pulse_recovery_trend("пловец").is_none());
+
+        trainings.push(pulse_pair(0, Some(70), Some(140)));
+        let trend = Analytics::new(trainings).pulse_recovery_trend("пловец").unwrap();
+        // (40 + 50) / 2 → (60 + 70) / 2
+        assert!((trend - 20.0).abs() < 1e-9, "trend = {}", trend);
+        assert!(Analytics::new(vec![]).pulse_recovery_trend("пловец").is_none());
+    }
+
+    #[test]
+    fn test_pulse_deltas() {
+        let trainings = vec![
+            pulse_pair(3, Some(70), Some(120)),
+            pulse_pair(2, None, Some(150)),
+            pulse_pair(1, Some(70), Some(110)),
+            pulse_pair(0, Some(75), Some(105)),
+        ];
+        let analytics = Analytics::new(trainings);
+        assert_eq!(analytics.pulse_deltas("пловец", 20), vec![50, 40, 30], "oldest first, incomplete pairs skipped");
+        assert_eq!(analytics.pulse_deltas("пловец", 2), vec![40, 30]);
+        assert!(analytics.pulse_deltas("отжимания", 20).is_empty());
+    }
+
+    #[test]
+    fn test_balance_score_by_week() {
+        let tz = progress_goal::moscow_tz();
+        let today = day(21); // Wednesday; weeks start Jan 5, 12, 19
+        assert!(Analytics::new(vec![]).balance_score_by_week_at(4, today, tz).is_empty());
+
+        let at = |d: u32, exercise: &str| Training {
+            exercise: exercise.to_string(),
+            ..training_on(d)
+        };
+        let trainings = vec![
+            // Week of Jan 5: pushups only
+            at(6, "отжимания на кулаках"),
+            at(7, "отжимания на кулаках"),
+            // Week of Jan 12: mixed
+            at(13, "отжимания на кулаках"),
+            at(13, "приседания с ударами"),
+            at(14, "пловец"),
+            at(15, "пресс складной нож"),
+            at(15, "подтягивание у полки"),
+        ];
+        let weeks = Analytics::new(trainings).balance_score_by_week_at(4, today, tz);
+        assert_eq!(weeks.len(), 2, "empty current week is skipped");
+        assert_eq!(weeks[0].0, day(5));
+        assert_eq!(weeks[1].0, day(12));
+        assert!(weeks[0].1 < weeks[1].1, "pushups only {} vs mixed {}", weeks[0].1, weeks[1].1);
+    }
+
+    #[test]
+    fn test_muscle_volume_by_week() {
+        let tz = progress_goal::moscow_tz();
+        let today = day(14); // Wednesday of the week of Jan 12
+        let at = |d: u32, exercise: &str, reps: i32| Training {
+            exercise: exercise.to_string(),
+            reps,
+            ..training_on(d)
+        };
+        let trainings = vec![
+            // Week of Jan 5: swimmer works the back
+            at(6, "пловец", 10),
+            at(7, "пловец", 12),
+            // Week of Jan 12: pushups only, no back
+            at(13, "отжимания на кулаках", 20),
+        ];
+        let analytics = Analytics::new(trainings);
+
+        let back = analytics.muscle_volume_by_week_at(MuscleGroup::Back, 3, today, tz);
+        let dec_29 = NaiveDate::from_ymd_opt(2025, 12, 29).unwrap();
+        assert_eq!(back, vec![(dec_29, 0), (day(5), 22), (day(12), 0)]);
+
+        let chest = analytics.muscle_volume_by_week_at(MuscleGroup::Chest, 2, today, tz);
+        assert_eq!(chest, vec![(day(5), 0), (day(12), 20)]);
+
+        let full_body = analytics.muscle_volume_by_week_at(MuscleGroup::FullBody, 2, today, tz);
+        assert!(full_body.iter().all(|(_, reps)| *reps == 0));
+        assert!(analytics.muscle_volume_by_week_at(MuscleGroup::Back, 0, today, tz).is_empty());
+    }
+
+    #[test]
+    fn test_sessions_this_week() {
+        let tz = progress_goal::moscow_tz();
+        // Two sets on Tuesday Jan 13 are one session; Jan 11 is last week's Sunday
+        let trainings = vec![training_on(11), training_on(13), training_on(13), training_on(14)];
+        let analytics = Analytics::new(trainings);
+        assert_eq!(analytics.sessions_this_week_at(day(14), tz), 2);
+        assert_eq!(analytics.sessions_this_week_at(day(12), tz), 0, "Monday: nothing yet");
+        assert_eq!(analytics.sessions_this_week_at(day(11), tz), 1, "Sunday closes the previous week");
+    }
+
+    #[test]
+    fn test_sessions_remaining() {
+        assert_eq!(sessions_remaining(4, 1), 3);
+        assert_eq!(sessions_remaining(4, 4), 0);
+        assert_eq!(sessions_remaining(3, 5), 0, "already met");
+    }
+
+    #[test]
+    fn test_personal_records() {
+        let timed = |secs: Option<i32>| Training {
+            duration_secs: secs,
+            ..create_training("стойка на локтях", 1, 1)
+        };
+        let analytics = Analytics::new(vec![
+            create_training("отжимания на кулаках", 1, 15),
+            create_training("отжимания на кулаках", 1, 22),
+            create_training("отжимания на кулаках", 1, 18),
+            timed(Some(45)),
+            timed(Some(90)),
+            timed(None),
+            create_training("неизвестное", 1, 100),
+        ]);
+        assert_eq!(
+            analytics.personal_records(),
+            vec![
+                ("отжимания на кулаках".to_string(), 22, false),
+                ("стойка на локтях".to_string(), 90, true),
+            ]
+        );
+        assert!(Analytics::new(vec![]).personal_records().is_empty());
+    }
+
+    /// Training at noon Moscow time on January `d`
+    fn training_on(d: u32) -> Training {
+        Training {
+            date: chrono::TimeZone::with_ymd_and_hms(&Utc, 2026, 1, d, 9, 0, 0).unwrap(),
+            ..create_training("пловец", 1, 10)
+        }
+    }
+
+    #[test]
+    fn test_streaks_no_trainings() {
+        let analytics = Analytics::new(vec![]);
+        assert_eq!(analytics.current_streak(), 0);
+        assert_eq!(analytics.longest_streak(), 0);
+    }
+
+    #[test]
+    fn test_streaks_single_day_counted_once() {
+        let analytics = Analytics::new(vec![training_on(5), training_on(5)]);
+        let tz = progress_goal::moscow_tz();
+        assert_eq!(analytics.current_streak_at(day(5), tz), 1);
+        assert_eq!(analytics.longest_streak(), 1);
+    }
+
+    #[test]
+    fn test_streaks_three_consecutive_days() {
+        let analytics = Analytics::new(vec![training_on(3), training_on(4), training_on(5)]);
+        let tz = progress_goal::moscow_tz();
+        assert_eq!(analytics.current_streak_at(day(5), tz), 3);
+        assert_eq!(analytics.current_streak_at(day(6), tz), 3, "today isn't over yet");
+        assert_eq!(analytics.current_streak_at(day(7), tz), 0);
+        assert_eq!(analytics.longest_streak(), 3);
+    }
+
+    #[test]
+    fn test_streaks_gap_resets_current() {
+        let trainings = [1, 2, 3, 5, 6].map(training_on).to_vec();
+        let analytics = Analytics::new(trainings);
+        let tz = progress_goal::moscow_tz();
+        assert_eq!(analytics.current_streak_at(day(6), tz), 2);
+        assert_eq!(analytics.longest_streak(), 3);
+    }
+
+    #[test]
+    fn test_acwr_steady_load() {
+        // 10 reps every day for 35 days: acute week = chronic weekly average
+        let trainings: Vec<_> = (0..35).map(|d| create_training_days_ago("пловец", 1, 10, d)).collect();
+        let acwr = Analytics::new(trainings).acwr().unwrap();
+        assert!((acwr - 1.0).abs() < 0.01, "acwr = {}", acwr);
+    }
+
+    #[test]
+    fn test_acwr_spike() {
+        // 10 reps daily for a month, then 30 reps daily this week
+        let trainings: Vec<_> = (0..35)
+            .map(|d| create_training_days_ago("пловец", 1, if d < 7 { 30 } else { 10 }, d))
+            .collect();
+        let acwr = Analytics::new(trainings).acwr().unwrap();
+        // acute 210, chronic (210 + 210) / 4 = 105
+        assert!((acwr - 2.0).abs() < 0.01, "acwr = {}", acwr);
+    }
+
+    #[test]
+    fn test_acwr_needs_history() {
+        let trainings: Vec<_> = (0..10).map(|d| create_training_days_ago("пловец", 1, 10, d)).collect();
+        assert_eq!(Analytics::new(trainings).acwr(), None);
+        assert_eq!(Analytics::new(vec![]).acwr(), None);
+    }
+
+    #[test]
+    fn test_resting_pulse_baseline_median() {
+        let with_pulse = |p: Option<i32>| Training { pulse_before: p, ..create_training("пловец", 1, 10) };
+
+        let few: Vec<_> = [70, 72, 74].iter().map(|p| with_pulse(Some(*p))).collect();
+        assert_eq!(Analytics::new(few).resting_pulse_baseline(), None);
+
+        let odd: Vec<_> = [90, 70, 72, 68, 71].iter().map(|p| with_pulse(Some(*p))).collect();
+        assert_eq!(Analytics::new(odd).resting_pulse_baseline(), Some(71.0));
+
+        let mut even: Vec<_> = [70, 72, 74, 76, 60, 80].iter().map(|p| with_pulse(Some(*p))).collect();
+        even.push(with_pulse(None));
+        assert_eq!(Analytics::new(even).resting_pulse_baseline(), Some(73.0));
+    }
+
+    #[test]
+    fn test_resting_pulse_elevated() {
+        assert!(!resting_pulse_elevated(80, 72.0));
+        assert!(resting_pulse_elevated(83, 72.0));
+    }
+
+    #[test]
+    fn test_training_days_dedup() {
+        let analytics = Analytics::new(vec![
+            create_training("пловец", 1, 10),
+            create_training("планка", 1, 10),
+        ]);
+        assert_eq!(analytics.training_days(FixedOffset::east_opt(0).unwrap()).len(), 1);
+    }
 }
@@ -1,9 +1,9 @@
 //! Muscle group load tracking for balanced training recommendations
 
 use std::collections::HashMap;
-use chrono::{DateTime, Local, Utc};
+use chrono::{DateTime, Local, NaiveDate, Utc};
 use crate::db::Training;
-use crate::exercises::{MuscleGroup, find_exercise_by_name};
+use crate::exercises::{BodyRegion, MuscleGroup};
 
 /// Load statistics for a single muscle group
 #[derive(Debug, Clone)]
@@ -22,6 +22,11 @@ pub struct MuscleTracker {
 impl MuscleTracker {
     /// Build tracker from training history
     pub fn from_trainings(trainings: &[Training]) -> Self {
+        Self::from_trainings_at(trainings, Local::now().date_naive())
+    }
+
+    /// Build tracker as of local date `today` (for past weeks)
+    pub fn from_trainings_at(trainings: &[Training], today: NaiveDate) -> Self {
         let mut loads: HashMap<MuscleGroup, MuscleLoad> = HashMap::new();
 
         // Initialize all muscle groups
@@ -34,23 +39,18 @@ impl MuscleTracker {
             });
         }
 
-        let now = Local::now();
-        let today = now.date_naive();
         let week_ago = today - chrono::Duration::days(7);
 
         for training in trainings {
-            // Find exercise definition to get muscle groups
-            let exercise = match find_exercise_by_name(&training.exercise) {
-                Some(ex) => ex,
-                None => continue, // Unknown exercise, skip
-            };
+            // Catalog muscle groups, or the ones of the stored category
+            let muscle_groups = training.muscle_groups();
 
             let training_date = training.date.with_timezone(&Local).date_naive();
             let is_today = training_date == today;
             let is_this_week = training_date >= week_ago;
 
             // Distribute reps to each muscle group the exercise targets
-            for muscle_group in exercise.muscle_groups {
+            for muscle_group in muscle_groups {
                 if let Some(load) = loads.get_mut(muscle_group) {
                     if is_today {
                         load.today_volume += training.reps;
@@ -94,33 +94,13 @@ impl MuscleTracker {
     }
 
     /// Calculate balance score (0-100%)
-    /// 100% = perfectly balanced across all groups
+    /// 100% = weekly volume matches `VOLUME_WEIGHTS` proportions
     pub fn get_balance_score(&self) -> f32 {
-        let volumes: Vec<i32> = self.loads.values()
+        let volumes: Vec<(MuscleGroup, i32)> = self.loads.values()
             .filter(|l| l.group != MuscleGroup::FullBody)
-            .map(|l| l.week_volume)
+            .map(|l| (l.group, l.week_volume))
             .collect();
-
-        if volumes.is_empty() {
-            return 0.0;
-        }
-
-        let total: i32 = volumes.iter().sum();
-        if total == 0 {
-            return 0.0;
-        }
-
-        let target = total as f32 / volumes.len() as f32;
-        let variance: f32 = volumes.iter()
-            .map(|v| (*v as f32 - target).powi(2))
-            .sum::<f32>() / volumes.len() as f32;
-
-        let std_dev = variance.sqrt();
-        let cv = if target > 0.0 { std_dev / target } else { 0.0 };
-
-        // Convert coefficient of variation to score (lower CV = higher score)
-        // CV of 0 = 100%, CV of 1+ = ~0%
-        ((1.0 - cv.min(1.0)) * 100.0).max(0.0)
+        weighted_balance(&volumes)
     }
 
     /// Get weekly report for /balance command
@@ -147,7 +127,7 @@ impl MuscleTracker {
             })
             .collect();
 
-        report.sort_by(|a, b| b.1.cmp(&a.1)); // Sort by volume descending
+        report.sort_by_key(|r| std::cmp::Reverse(r.1)); // Sort by volume descending
         report
     }
 
@@ -162,24 +142,108 @@ impl MuscleTracker {
         let mut report: Vec<_> = self.loads.values()
             .filter(|l| l.today_volume > 0) // Only show groups with activity today
             .map(|load| {
-                let ratio = load.today_volume as f32 / max_volume as f32;
-                let bar = match ratio {
-                    r if r >= 0.75 => "[++++]",
-                    r if r >= 0.50 => "[+++.]",
-                    r if r >= 0.25 => "[++..]",
-                    _ => "[+...]",
-                };
+                let bar = load_bar(load.today_volume, max_volume);
                 (load.group.name_ru(), load.today_volume, bar)
             })
             .collect();
 
-        report.sort_by(|a, b| b.1.cmp(&a.1)); // Sort by volume descending
+        report.sort_by_key(|r| std::cmp::Reverse(r.1)); // Sort by volume descending
 
         report.iter()
             .map(|(name, vol, bar)| format!("{} {}: {}", bar, name, vol))
             .collect::<Vec<_>>()
             .join("\n")
     }
+
+    /// Today's volume summed per body region, head to toe
+    pub fn today_region_volumes(&self) -> Vec<(BodyRegion, i32)> {
+        BodyRegion::all()
+            .iter()
+            .map(|region| {
+                let volume = self.loads.values()
+                    .filter(|l| l.group.region() == Some(*region))
+                    .map(|l| l.today_volume)
+                    .sum();
+                (*region, volume)
+            })
+            .collect()
+    }
+
+    /// Glanceable body-region map of today's load (for /body)
+    pub fn get_body_map(&self) -> String {
+        let regions = self.today_region_volumes();
+        let max_volume = regions.iter().map(|(_, v)| *v).max().unwrap_or(0).max(1);
+
+        let mut lines: Vec<String> = regions.iter()
+            .map(|(region, volume)| {
+                let bar = if *volume > 0 { load_bar(*volume, max_volume) } else { "[....]" };
+                format!("{} {} {}: {}", region.emoji(), bar, region.name_ru(), volume)
+            })
+            .collect();
+
+        let full_body = self.get_load(&MuscleGroup::FullBody).map(|l| l.today_volume).unwrap_or(0);
+        if full_body > 0 {
+            lines.push(format!("🌀 {}: {}", MuscleGroup::FullBody.name_ru(), full_body));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Relative weekly volume each muscle group needs: big groups (legs, back)
+/// tolerate and need more work than small ones (biceps, triceps)
+pub const VOLUME_WEIGHTS: &[(MuscleGroup, f32)] = &[
+    (MuscleGroup::Chest, 1.0),
+    (MuscleGroup::Shoulders, 0.8),
+    (MuscleGroup::Triceps, 0.6),
+    (MuscleGroup::Back, 1.2),
+    (MuscleGroup::Biceps, 0.6),
+    (MuscleGroup::Core, 1.0),
+    (MuscleGroup::Glutes, 1.2),
+    (MuscleGroup::Quads, 1.2),
+    (MuscleGroup::Hamstrings, 1.0),
+    (MuscleGroup::Calves, 0.8),
+];
+
+/// Target weight of a muscle group (1.0 if not listed)
+pub fn volume_weight(group: MuscleGroup) -> f32 {
+    VOLUME_WEIGHTS.iter()
+        .find(|(g, _)| *g == group)
+        .map(|(_, w)| *w)
+        .unwrap_or(1.0)
+}
+
+/// Balance score (0-100%) of volumes against weighted targets.
+/// Each group's target is its weight's share of the total volume;
+/// the score falls with the weighted coefficient of variation of volume/target.
+fn weighted_balance(volumes: &[(MuscleGroup, i32)]) -> f32 {
+    let total: i32 = volumes.iter().map(|(_, v)| *v).sum();
+    let weight_sum: f32 = volumes.iter().map(|(g, _)| volume_weight(*g)).sum();
+    if total == 0 || weight_sum <= 0.0 {
+        return 0.0;
+    }
+
+    // ratio = volume / target; its weighted mean is exactly 1
+    let variance: f32 = volumes.iter()
+        .map(|(group, volume)| {
+            let weight = volume_weight(*group);
+            let target = total as f32 * weight / weight_sum;
+            weight * (*volume as f32 / target - 1.0).powi(2)
+        })
+        .sum::<f32>() / weight_sum;
+
+    // CV of 0 = 100%, CV of 1+ = 0%
+    let cv = variance.sqrt();
+    ((1.0 - cv.min(1.0)) * 100.0).max(0.0)
+}
+
+/// Four-step bar for a volume relative to the day's maximum
+fn load_bar(volume: i32, max_volume: i32) -> &'static str {
+    match volume as f32 / max_volume.max(1) as f32 {
+        r if r >= 0.75 => "[++++]",
+        r if r >= 0.50 => "[+++.]",
+        r if r >= 0.25 => "[++..]",
+        _ => "[+...]",
+    }
 }
 
 #[cfg(test)]
@@ -197,8 +261,12 @@ mod tests {
             duration_secs: None,
             pulse_before: None,
             pulse_after: None,
+            rpe: None,
             notes: None,
             user_id: None,
+            context: None,
+            category: None,
+            is_timed: None,
         }
     }
 
@@ -212,8 +280,12 @@ mod tests {
             duration_secs: None,
             pulse_before: None,
             pulse_after: None,
+            rpe: None,
             notes: None,
             user_id: None,
+            context: None,
+            category: None,
+            is_timed: None,
         }
     }
 
@@ -236,6 +308,25 @@ mod tests {
         assert!(chest.last_trained.is_some());
     }
 
+    #[test]
+    fn test_body_map_regions() {
+        let trainings = vec![create_training("отжимания на кулаках", 15)];
+        let tracker = MuscleTracker::from_trainings(&trainings);
+
+        let regions = tracker.today_region_volumes();
+        assert_eq!(regions, vec![
+            (BodyRegion::UpperPush, 45),
+            (BodyRegion::UpperPull, 0),
+            (BodyRegion::Core, 15),
+            (BodyRegion::Legs, 0),
+        ]);
+
+        let map = tracker.get_body_map();
+        assert!(map.contains("💪 [++++] верх, жим: 45"));
+        assert!(map.contains("🦵 [....] ноги: 0"));
+        assert!(!map.contains("всё тело"));
+    }
+
     #[test]
     fn test_multi_muscle_exercise() {
         let trainings = vec![create_training("отжимания на кулаках", 15)];
@@ -292,6 +383,34 @@ mod tests {
         assert_eq!(tracker.get_balance_score(), 0.0);
     }
 
+    #[test]
+    fn test_weighted_balance_perfect_at_weights() {
+        // Volume proportional to the weights is perfect balance
+        let volumes: Vec<_> = VOLUME_WEIGHTS.iter()
+            .map(|(g, w)| (*g, (w * 100.0) as i32))
+            .collect();
+        assert!((weighted_balance(&volumes) - 100.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_weighted_balance_equal_volume_not_perfect() {
+        // Same raw volume everywhere overworks small groups
+        let volumes: Vec<_> = VOLUME_WEIGHTS.iter().map(|(g, _)| (*g, 50)).collect();
+        let score = weighted_balance(&volumes);
+        assert!(score < 100.0 && score > 50.0, "score = {}", score);
+    }
+
+    #[test]
+    fn test_weighted_balance_prefers_big_groups() {
+        // Extra volume on legs is closer to target than the same extra on biceps
+        let base = |extra: MuscleGroup| -> Vec<(MuscleGroup, i32)> {
+            VOLUME_WEIGHTS.iter()
+                .map(|(g, w)| (*g, (w * 100.0) as i32 + if *g == extra { 30 } else { 0 }))
+                .collect()
+        };
+        assert!(weighted_balance(&base(MuscleGroup::Quads)) > weighted_balance(&base(MuscleGroup::Biceps)));
+    }
+
     #[test]
     fn test_balance_score_increases_with_variety() {
         // Only pushups - imbalanced
@@ -299,10 +418,16 @@ mod tests {
         let tracker1 = MuscleTracker::from_trainings(&pushup_only);
         let score1 = tracker1.get_balance_score();
 
-        // Add some leg work - more balanced
+        // Push, pull, legs and calves - closer to the weighted targets.
+        // (Pushups plus a bit of squats alone still scores 0: shoulders and
+        // core are far over target while back and hamstrings get nothing.)
         let mixed = vec![
-            create_training("отжимания на кулаках", 50),
+            create_training("отжимания на кулаках", 30),
             create_training("приседания с ударами", 30),
+            create_training("выпады в сторону", 40),
+            create_training("румынская тяга на одной ноге", 30),
+            create_training("впусти меня", 40),
+            create_training("подъём на носки", 40),
         ];
         let tracker2 = MuscleTracker::from_trainings(&mixed);
         let score2 = tracker2.get_balance_score();
@@ -1,18 +1,34 @@
-//! Progress prediction using linear regression (linfa)
+//! Progress prediction using linear or quadratic regression (linfa)
 
 use chrono::{DateTime, Utc};
 use linfa::prelude::*;
 use linfa_linear::LinearRegression;
 use ndarray::{Array1, Array2};
+use serde::Serialize;
 
 use crate::db::Training;
 
 /// Minimum data points required for training
-const MIN_DATA_POINTS: usize = 3;
+pub const MIN_DATA_POINTS: usize = 3;
 
-/// Progress predictor using linear regression
+/// Daily progress below this (reps/day, either way) counts as stalled
+const PLATEAU_EPSILON: f64 = 0.02;
+
+/// Minimum sessions before a plateau is called
+const PLATEAU_MIN_POINTS: usize = 5;
+
+/// Minimum history span (days) before a plateau is called
+const PLATEAU_MIN_SPAN_DAYS: i64 = 14;
+
+/// Highest polynomial degree supported by `train_poly`
+const MAX_DEGREE: usize = 2;
+
+/// Progress predictor using linear (or quadratic) regression over days
 pub struct ProgressPredictor {
     slope: f64,
+    /// Coefficient of days², 0 for the linear model
+    quadratic: f64,
+    degree: usize,
     intercept: f64,
     r2_score: f64,
     data_points: usize,
@@ -22,7 +38,7 @@ pub struct ProgressPredictor {
 }
 
 /// Prediction result for display
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct Prediction {
     pub daily_progress: f64,
     pub week_prediction: f64,
@@ -40,13 +56,23 @@ pub struct Prediction {
 impl ProgressPredictor {
     /// Train a predictor from training history for a specific exercise
     pub fn train(trainings: &[Training], exercise: &str) -> Option<Self> {
+        Self::train_poly(trainings, exercise, 1)
+    }
+
+    /// Like `train`, but fits a polynomial of `degree` (1 or 2) in days,
+    /// which follows plateaus and jumps better than a straight line
+    pub fn train_poly(trainings: &[Training], exercise: &str, degree: usize) -> Option<Self> {
+        if !(1..=MAX_DEGREE).contains(&degree) {
+            return None;
+        }
+
         // Filter trainings for this exercise
         let exercise_trainings: Vec<_> = trainings
             .iter()
             .filter(|t| t.exercise == exercise)
             .collect();
 
-        if exercise_trainings.len() < MIN_DATA_POINTS {
+        if exercise_trainings.len() < MIN_DATA_POINTS.max(degree + 2) {
             return None;
         }
 
@@ -56,21 +82,23 @@ impl ProgressPredictor {
             .map(|t| t.date)
             .min()?;
 
-        // Prepare data: X = days since first training, Y = reps
+        // Prepare data: X = days since first training (and its powers), Y = reps
         let mut x_data: Vec<f64> = Vec::new();
         let mut y_data: Vec<f64> = Vec::new();
 
         for training in &exercise_trainings {
             let days_offset = (training.date - first_date).num_days() as f64;
-            x_data.push(days_offset);
+            for power in 1..=degree {
+                x_data.push(days_offset.powi(power as i32));
+            }
             y_data.push(training.reps as f64);
         }
 
-        let n_samples = x_data.len();
+        let n_samples = y_data.len();
 
         // Create ndarray structures
         let records = Array2::from_shape_vec(
-            (n_samples, 1),
+            (n_samples, degree),
             x_data,
         ).ok()?;
 
@@ -87,7 +115,11 @@ impl ProgressPredictor {
         // Get model parameters
         let params = model.params();
         let slope = params[0];
+        let quadratic = if degree == 2 { params[1] } else { 0.0 };
         let intercept = model.intercept();
+        if !slope.is_finite() || !quadratic.is_finite() {
+            return None;
+        }
 
         // Calculate R2 score
         let predictions = model.predict(&dataset);
@@ -101,6 +133,8 @@ impl ProgressPredictor {
 
         Some(Self {
             slope,
+            quadratic,
+            degree,
             intercept,
             r2_score,
             data_points: n_samples,
@@ -109,12 +143,20 @@ impl ProgressPredictor {
         })
     }
 
+    /// Train linear and quadratic models and keep the one with the higher R²
+    pub fn best_fit(trainings: &[Training], exercise: &str) -> Option<Self> {
+        let linear = Self::train(trainings, exercise)?;
+        match Self::train_poly(trainings, exercise, 2) {
+            Some(poly) if poly.r2_score > linear.r2_score => Some(poly),
+            _ => Some(linear),
+        }
+    }
+
     /// Predict reps for a given number of days ahead from now
     pub fn predict_reps(&self, days_ahead: i32) -> f64 {
         let now = Utc::now();
         let days_from_start = (now - self.first_date).num_days() as f64;
-        let future_day = days_from_start + days_ahead as f64;
-        self.slope * future_day + self.intercept
+        self.trend_at(days_from_start + days_ahead as f64)
     }
 
     /// Get current predicted level (reps today)
@@ -122,9 +164,65 @@ impl ProgressPredictor {
         self.predict_reps(0)
     }
 
-    /// Get daily progress (slope)
+    /// Days from now until the trend reaches `target` reps at today's rate.
+    /// None if the trend isn't rising or the target is already met.
+    pub fn days_to_reach(&self, target: f64) -> Option<i32> {
+        let rate = self.daily_progress();
+        if rate <= 0.0 {
+            return None;
+        }
+        let current = self.current_level();
+        if current >= target {
+            return None;
+        }
+        Some(((target - current) / rate).ceil() as i32)
+    }
+
+    /// Whole days from now until the trend reaches `target_reps`; 0 if the
+    /// trend is already there. None if the trend is flat or declining.
+    pub fn days_until(&self, target_reps: i32) -> Option<i32> {
+        if self.daily_progress() <= 0.0 {
+            return None;
+        }
+        Some(self.days_to_reach(target_reps as f64).unwrap_or(0))
+    }
+
+    /// Get daily progress: the trend's slope today
     pub fn daily_progress(&self) -> f64 {
-        self.slope
+        let days_from_start = (Utc::now() - self.first_date).num_days() as f64;
+        self.slope + 2.0 * self.quadratic * days_from_start
+    }
+
+    /// Whether progress has stalled: a flat trend over at least
+    /// `PLATEAU_MIN_POINTS` sessions spanning `PLATEAU_MIN_SPAN_DAYS`+
+    pub fn detect_plateau(&self) -> bool {
+        let dates = self.exercise_trainings.iter().map(|(d, _)| *d);
+        let (Some(first), Some(last)) = (dates.clone().min(), dates.max()) else {
+            return false;
+        };
+        self.data_points >= PLATEAU_MIN_POINTS
+            && (last - first).num_days() >= PLATEAU_MIN_SPAN_DAYS
+            && self.daily_progress().abs() < PLATEAU_EPSILON
+    }
+
+    /// Trend value `day` days after the first training
+    pub fn trend_at(&self, day: f64) -> f64 {
+        self.quadratic * day * day + self.slope * day + self.intercept
+    }
+
+    /// Training points as (days since first training, reps), oldest first
+    pub fn points(&self) -> Vec<(f64, i32)> {
+        let mut points: Vec<_> = self.exercise_trainings
+            .iter()
+            .map(|(date, reps)| ((*date - self.first_date).num_days() as f64, *reps))
+            .collect();
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        points
+    }
+
+    /// Polynomial degree of the fitted model (1 = linear)
+    pub fn degree(&self) -> usize {
+        self.degree
     }
 
     /// Get R2 score (model fit quality, 0-1)
@@ -175,7 +273,7 @@ impl ProgressPredictor {
     /// Get full prediction for display
     pub fn get_prediction(&self) -> Prediction {
         Prediction {
-            daily_progress: self.slope,
+            daily_progress: self.daily_progress(),
             week_prediction: self.predict_reps(7),
             month_prediction: self.predict_reps(30),
             r2_score: self.r2_score,
@@ -231,8 +329,12 @@ mod tests {
             duration_secs: None,
             pulse_before: None,
             pulse_after: None,
+            rpe: None,
             notes: None,
             user_id: None,
+            context: None,
+            category: None,
+            is_timed: None,
         }
     }
 
@@ -338,6 +440,117 @@ mod tests {
         assert!(formatted.contains("Частота:"), "Format: {}", formatted);
     }
 
+    #[test]
+    fn test_days_to_reach() {
+        // +1 rep per day, today's level = 20
+        let trainings = vec![
+            create_training("пловец", 10, 10),
+            create_training("пловец", 15, 5),
+            create_training("пловец", 20, 0),
+        ];
+        let predictor = ProgressPredictor::train(&trainings, "пловец").unwrap();
+
+        assert_eq!(predictor.days_to_reach(30.0), Some(10));
+        assert_eq!(predictor.days_to_reach(20.5), Some(1));
+        assert_eq!(predictor.days_to_reach(15.0), None, "already met");
+    }
+
+    #[test]
+    fn test_days_until() {
+        // +1 rep per day, today's level = 20
+        let trainings = vec![
+            create_training("пловец", 10, 10),
+            create_training("пловец", 15, 5),
+            create_training("пловец", 20, 0),
+        ];
+        let predictor = ProgressPredictor::train(&trainings, "пловец").unwrap();
+        assert_eq!(predictor.days_until(50), Some(30));
+        assert_eq!(predictor.days_until(20), Some(0), "already achieved");
+        assert_eq!(predictor.days_until(12), Some(0), "already exceeded");
+    }
+
+    #[test]
+    fn test_days_until_flat_or_declining() {
+        let flat: Vec<_> = [10, 5, 0].iter().map(|d| create_training("пловец", 20, *d)).collect();
+        assert_eq!(ProgressPredictor::train(&flat, "пловец").unwrap().days_until(30), None);
+
+        let declining = vec![
+            create_training("пловец", 20, 10),
+            create_training("пловец", 15, 5),
+            create_training("пловец", 10, 0),
+        ];
+        assert_eq!(ProgressPredictor::train(&declining, "пловец").unwrap().days_until(5), None);
+    }
+
+    #[test]
+    fn test_days_to_reach_flat_trend() {
+        let trainings = vec![
+            create_training("пловец", 20, 10),
+            create_training("пловец", 20, 5),
+            create_training("пловец", 20, 0),
+        ];
+        let predictor = ProgressPredictor::train(&trainings, "пловец").unwrap();
+        assert_eq!(predictor.days_to_reach(30.0), None);
+    }
+
+    #[test]
+    fn test_poly_beats_linear_on_quadratic_data() {
+        // reps = 10 + 0.1 * day², accelerating over four weeks
+        let trainings: Vec<_> = (0..=28)
+            .step_by(2)
+            .map(|day| create_training("pushups", 10 + day * day / 10, 28 - day as i64))
+            .collect();
+
+        let linear = ProgressPredictor::train(&trainings, "pushups").unwrap();
+        let poly = ProgressPredictor::train_poly(&trainings, "pushups", 2).unwrap();
+        assert_eq!(poly.degree(), 2);
+        assert!(poly.r2_score() > linear.r2_score(), "poly {} vs linear {}", poly.r2_score(), linear.r2_score());
+        assert!(poly.r2_score() > 0.99);
+
+        // Rate today follows the curve (~0.2 * 28), not the average slope
+        assert!(poly.daily_progress() > linear.daily_progress());
+
+        let best = ProgressPredictor::best_fit(&trainings, "pushups").unwrap();
+        assert_eq!(best.degree(), 2);
+    }
+
+    #[test]
+    fn test_best_fit_keeps_linear_for_linear_data() {
+        let trainings = vec![
+            create_training("pushups", 10, 21),
+            create_training("pushups", 11, 14),
+            create_training("pushups", 12, 7),
+            create_training("pushups", 13, 0),
+        ];
+        let best = ProgressPredictor::best_fit(&trainings, "pushups").unwrap();
+        assert!(best.r2_score() > 0.99);
+        assert!(ProgressPredictor::train_poly(&trainings, "pushups", 3).is_none());
+    }
+
+    #[test]
+    fn test_detect_plateau_flat() {
+        let trainings: Vec<_> = [21, 16, 11, 6, 0]
+            .iter()
+            .map(|days_ago| create_training("pushups", 20, *days_ago))
+            .collect();
+        let predictor = ProgressPredictor::train(&trainings, "pushups").unwrap();
+        assert!(predictor.detect_plateau());
+    }
+
+    #[test]
+    fn test_detect_plateau_rising_or_short() {
+        let rising: Vec<_> = [21, 16, 11, 6, 0]
+            .iter()
+            .enumerate()
+            .map(|(i, days_ago)| create_training("pushups", 10 + 3 * i as i32, *days_ago))
+            .collect();
+        assert!(!ProgressPredictor::train(&rising, "pushups").unwrap().detect_plateau());
+
+        // Flat, but only a week of history
+        let short: Vec<_> = (0..5).map(|d| create_training("pushups", 20, d)).collect();
+        assert!(!ProgressPredictor::train(&short, "pushups").unwrap().detect_plateau());
+    }
+
     #[test]
     fn test_negative_trend() {
         // Decreasing performance
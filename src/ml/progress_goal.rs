@@ -5,16 +5,17 @@
 
 use std::collections::HashMap;
 use chrono::{DateTime, FixedOffset, Utc};
+use serde::Serialize;
 
-use crate::db::Training;
+use crate::db::{user_tz, Training, DEFAULT_TZ_OFFSET_MINUTES};
 use crate::exercises::{find_exercise_by_name, MuscleGroup};
 
 /// Days to consolidate a new record before challenging to beat it
 const RECORD_CONSOLIDATION_DAYS: i64 = 7;
 
-/// Moscow timezone offset (UTC+3)
-fn moscow_tz() -> FixedOffset {
-    FixedOffset::east_opt(3 * 3600).unwrap()
+/// Moscow timezone offset (UTC+3), the default user zone
+pub(crate) fn moscow_tz() -> FixedOffset {
+    user_tz(DEFAULT_TZ_OFFSET_MINUTES)
 }
 
 /// Session context representing fatigue state
@@ -39,7 +40,7 @@ pub struct HistoricalSession {
 }
 
 /// Confidence level for goal prediction
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
 pub enum GoalConfidence {
     /// < 3 similar sessions
     Low,
@@ -60,7 +61,7 @@ impl GoalConfidence {
 }
 
 /// Progress goal with fatigue adjustment
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct ProgressGoal {
     /// Target value (reps or seconds for timed exercises) - fatigue-adjusted
     pub target_value: i32,
@@ -253,15 +254,25 @@ impl ProgressGoal {
     }
 }
 
+/// Tuning of the fatigue model
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FatigueParams {
+    /// Fatigue sensitivity: `k` reps = ~63% fatigue contribution (higher = fatigues slower)
+    pub k: f32,
+    /// Minimum similarity for a past session to count as comparable
+    pub similarity_threshold: f32,
+}
+
+impl Default for FatigueParams {
+    fn default() -> Self {
+        Self { k: 50.0, similarity_threshold: 0.5 }
+    }
+}
+
 /// Goal calculator with session context matching
 pub struct GoalCalculator;
 
 impl GoalCalculator {
-    /// Minimum similarity threshold for matching sessions
-    const MIN_SIMILARITY: f32 = 0.5;
-
-    /// Fatigue sensitivity: 50 reps = ~63% fatigue contribution
-    const FATIGUE_K: f32 = 50.0;
 
     /// Find personal best value and the date when it was achieved
     fn find_personal_best_with_date(
@@ -323,32 +334,69 @@ impl GoalCalculator {
             })
     }
 
-    /// Calculate fatigue-aware goal for an exercise
+    /// Calculate fatigue-aware goal for an exercise (Moscow day boundaries)
     pub fn calculate(
         trainings: &[Training],
         exercise_name: &str,
     ) -> Option<ProgressGoal> {
-        let exercise = find_exercise_by_name(exercise_name)?;
-        let is_timed = exercise.is_timed;
+        Self::calculate_in(trainings, exercise_name, moscow_tz())
+    }
+
+    /// Calculate fatigue-aware goal with "today" taken in the user's timezone
+    pub fn calculate_in(
+        trainings: &[Training],
+        exercise_name: &str,
+        tz: FixedOffset,
+    ) -> Option<ProgressGoal> {
+        Self::calculate_in_with(trainings, exercise_name, tz, FatigueParams::default())
+    }
+
+    /// Calculate fatigue-aware goal with a tuned fatigue model (Moscow day boundaries)
+    pub fn calculate_with(
+        trainings: &[Training],
+        exercise_name: &str,
+        params: FatigueParams,
+    ) -> Option<ProgressGoal> {
+        Self::calculate_in_with(trainings, exercise_name, moscow_tz(), params)
+    }
+
+    /// Calculate fatigue-aware goal in the user's timezone with a tuned fatigue model
+    pub fn calculate_in_with(
+        trainings: &[Training],
+        exercise_name: &str,
+        tz: FixedOffset,
+        params: FatigueParams,
+    ) -> Option<ProgressGoal> {
+        // Catalog entry, or the snapshot stored on the latest logged set
+        let (is_timed, muscle_groups) = match find_exercise_by_name(exercise_name) {
+            Some(ex) => (ex.is_timed, ex.muscle_groups),
+            None => {
+                let latest = trainings
+                    .iter()
+                    .filter(|t| t.exercise == exercise_name)
+                    .max_by_key(|t| t.date)?;
+                (latest.exercise_is_timed()?, latest.muscle_groups())
+            }
+        };
 
         // Build current session context
-        let current_context = Self::build_current_context(trainings);
+        let current_context = Self::build_current_context(trainings, tz);
 
         // Calculate fatigue factor
-        let fatigue_factor = Self::fatigue_factor(&current_context, exercise.muscle_groups);
+        let fatigue_factor = Self::fatigue_factor(&current_context, muscle_groups, params.k);
 
         // Find fatigued muscles
-        let fatigued_muscles: Vec<MuscleGroup> = exercise.muscle_groups
+        let fatigued_muscles: Vec<MuscleGroup> = muscle_groups
             .iter()
             .filter(|m| current_context.prior_load.get(*m).copied().unwrap_or(0) > 0)
             .copied()
             .collect();
 
         // Get today's stats for this exercise
-        let today = Utc::now().with_timezone(&moscow_tz()).date_naive();
+        let today = Utc::now().with_timezone(&tz).date_naive();
         let today_exercises: Vec<_> = trainings
             .iter()
-            .filter(|t| t.date.with_timezone(&moscow_tz()).date_naive() == today)
+            .filter(|t| t.date.with_timezone(&tz).date_naive() == today)
             .filter(|t| t.exercise == exercise_name)
             .collect();
         let today_sets = today_exercises.len();
@@ -408,7 +456,9 @@ impl GoalCalculator {
         };
 
         // Find similar historical sessions for fatigue-adjusted target
-        let similar = Self::find_similar_sessions(trainings, exercise_name, &current_context, is_timed);
+        let similar = Self::find_similar_sessions(
+            trainings, exercise_name, &current_context, is_timed, tz, params.similarity_threshold,
+        );
 
         // Calculate fatigue-adjusted target value
         let target_value = if similar.is_empty() {
@@ -488,12 +538,12 @@ impl GoalCalculator {
     }
 
     /// Build session context from today's trainings
-    fn build_current_context(trainings: &[Training]) -> SessionContext {
-        let today = Utc::now().with_timezone(&moscow_tz()).date_naive();
+    fn build_current_context(trainings: &[Training], tz: FixedOffset) -> SessionContext {
+        let today = Utc::now().with_timezone(&tz).date_naive();
 
         let today_trainings: Vec<_> = trainings
             .iter()
-            .filter(|t| t.date.with_timezone(&moscow_tz()).date_naive() == today)
+            .filter(|t| t.date.with_timezone(&tz).date_naive() == today)
             .collect();
 
         if today_trainings.is_empty() {
@@ -505,10 +555,8 @@ impl GoalCalculator {
         let mut total_duration = 0;
 
         for t in &today_trainings {
-            if let Some(ex) = find_exercise_by_name(&t.exercise) {
-                for muscle in ex.muscle_groups {
-                    *prior_load.entry(*muscle).or_insert(0) += t.reps;
-                }
+            for muscle in t.muscle_groups() {
+                *prior_load.entry(*muscle).or_insert(0) += t.reps;
             }
             total_duration += t.duration_secs.unwrap_or(0);
         }
@@ -521,7 +569,7 @@ impl GoalCalculator {
     }
 
     /// Calculate fatigue factor for target muscle groups
-    fn fatigue_factor(context: &SessionContext, muscles: &[MuscleGroup]) -> f32 {
+    fn fatigue_factor(context: &SessionContext, muscles: &[MuscleGroup], k: f32) -> f32 {
         if muscles.is_empty() {
             return 0.0;
         }
@@ -530,7 +578,7 @@ impl GoalCalculator {
         for muscle in muscles {
             let load = context.prior_load.get(muscle).copied().unwrap_or(0);
             // Exponential saturation: fatigue = 1 - e^(-load/k)
-            let fatigue = 1.0 - (-load as f32 / Self::FATIGUE_K).exp();
+            let fatigue = 1.0 - (-load as f32 / k).exp();
             total += fatigue;
         }
 
@@ -543,12 +591,14 @@ impl GoalCalculator {
         exercise_name: &str,
         current_context: &SessionContext,
         is_timed: bool,
+        tz: FixedOffset,
+        min_similarity: f32,
     ) -> Vec<(HistoricalSession, f32)> {
         // Group trainings by day
-        let sessions_by_day = Self::group_by_day(trainings);
+        let sessions_by_day = Self::group_by_day(trainings, tz);
 
         let mut similar = Vec::new();
-        let today = Utc::now().with_timezone(&moscow_tz()).date_naive();
+        let today = Utc::now().with_timezone(&tz).date_naive();
 
         for (date, day_trainings) in sessions_by_day {
             // Skip today
@@ -576,7 +626,7 @@ impl GoalCalculator {
                 if training.exercise == exercise_name {
                     let similarity = Self::compute_similarity(&context_before, current_context);
 
-                    if similarity >= Self::MIN_SIMILARITY {
+                    if similarity >= min_similarity {
                         // Use duration_secs for timed exercises, reps otherwise
                         let achieved_value = if is_timed {
                             training.duration_secs.unwrap_or(0)
@@ -596,10 +646,8 @@ impl GoalCalculator {
                 }
 
                 // Update accumulated load
-                if let Some(ex) = find_exercise_by_name(&training.exercise) {
-                    for muscle in ex.muscle_groups {
-                        *accumulated_load.entry(*muscle).or_insert(0) += training.reps;
-                    }
+                for muscle in training.muscle_groups() {
+                    *accumulated_load.entry(*muscle).or_insert(0) += training.reps;
                 }
                 session_duration += training.duration_secs.unwrap_or(0);
             }
@@ -609,11 +657,11 @@ impl GoalCalculator {
     }
 
     /// Group trainings by day
-    fn group_by_day(trainings: &[Training]) -> HashMap<chrono::NaiveDate, Vec<&Training>> {
+    fn group_by_day(trainings: &[Training], tz: FixedOffset) -> HashMap<chrono::NaiveDate, Vec<&Training>> {
         let mut by_day: HashMap<chrono::NaiveDate, Vec<&Training>> = HashMap::new();
 
         for t in trainings {
-            let date = t.date.with_timezone(&moscow_tz()).date_naive();
+            let date = t.date.with_timezone(&tz).date_naive();
             by_day.entry(date).or_default().push(t);
         }
 
@@ -667,16 +715,33 @@ mod tests {
             duration_secs: Some(60),
             pulse_before: None,
             pulse_after: None,
+            rpe: None,
             notes: None,
             user_id: None,
+            context: None,
+            category: None,
+            is_timed: None,
         }
     }
 
+    #[test]
+    fn test_group_by_day_uses_timezone() {
+        // 15:00 UTC is still Jan 5 in Moscow but already Jan 6 in UTC+10
+        let mut training = create_training("отжимания на кулаках", 10, 0);
+        training.date = DateTime::parse_from_rfc3339("2026-01-05T15:00:00+00:00").unwrap().with_timezone(&Utc);
+        let trainings = vec![training];
+
+        let moscow = GoalCalculator::group_by_day(&trainings, moscow_tz());
+        let vladivostok = GoalCalculator::group_by_day(&trainings, user_tz(600));
+        assert!(moscow.contains_key(&chrono::NaiveDate::from_ymd_opt(2026, 1, 5).unwrap()));
+        assert!(vladivostok.contains_key(&chrono::NaiveDate::from_ymd_opt(2026, 1, 6).unwrap()));
+    }
+
     #[test]
     fn test_empty_context_no_fatigue() {
         let context = SessionContext::default();
         let muscles = &[MuscleGroup::Chest, MuscleGroup::Triceps];
-        let fatigue = GoalCalculator::fatigue_factor(&context, muscles);
+        let fatigue = GoalCalculator::fatigue_factor(&context, muscles, FatigueParams::default().k);
         assert_eq!(fatigue, 0.0);
     }
 
@@ -687,7 +752,7 @@ mod tests {
         context.prior_load.insert(MuscleGroup::Triceps, 50);
 
         let muscles = &[MuscleGroup::Chest, MuscleGroup::Triceps];
-        let fatigue = GoalCalculator::fatigue_factor(&context, muscles);
+        let fatigue = GoalCalculator::fatigue_factor(&context, muscles, FatigueParams::default().k);
 
         // 50 reps with k=50 should give ~63% fatigue per muscle
         assert!(fatigue > 0.5 && fatigue < 0.7, "Fatigue: {}", fatigue);
@@ -700,12 +765,32 @@ mod tests {
         // Triceps not loaded
 
         let muscles = &[MuscleGroup::Chest, MuscleGroup::Triceps];
-        let fatigue = GoalCalculator::fatigue_factor(&context, muscles);
+        let fatigue = GoalCalculator::fatigue_factor(&context, muscles, FatigueParams::default().k);
 
         // Only half the muscles are fatigued
         assert!(fatigue > 0.2 && fatigue < 0.4, "Fatigue: {}", fatigue);
     }
 
+    #[test]
+    fn test_higher_fatigue_k_reduces_target_less() {
+        // 40 pushups already done today: same prior load for both calculations
+        let trainings = vec![create_training("отжимания на кулаках", 40, 0)];
+
+        let default = GoalCalculator::calculate(&trainings, "отжимания на кулаках").unwrap();
+        let conditioned = GoalCalculator::calculate_with(
+            &trainings,
+            "отжимания на кулаках",
+            FatigueParams { k: 200.0, ..FatigueParams::default() },
+        )
+        .unwrap();
+
+        assert!(conditioned.fatigue_factor < default.fatigue_factor);
+        assert!(
+            conditioned.target_value > default.target_value,
+            "k=200: {}, default: {}", conditioned.target_value, default.target_value
+        );
+    }
+
     #[test]
     fn test_similarity_same_context() {
         let context = SessionContext::default();
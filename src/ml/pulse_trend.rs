@@ -0,0 +1,110 @@
+//! Pulse trend - Linear regression of post-set pulse over time
+
+use linfa::prelude::*;
+use linfa_linear::LinearRegression;
+use ndarray::{Array1, Array2};
+
+use crate::db::Training;
+
+/// Minimum pulse readings required for a trend
+const MIN_DATA_POINTS: usize = 3;
+
+/// Slope of `pulse_after` for an exercise, in bpm per day.
+/// Negative means the pulse is falling (conditioning improves).
+/// Rows without a pulse reading are ignored.
+pub fn pulse_trend(trainings: &[Training], exercise: &str) -> Option<f64> {
+    let readings: Vec<_> = trainings
+        .iter()
+        .filter(|t| t.exercise == exercise)
+        .filter_map(|t| t.pulse_after.map(|p| (t.date, p)))
+        .collect();
+
+    if readings.len() < MIN_DATA_POINTS {
+        return None;
+    }
+
+    let first_date = readings.iter().map(|(d, _)| *d).min()?;
+    let x_data: Vec<f64> = readings
+        .iter()
+        .map(|(d, _)| (*d - first_date).num_days() as f64)
+        .collect();
+
+    // All readings on one day: no time axis to regress on
+    if x_data.iter().all(|x| *x == x_data[0]) {
+        return None;
+    }
+
+    let y_data: Vec<f64> = readings.iter().map(|(_, p)| *p as f64).collect();
+
+    let records = Array2::from_shape_vec((x_data.len(), 1), x_data).ok()?;
+    let dataset = Dataset::new(records, Array1::from_vec(y_data));
+    let model = LinearRegression::default().fit(&dataset).ok()?;
+
+    Some(model.params()[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn training(exercise: &str, days: i64, pulse_after: Option<i32>) -> Training {
+        Training {
+            id: None,
+            date: Utc.with_ymd_and_hms(2026, 1, 1, 10, 0, 0).unwrap() + Duration::days(days),
+            exercise: exercise.to_string(),
+            sets: 1,
+            reps: 10,
+            duration_secs: None,
+            pulse_before: None,
+            pulse_after,
+            rpe: None,
+            notes: None,
+            user_id: None,
+            context: None,
+            category: None,
+            is_timed: None,
+        }
+    }
+
+    #[test]
+    fn test_falling_pulse() {
+        let trainings = vec![
+            training("Пловец", 0, Some(130)),
+            training("Пловец", 7, Some(126)),
+            training("Пловец", 14, Some(122)),
+        ];
+        let slope = pulse_trend(&trainings, "Пловец").unwrap();
+        assert!((slope - (-8.0 / 14.0)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_ignores_missing_pulse_and_other_exercises() {
+        let trainings = vec![
+            training("Пловец", 0, Some(120)),
+            training("Пловец", 1, None),
+            training("Пловец", 2, Some(122)),
+            training("Планка", 3, Some(200)),
+            training("Пловец", 4, Some(124)),
+        ];
+        let slope = pulse_trend(&trainings, "Пловец").unwrap();
+        assert!((slope - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_not_enough_data() {
+        let trainings = vec![
+            training("Пловец", 0, Some(120)),
+            training("Пловец", 1, None),
+            training("Пловец", 2, Some(118)),
+        ];
+        assert!(pulse_trend(&trainings, "Пловец").is_none());
+
+        let same_day = vec![
+            training("Пловец", 0, Some(120)),
+            training("Пловец", 0, Some(121)),
+            training("Пловец", 0, Some(122)),
+        ];
+        assert!(pulse_trend(&same_day, "Пловец").is_none());
+    }
+}
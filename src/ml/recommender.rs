@@ -2,7 +2,7 @@
 
 use chrono::{Local, Utc};
 use crate::db::Training;
-use crate::exercises::{Exercise, get_base_exercises, get_all_exercises};
+use crate::exercises::{Category, Equipment, Exercise, MuscleGroup, get_base_exercises, get_all_exercises};
 use super::muscle_tracker::MuscleTracker;
 
 /// A recommendation with explanation
@@ -22,36 +22,106 @@ pub struct Recommendation {
 pub struct Recommender {
     tracker: MuscleTracker,
     trainings: Vec<Training>,
+    /// Muscle groups reported sore - exercises hitting them are deprioritized
+    sore_groups: Vec<MuscleGroup>,
+    /// Today's focus category from the weekday template
+    focus: Option<Category>,
+    /// Percent of base exercises done today that unlocks bonus (100 = all)
+    bonus_threshold: u32,
+    /// Gear at hand; None means no restriction
+    available_equipment: Option<Vec<Equipment>>,
+    /// Hardest bonus exercise to suggest (1-5); None means no cap
+    max_difficulty: Option<u8>,
 }
 
 impl Recommender {
     /// Create recommender from training history
     pub fn new(trainings: Vec<Training>) -> Self {
         let tracker = MuscleTracker::from_trainings(&trainings);
-        Self { tracker, trainings }
+        Self {
+            tracker,
+            trainings,
+            sore_groups: Vec::new(),
+            focus: None,
+            bonus_threshold: 100,
+            available_equipment: None,
+            max_difficulty: None,
+        }
+    }
+
+    /// Deprioritize exercises that target these (sore) muscle groups
+    pub fn with_sore_groups(mut self, sore_groups: Vec<MuscleGroup>) -> Self {
+        self.sore_groups = sore_groups;
+        self
+    }
+
+    /// Bias selection toward today's focus category (weekday template)
+    pub fn with_focus(mut self, focus: Option<Category>) -> Self {
+        self.focus = focus;
+        self
+    }
+
+    /// Unlock bonus exercises once this percent of the base program is done today
+    pub fn with_bonus_threshold(mut self, percent: u32) -> Self {
+        self.bonus_threshold = percent.clamp(1, 100);
+        self
+    }
+
+    /// Skip exercises that need gear not in `available`
+    pub fn with_available_equipment(mut self, available: &[Equipment]) -> Self {
+        self.available_equipment = Some(available.to_vec());
+        self
+    }
+
+    /// Keep bonus suggestions at or below this difficulty (None = no cap)
+    pub fn with_max_difficulty(mut self, max: Option<u8>) -> Self {
+        self.max_difficulty = max;
+        self
+    }
+
+    /// Whether the exercise is within the user's difficulty cap
+    fn within_difficulty(&self, exercise: &Exercise) -> bool {
+        self.max_difficulty.is_none_or(|max| exercise.difficulty <= max)
+    }
+
+    /// Whether the exercise can be done with the gear at hand
+    fn is_doable(&self, exercise: &Exercise) -> bool {
+        self.available_equipment
+            .as_ref()
+            .is_none_or(|available| exercise.doable_with(available))
+    }
+
+    /// Whether the exercise matches today's focus category
+    fn is_focus(&self, exercise: &Exercise) -> bool {
+        self.focus == Some(exercise.category)
+    }
+
+    /// Sore muscle groups this exercise targets
+    fn sore_targets(&self, exercise: &Exercise) -> Vec<MuscleGroup> {
+        exercise.muscle_groups
+            .iter()
+            .copied()
+            .filter(|mg| self.sore_groups.contains(mg))
+            .collect()
     }
 
     /// Check if all base exercises were done today
     fn base_program_done_today(&self) -> bool {
-        let today = Local::now().date_naive();
-        let base_exercises = get_base_exercises();
+        get_base_exercises().iter().all(|e| self.is_done_today(e.name))
+    }
 
-        for exercise in base_exercises {
-            let done_today = self.trainings.iter().any(|t| {
-                t.exercise == exercise.name &&
-                t.date.with_timezone(&Local).date_naive() == today
-            });
-            if !done_today {
-                return false;
-            }
-        }
-        true
+    /// Check if enough of the base program is done today to unlock bonus.
+    /// Base exercises needing unavailable gear don't count.
+    fn bonus_unlocked(&self) -> bool {
+        let doable: Vec<_> = get_base_exercises().iter().filter(|e| self.is_doable(e)).collect();
+        let done = doable.iter().filter(|e| self.is_done_today(e.name)).count();
+        done * 100 >= doable.len() * self.bonus_threshold as usize
     }
 
     /// Get best exercise recommendation
     pub fn get_recommendation(&self) -> Option<Recommendation> {
-        // Check if base program is done today
-        if self.base_program_done_today() {
+        // Check if enough of the base program is done today
+        if self.bonus_unlocked() {
             return self.get_bonus_recommendation();
         }
 
@@ -68,6 +138,26 @@ impl Recommender {
         })
     }
 
+    /// Today's base program in recommendation order (warmup first, cooldown last),
+    /// with what's already logged today. Exercises needing unavailable gear are left out.
+    pub fn daily_plan(&self) -> Vec<PlanItem> {
+        let rank = |e: &Exercise| match e.id {
+            "taiji_shadow" => 0,
+            "taiji_shadow_weapon" => 2,
+            _ => 1,
+        };
+        let mut exercises: Vec<&'static Exercise> = get_base_exercises()
+            .iter()
+            .filter(|e| self.is_doable(e))
+            .collect();
+        exercises.sort_by_key(|e| rank(e));
+
+        exercises
+            .into_iter()
+            .map(|exercise| PlanItem { exercise, done: self.is_done_today(exercise.name) })
+            .collect()
+    }
+
     /// Recommend base exercise with fixed order:
     /// 1. taiji_shadow first (warmup)
     /// 2. other base exercises (middle)
@@ -77,19 +167,19 @@ impl Recommender {
         let today = Local::now().date_naive();
 
         // Priority 1: Warmup - taiji_shadow first
-        if !self.is_done_today("тайцзи бой с тенью") {
-            if let Some(ex) = exercises.iter().find(|e| e.id == "taiji_shadow") {
-                let hours_since = self.hours_since_exercise(ex.name);
-                if hours_since >= 1.0 {
-                    return Some(Recommendation {
-                        exercise: ex,
-                        reason: "разминка — начни с этого".to_string(),
-                        confidence: 1.0,
-                        is_bonus: false,
-                        detailed_description: None,
-                        focus_cues: None,
-                    });
-                }
+        if !self.is_done_today("тайцзи бой с тенью")
+            && let Some(ex) = exercises.iter().find(|e| e.id == "taiji_shadow")
+        {
+            let hours_since = self.hours_since_exercise(ex.name);
+            if hours_since >= 1.0 {
+                return Some(Recommendation {
+                    exercise: ex,
+                    reason: "разминка — начни с этого".to_string(),
+                    confidence: 1.0,
+                    is_bonus: false,
+                    detailed_description: None,
+                    focus_cues: None,
+                });
             }
         }
 
@@ -103,6 +193,11 @@ impl Recommender {
                 continue;
             }
 
+            // Skip if the gear isn't at hand
+            if !self.is_doable(exercise) {
+                continue;
+            }
+
             // Skip if done today
             let done_today = self.trainings.iter().any(|t| {
                 t.exercise == exercise.name &&
@@ -130,13 +225,25 @@ impl Recommender {
                 0.3
             };
 
-            let reason = if !targets_underworked.is_empty() {
+            let sore = self.sore_targets(exercise);
+            let (score, reason) = if !sore.is_empty() {
+                // Still possible, but only after everything that doesn't hurt
+                let names: Vec<_> = sore.iter().map(|mg| mg.name_ru()).collect();
+                (score - 1.0, format!("{} болит — полегче", names.join(", ")))
+            } else if !targets_underworked.is_empty() {
                 let names: Vec<_> = targets_underworked.iter().map(|mg| mg.name_ru()).collect();
-                format!("{} мало работали", names.join(", "))
+                (score, format!("{} мало работали", names.join(", ")))
             } else if hours_since == f32::MAX {
-                "ещё не делали".to_string()
+                (score, "ещё не делали".to_string())
+            } else {
+                (score, format!("отдохнули {:.0}ч", hours_since))
+            };
+
+            // Weekday focus outranks balance, but never a sore-muscle penalty
+            let (score, reason) = if self.is_focus(exercise) && sore.is_empty() {
+                (score + 1.0, format!("день: {}, {}", exercise.category.name_ru(), reason))
             } else {
-                format!("отдохнули {:.0}ч", hours_since)
+                (score, reason)
             };
 
             candidates.push((exercise, score, reason));
@@ -158,19 +265,19 @@ impl Recommender {
         }
 
         // Priority 3: Cooldown - taiji_shadow_weapon last
-        if !self.is_done_today("тайцзи бой с тенью с оружием") {
-            if let Some(ex) = exercises.iter().find(|e| e.id == "taiji_shadow_weapon") {
-                let hours_since = self.hours_since_exercise(ex.name);
-                if hours_since >= 1.0 {
-                    return Some(Recommendation {
-                        exercise: ex,
-                        reason: "завершение комплекса".to_string(),
-                        confidence: 1.0,
-                        is_bonus: false,
-                        detailed_description: None,
-                        focus_cues: None,
-                    });
-                }
+        if !self.is_done_today("тайцзи бой с тенью с оружием")
+            && let Some(ex) = exercises.iter().find(|e| e.id == "taiji_shadow_weapon")
+        {
+            let hours_since = self.hours_since_exercise(ex.name);
+            if hours_since >= 1.0 {
+                return Some(Recommendation {
+                    exercise: ex,
+                    reason: "завершение комплекса".to_string(),
+                    confidence: 1.0,
+                    is_bonus: false,
+                    detailed_description: None,
+                    focus_cues: None,
+                });
             }
         }
 
@@ -182,11 +289,16 @@ impl Recommender {
     /// Priority 2: Never done (any)
     /// Priority 3: All done → recommend for balance (sorted by recency + underworked)
     fn get_bonus_recommendation(&self) -> Option<Recommendation> {
-        let bonus_exercises: Vec<_> = get_all_exercises()
+        let mut bonus_exercises: Vec<_> = get_all_exercises()
             .into_iter()
-            .filter(|e| !e.is_base)
+            .filter(|e| !e.is_base && self.is_doable(e) && self.within_difficulty(e))
             .collect();
 
+        // Skip exercises hitting sore muscles, unless nothing else is left
+        if bonus_exercises.iter().any(|e| self.sore_targets(e).is_empty()) {
+            bonus_exercises.retain(|e| self.sore_targets(e).is_empty());
+        }
+
         let underworked = self.tracker.get_underworked_groups(5);
 
         // Helper: check if exercise targets underworked muscles
@@ -207,7 +319,7 @@ impl Recommender {
         if !never_done_underworked.is_empty() {
             // Sort by number of underworked muscles targeted
             let mut sorted = never_done_underworked;
-            sorted.sort_by_key(|e| std::cmp::Reverse(underworked_count(e)));
+            sorted.sort_by_key(|e| std::cmp::Reverse((self.is_focus(e), underworked_count(e))));
 
             let exercise = sorted[0];
             let muscle_names: Vec<_> = exercise.muscle_groups
@@ -234,7 +346,7 @@ impl Recommender {
         if !never_done_any.is_empty() {
             // Prefer those targeting underworked muscles, then by variety
             let mut sorted = never_done_any;
-            sorted.sort_by_key(|e| std::cmp::Reverse(underworked_count(e)));
+            sorted.sort_by_key(|e| std::cmp::Reverse((self.is_focus(e), underworked_count(e))));
 
             let exercise = sorted[0];
             return Some(Recommendation {
@@ -259,7 +371,8 @@ impl Recommender {
                 let days = self.days_since_exercise(e.name).unwrap_or(0);
                 let underworked_score = underworked_count(e) as f32 * 10.0;
                 let recency_score = (days as f32).min(30.0); // Cap at 30 days
-                let total_score = underworked_score + recency_score;
+                let focus_score = if self.is_focus(e) { 20.0 } else { 0.0 };
+                let total_score = underworked_score + recency_score + focus_score;
                 (*e, total_score)
             })
             .collect();
@@ -408,10 +521,10 @@ impl Recommender {
                     t.exercise == exercise.name &&
                     t.date.with_timezone(&Local).date_naive() < today
                 })
-                .map(|t| if is_timed { t.duration_secs.unwrap_or(0) as i32 } else { t.reps })
+                .map(|t| if is_timed { t.duration_secs.unwrap_or(0) } else { t.reps })
                 .max();
 
-            let is_record = previous_best.map_or(false, |prev| value > prev);
+            let is_record = previous_best.is_some_and(|prev| value > prev);
             if is_record {
                 new_records.push(exercise.name.to_string());
             }
@@ -461,6 +574,13 @@ pub struct ExerciseSummary {
     pub role: Option<String>,
 }
 
+/// One exercise of today's plan
+#[derive(Debug, Clone)]
+pub struct PlanItem {
+    pub exercise: &'static Exercise,
+    pub done: bool,
+}
+
 /// Summary of completed base program
 #[derive(Debug, Clone)]
 pub struct BaseProgramSummary {
@@ -542,8 +662,12 @@ mod tests {
             duration_secs: None,
             pulse_before: None,
             pulse_after: None,
+            rpe: None,
             notes: None,
             user_id: None,
+            context: None,
+            category: None,
+            is_timed: None,
         }
     }
 
@@ -557,8 +681,12 @@ mod tests {
             duration_secs: None,
             pulse_before: None,
             pulse_after: None,
+            rpe: None,
             notes: None,
             user_id: None,
+            context: None,
+            category: None,
+            is_timed: None,
         }
     }
 
@@ -606,6 +734,44 @@ mod tests {
             "Should recommend base exercise, got: {}", rec.exercise.name);
     }
 
+    #[test]
+    fn test_sore_groups_deprioritized() {
+        let trainings = vec![
+            create_training_local_today("тайцзи бой с тенью", 1, 2), // warmup done
+        ];
+        let recommender = Recommender::new(trainings)
+            .with_sore_groups(vec![MuscleGroup::Core]);
+        let rec = recommender.get_recommendation().unwrap();
+
+        // Swimmer is the only middle exercise not loading the core
+        assert_eq!(rec.exercise.id, "swimmer");
+    }
+
+    #[test]
+    fn test_weekday_focus_biases_base_selection() {
+        let trainings = vec![
+            create_training_local_today("тайцзи бой с тенью", 1, 2),
+        ];
+        let recommender = Recommender::new(trainings)
+            .with_focus(Some(Category::Legs));
+        let rec = recommender.get_recommendation().unwrap();
+
+        assert_eq!(rec.exercise.id, "squats_strikes");
+        assert!(rec.reason.starts_with("день: ноги"), "reason: {}", rec.reason);
+    }
+
+    #[test]
+    fn test_sore_exercise_still_offered_as_last_resort() {
+        let trainings = vec![
+            create_training_local_today("тайцзи бой с тенью", 1, 2),
+        ];
+        let recommender = Recommender::new(trainings)
+            .with_sore_groups(MuscleGroup::all().to_vec());
+        let rec = recommender.get_recommendation().unwrap();
+
+        assert!(rec.reason.contains("болит"), "reason: {}", rec.reason);
+    }
+
     #[test]
     fn test_hours_since_never_done() {
         let recommender = Recommender::new(vec![]);
@@ -739,7 +905,7 @@ mod tests {
         ];
         let recommender = Recommender::new(trainings);
         let days = recommender.days_since_exercise("впусти меня").unwrap();
-        assert!(days >= 1 && days <= 3, "Expected ~2 days, got {}", days);
+        assert!((1..=3).contains(&days), "Expected ~2 days, got {}", days);
     }
 
     fn create_training_local_today(exercise: &str, reps: i32, hours_ago: i64) -> Training {
@@ -756,8 +922,12 @@ mod tests {
             duration_secs: None,
             pulse_before: None,
             pulse_after: None,
+            rpe: None,
             notes: None,
             user_id: None,
+            context: None,
+            category: None,
+            is_timed: None,
         }
     }
 
@@ -787,6 +957,104 @@ mod tests {
         assert!(rec.focus_cues.is_some(), "Bonus should have focus_cues");
     }
 
+    #[test]
+    fn test_bonus_threshold_unlocks_on_partial_day() {
+        // 7 of 8 base exercises (87.5%) done today
+        let trainings = vec![
+            create_training_local_today("отжимания на кулаках", 20, 2),
+            create_training_local_today("отжимания с ручками", 20, 2),
+            create_training_local_today("пресс складной нож", 20, 2),
+            create_training_local_today("стойка на локтях", 60, 2),
+            create_training_local_today("приседания с ударами", 30, 2),
+            create_training_local_today("пловец", 20, 2),
+            create_training_local_today("тайцзи бой с тенью", 60, 2),
+        ];
+
+        let strict = Recommender::new(trainings.clone());
+        assert!(!strict.get_recommendation().unwrap().is_bonus, "default needs the whole base");
+
+        let relaxed = Recommender::new(trainings.clone()).with_bonus_threshold(80);
+        assert!(relaxed.get_recommendation().unwrap().is_bonus);
+
+        let too_high = Recommender::new(trainings).with_bonus_threshold(90);
+        assert!(!too_high.get_recommendation().unwrap().is_bonus);
+    }
+
+    fn plan_ids(recommender: &Recommender) -> Vec<(&'static str, bool)> {
+        recommender.daily_plan().iter().map(|item| (item.exercise.id, item.done)).collect()
+    }
+
+    #[test]
+    fn test_daily_plan_order_and_done() {
+        let trainings = vec![create_training_local_today("пловец", 20, 2)];
+        let plan = plan_ids(&Recommender::new(trainings));
+
+        assert_eq!(plan.first().unwrap().0, "taiji_shadow");
+        assert_eq!(plan.last().unwrap().0, "taiji_shadow_weapon");
+        assert_eq!(plan.len(), get_base_exercises().len());
+        let done: Vec<_> = plan.iter().filter(|(_, done)| *done).map(|(id, _)| *id).collect();
+        assert_eq!(done, vec!["swimmer"]);
+    }
+
+    #[test]
+    fn test_daily_plan_skips_unavailable_gear() {
+        let plan = plan_ids(&Recommender::new(vec![]).with_available_equipment(&[]));
+        assert!(plan.iter().all(|(id, _)| *id != "pushups_handles"));
+        assert_eq!(plan.len(), get_base_exercises().len() - 1);
+    }
+
+    #[test]
+    fn test_equipment_filter() {
+        // Everything but handle push-ups done today
+        let trainings = vec![
+            create_training_local_today("отжимания на кулаках", 20, 2),
+            create_training_local_today("пресс складной нож", 20, 2),
+            create_training_local_today("стойка на локтях", 60, 2),
+            create_training_local_today("приседания с ударами", 30, 2),
+            create_training_local_today("пловец", 20, 2),
+            create_training_local_today("тайцзи бой с тенью", 60, 2),
+            create_training_local_today("тайцзи бой с тенью с оружием", 60, 2),
+        ];
+
+        let at_home = Recommender::new(trainings.clone()).get_recommendation().unwrap();
+        assert_eq!(at_home.exercise.id, "pushups_handles");
+
+        // Without gear the missing base exercise doesn't block bonus
+        let traveling = Recommender::new(trainings)
+            .with_available_equipment(&[])
+            .get_recommendation()
+            .unwrap();
+        assert!(traveling.is_bonus);
+        assert!(traveling.exercise.equipment.is_empty());
+    }
+
+    #[test]
+    fn test_max_difficulty_caps_bonus_pool() {
+        let trainings = vec![
+            create_training_local_today("отжимания на кулаках", 20, 2),
+            create_training_local_today("отжимания с ручками", 20, 2),
+            create_training_local_today("пресс складной нож", 20, 2),
+            create_training_local_today("стойка на локтях", 60, 2),
+            create_training_local_today("приседания с ударами", 30, 2),
+            create_training_local_today("пловец", 20, 2),
+            create_training_local_today("тайцзи бой с тенью", 60, 2),
+            create_training_local_today("тайцзи бой с тенью с оружием", 60, 2),
+        ];
+
+        let beginner = Recommender::new(trainings.clone()).with_max_difficulty(Some(1));
+        let rec = beginner.get_recommendation().unwrap();
+        assert!(rec.is_bonus);
+        assert_eq!(rec.exercise.difficulty, 1);
+
+        // Every exercise done: balance picks still respect the cap
+        let mut all_done = trainings;
+        for ex in get_all_exercises().into_iter().filter(|e| !e.is_base) {
+            all_done.push(create_training_local_today(ex.name, 10, 30));
+        }
+        let rec = Recommender::new(all_done).with_max_difficulty(Some(2)).get_recommendation().unwrap();
+        assert!(rec.exercise.difficulty <= 2);
+    }
+
     #[test]
     fn test_bonus_prioritizes_never_done() {
         // Do all base + some bonus exercises
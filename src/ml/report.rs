@@ -0,0 +1,103 @@
+//! Progress report - everything known about one exercise in a single struct
+
+use serde::Serialize;
+
+use crate::db::Training;
+use super::{Analytics, GoalCalculator, Prediction, ProgressGoal, ProgressPredictor};
+
+/// Aggregated progress for an exercise (serializable for external frontends)
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgressReport {
+    pub exercise: String,
+    /// Logged sets of this exercise, all time
+    pub total_sets: usize,
+    /// Total volume (sets * reps), all time
+    pub total_volume: i32,
+    /// Personal record (reps, or seconds for timed exercises)
+    pub personal_best: Option<i32>,
+    /// Linear-regression trend and averages (needs enough data points)
+    pub prediction: Option<Prediction>,
+    /// Fatigue-adjusted target, consolidation status and today's stats
+    pub goal: Option<ProgressGoal>,
+}
+
+/// Build a full progress report for `exercise` from a user's trainings
+pub fn progress_report(trainings: &[Training], exercise: &str) -> ProgressReport {
+    let exercise_trainings: Vec<Training> = trainings
+        .iter()
+        .filter(|t| t.exercise == exercise)
+        .cloned()
+        .collect();
+
+    let goal = GoalCalculator::calculate(trainings, exercise);
+    let personal_best = goal.as_ref()
+        .and_then(|g| g.personal_best)
+        .or_else(|| exercise_trainings.iter().map(|t| t.reps).max());
+
+    ProgressReport {
+        exercise: exercise.to_string(),
+        total_sets: exercise_trainings.len(),
+        total_volume: Analytics::new(exercise_trainings).total_volume(exercise),
+        personal_best,
+        prediction: ProgressPredictor::train(trainings, exercise).map(|p| p.get_prediction()),
+        goal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    fn training_days_ago(exercise: &str, reps: i32, days_ago: i64) -> Training {
+        Training {
+            id: None,
+            date: Utc::now() - Duration::days(days_ago),
+            exercise: exercise.to_string(),
+            sets: 1,
+            reps,
+            duration_secs: Some(30),
+            pulse_before: None,
+            pulse_after: None,
+            rpe: None,
+            notes: None,
+            user_id: None,
+            context: None,
+            category: None,
+            is_timed: None,
+        }
+    }
+
+    #[test]
+    fn test_progress_report_empty() {
+        let report = progress_report(&[], "пловец");
+        assert_eq!(report.total_sets, 0);
+        assert_eq!(report.total_volume, 0);
+        assert!(report.personal_best.is_none());
+        assert!(report.prediction.is_none());
+    }
+
+    #[test]
+    fn test_progress_report_composes_parts() {
+        let trainings = vec![
+            training_days_ago("пловец", 24, 1),
+            training_days_ago("пловец", 22, 3),
+            training_days_ago("пловец", 20, 5),
+            training_days_ago("планка на локтях", 60, 1),
+        ];
+        let report = progress_report(&trainings, "пловец");
+
+        assert_eq!(report.total_sets, 3);
+        assert_eq!(report.total_volume, 66);
+        assert_eq!(report.personal_best, Some(24));
+        assert!(report.prediction.is_some(), "3 points are enough for a trend");
+        assert!(report.goal.is_some());
+    }
+
+    #[test]
+    fn test_progress_report_serializes() {
+        let trainings = vec![training_days_ago("пловец", 20, 1)];
+        let json = serde_json::to_string(&progress_report(&trainings, "пловец")).unwrap();
+        assert!(json.contains("\"exercise\":\"пловец\""), "{}", json);
+    }
+}
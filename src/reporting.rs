@@ -0,0 +1,154 @@
+//! Markdown reports for a training journal (`majowuji report`)
+
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, NaiveDate};
+
+use crate::db::{user_tz, Training, DEFAULT_TZ_OFFSET_MINUTES};
+use crate::ml::{Analytics, MuscleTracker};
+
+/// Seconds as "1h 05m 30s" / "5m 30s" / "30s"
+pub fn format_secs(secs: i64) -> String {
+    let (hours, minutes, secs) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, secs)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, secs)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Markdown summary of one calendar month (Moscow days): totals, records,
+/// muscle balance at the month's end and a day-by-day table
+pub fn monthly_report(trainings: &[Training], year: i32, month: u32) -> String {
+    let title = format!("# Training report {}-{:02}\n", year, month);
+    let Some(first_day) = NaiveDate::from_ymd_opt(year, month, 1) else {
+        return format!("{}\nInvalid month.\n", title);
+    };
+    let last_day = first_day
+        .checked_add_months(chrono::Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .unwrap_or(first_day);
+
+    let tz = user_tz(DEFAULT_TZ_OFFSET_MINUTES);
+    let local_date = |t: &Training| t.date.with_timezone(&tz).date_naive();
+    let in_month: Vec<Training> = trainings
+        .iter()
+        .filter(|t| local_date(t).year() == year && local_date(t).month() == month)
+        .cloned()
+        .collect();
+    if in_month.is_empty() {
+        return format!("{}\nNo data for this month.\n", title);
+    }
+
+    let mut days: BTreeMap<NaiveDate, Vec<&Training>> = BTreeMap::new();
+    for t in &in_month {
+        days.entry(local_date(t)).or_default().push(t);
+    }
+
+    let analytics = Analytics::new(in_month.clone());
+    let mut text = title;
+    text.push_str("\n## Summary\n\n");
+    text.push_str(&format!("- Sessions (training days): {}\n", days.len()));
+    text.push_str(&format!("- Sets: {}\n", analytics.total_sets()));
+    text.push_str(&format!("- Reps: {}\n", analytics.total_reps(None)));
+    text.push_str(&format!("- Time: {}\n", format_secs(analytics.total_duration_secs(None))));
+
+    text.push_str("\n## Records\n\n| Exercise | Best |\n|---|---|\n");
+    for (exercise, value, is_timed) in analytics.personal_records() {
+        let best = if is_timed {
+            format_secs(i64::from(value))
+        } else {
+            format!("{} reps", value)
+        };
+        text.push_str(&format!("| {} | {} |\n", exercise, best));
+    }
+
+    let tracker = MuscleTracker::from_trainings_at(&in_month, last_day);
+    text.push_str(&format!(
+        "\n## Muscle balance\n\nBalance score over the month's last week: {:.0}%\n",
+        tracker.get_balance_score()
+    ));
+
+    text.push_str("\n## Days\n\n| Date | Sets | Reps | Time | Exercises |\n|---|---|---|---|---|\n");
+    for (date, day) in &days {
+        let reps: i64 = day.iter().map(|t| i64::from(t.sets) * i64::from(t.reps)).sum();
+        let secs: i64 = day.iter().filter_map(|t| t.duration_secs).map(i64::from).sum();
+        let mut exercises: Vec<&str> = day.iter().map(|t| t.exercise.as_str()).collect();
+        exercises.sort_unstable();
+        exercises.dedup();
+        text.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            date.format("%Y-%m-%d"),
+            day.iter().map(|t| t.sets).sum::<i32>(),
+            reps,
+            format_secs(secs),
+            exercises.join(", ")
+        ));
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+
+    fn training(date: &str, exercise: &str, reps: i32, duration_secs: Option<i32>) -> Training {
+        Training {
+            id: None,
+            date: DateTime::parse_from_rfc3339(date).unwrap().with_timezone(&Utc),
+            exercise: exercise.to_string(),
+            sets: 1,
+            reps,
+            duration_secs,
+            pulse_before: None,
+            pulse_after: None,
+            rpe: None,
+            notes: None,
+            user_id: None,
+            context: None,
+            category: None,
+            is_timed: None,
+        }
+    }
+
+    #[test]
+    fn test_format_secs() {
+        assert_eq!(format_secs(30), "30s");
+        assert_eq!(format_secs(330), "5m 30s");
+        assert_eq!(format_secs(3930), "1h 05m 30s");
+    }
+
+    #[test]
+    fn test_monthly_report_sections() {
+        let trainings = vec![
+            training("2026-01-05T09:00:00+03:00", "пловец", 12, Some(40)),
+            training("2026-01-05T09:10:00+03:00", "пловец", 15, Some(45)),
+            training("2026-01-06T09:00:00+03:00", "стойка на локтях", 1, Some(90)),
+            // Moscow Feb 1st: outside the month
+            training("2026-01-31T22:00:00+00:00", "пловец", 30, None),
+            training("2025-12-31T09:00:00+03:00", "пловец", 40, None),
+        ];
+
+        let report = monthly_report(&trainings, 2026, 1);
+        assert!(report.starts_with("# Training report 2026-01"), "{}", report);
+        assert!(report.contains("- Sessions (training days): 2"), "{}", report);
+        assert!(report.contains("- Sets: 3"), "{}", report);
+        assert!(report.contains("- Reps: 28"), "{}", report);
+        assert!(report.contains("- Time: 2m 55s"), "{}", report);
+        assert!(report.contains("| пловец | 15 reps |"), "{}", report);
+        assert!(report.contains("| стойка на локтях | 1m 30s |"), "{}", report);
+        assert!(report.contains("## Muscle balance"), "{}", report);
+        assert!(report.contains("| 2026-01-05 | 2 | 27 | 1m 25s | пловец |"), "{}", report);
+        assert!(report.contains("| 2026-01-06 | 1 | 1 | 1m 30s | стойка на локтях |"), "{}", report);
+    }
+
+    #[test]
+    fn test_monthly_report_empty_month() {
+        let trainings = vec![training("2026-01-05T09:00:00+03:00", "пловец", 12, None)];
+        assert!(monthly_report(&trainings, 2026, 2).contains("No data for this month."));
+        assert!(monthly_report(&trainings, 2026, 13).contains("Invalid month."));
+    }
+}
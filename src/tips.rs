@@ -1,6 +1,7 @@
 //! Tips module - советы из книги "You Are Your Own Gym"
 
 use rand::seq::SliceRandom;
+use rand::Rng;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TipCategory {
@@ -31,6 +32,32 @@ impl TipCategory {
             TipCategory::Recovery => "Восстановление",
         }
     }
+
+    /// Stable id for storage
+    pub fn id(&self) -> &'static str {
+        match self {
+            TipCategory::Motivation => "motivation",
+            TipCategory::Nutrition => "nutrition",
+            TipCategory::Training => "training",
+            TipCategory::Technique => "technique",
+            TipCategory::Recovery => "recovery",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<TipCategory> {
+        Self::all().iter().copied().find(|c| c.id() == id)
+    }
+
+    /// All categories in rotation order
+    pub fn all() -> &'static [TipCategory] {
+        &[
+            TipCategory::Motivation,
+            TipCategory::Nutrition,
+            TipCategory::Training,
+            TipCategory::Technique,
+            TipCategory::Recovery,
+        ]
+    }
 }
 
 pub struct Tip {
@@ -172,12 +199,42 @@ pub fn get_random_tip() -> &'static Tip {
     TIPS.choose(&mut rand::thread_rng()).unwrap_or(&TIPS[0])
 }
 
+/// Случайный совет, кроме недавно показанных (индексы в `TIPS`).
+/// Если показаны все — любой совет.
+pub fn get_random_tip_excluding(recent: &[usize]) -> (&'static Tip, usize) {
+    let mut rng = rand::thread_rng();
+    let fresh: Vec<usize> = (0..TIPS.len()).filter(|i| !recent.contains(i)).collect();
+    let index = fresh
+        .choose(&mut rng)
+        .copied()
+        .unwrap_or_else(|| rng.gen_range(0..TIPS.len()));
+    (&TIPS[index], index)
+}
+
 /// Получить случайный совет определённой категории
 pub fn get_random_tip_by_category(category: TipCategory) -> Option<&'static Tip> {
     let filtered: Vec<_> = TIPS.iter().filter(|t| t.category == category).collect();
     filtered.choose(&mut rand::thread_rng()).copied()
 }
 
+/// Категория следующего совета дня: следующая по кругу после вчерашней
+/// (категории без советов пропускаются)
+pub fn next_daily_category(previous: Option<TipCategory>) -> TipCategory {
+    let all = TipCategory::all();
+    let start = previous
+        .and_then(|p| all.iter().position(|c| *c == p))
+        .map_or(0, |i| i + 1);
+    (0..all.len())
+        .map(|offset| all[(start + offset) % all.len()])
+        .find(|c| TIPS.iter().any(|t| t.category == *c))
+        .unwrap_or(TIPS[0].category)
+}
+
+/// Совет дня: случайный совет из следующей по кругу категории
+pub fn get_daily_tip(previous: Option<TipCategory>) -> &'static Tip {
+    get_random_tip_by_category(next_daily_category(previous)).unwrap_or_else(get_random_tip)
+}
+
 /// Форматировать совет для отправки
 pub fn format_tip(tip: &Tip) -> String {
     format!(
@@ -232,6 +289,44 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_get_random_tip_excluding_skips_recent() {
+        let recent = [0, 1, 2];
+        for _ in 0..200 {
+            let (tip, index) = get_random_tip_excluding(&recent);
+            assert!(!recent.contains(&index));
+            assert_eq!(tip.text, TIPS[index].text);
+        }
+    }
+
+    #[test]
+    fn test_get_random_tip_excluding_all_falls_back() {
+        let all: Vec<usize> = (0..TIPS.len()).collect();
+        let (_, index) = get_random_tip_excluding(&all);
+        assert!(index < TIPS.len());
+    }
+
+    #[test]
+    fn test_tip_category_ids_round_trip() {
+        for category in TipCategory::all() {
+            assert_eq!(TipCategory::from_id(category.id()), Some(*category));
+        }
+        assert_eq!(TipCategory::from_id("sleep"), None);
+    }
+
+    #[test]
+    fn test_daily_tip_rotates_categories() {
+        let mut previous = None;
+        for _ in 0..TipCategory::all().len() * 2 {
+            let tip = get_daily_tip(previous);
+            assert_ne!(Some(tip.category), previous);
+            previous = Some(tip.category);
+        }
+        assert_eq!(next_daily_category(None), TipCategory::Motivation);
+        assert_eq!(next_daily_category(Some(TipCategory::Motivation)), TipCategory::Nutrition);
+        assert_eq!(next_daily_category(Some(TipCategory::Recovery)), TipCategory::Motivation);
+    }
+
     #[test]
     fn test_get_random_tip_by_category_returns_correct_category() {
         // Проверяем каждую категорию
@@ -8,44 +8,256 @@ use crossterm::{
 };
 use ratatui::{
     prelude::*,
-    widgets::{Block, Borders, Paragraph, Table, Row, Cell},
+    widgets::{BarChart, Block, Borders, Clear, Paragraph, Table, TableState, Row, Cell},
 };
 use std::io::{stdout, Stdout};
+use std::time::{Duration, Instant};
 
-use crate::db::{Database, Training};
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, Utc};
+
+use crate::db::{user_tz, Database, Training, DEFAULT_TZ_OFFSET_MINUTES};
+use crate::exercises::{validate_reps, validate_sets, Category};
+use crate::ml::MuscleTracker;
 
 type Tui = Terminal<CrosstermBackend<Stdout>>;
 
+/// Days in the volume chart (toggled with `v`)
+const VOLUME_DAYS: i64 = 7;
+
+/// Field of the add-training form
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum InputField {
+    #[default]
+    Exercise,
+    Sets,
+    Reps,
+}
+
+impl InputField {
+    fn next(self) -> Self {
+        match self {
+            InputField::Exercise => InputField::Sets,
+            InputField::Sets => InputField::Reps,
+            InputField::Reps => InputField::Exercise,
+        }
+    }
+
+    fn prev(self) -> Self {
+        match self {
+            InputField::Exercise => InputField::Reps,
+            InputField::Sets => InputField::Exercise,
+            InputField::Reps => InputField::Sets,
+        }
+    }
+}
+
+/// In-progress add-training form (opened with `a`)
+#[derive(Debug, Default)]
+struct InputState {
+    exercise: String,
+    sets: String,
+    reps: String,
+    field: InputField,
+    /// Validation message from the last submit attempt
+    error: Option<String>,
+}
+
+impl InputState {
+    fn new() -> Self {
+        Self { sets: "1".to_string(), ..Self::default() }
+    }
+
+    fn push(&mut self, c: char) {
+        match self.field {
+            InputField::Exercise => self.exercise.push(c),
+            InputField::Sets if c.is_ascii_digit() => self.sets.push(c),
+            InputField::Reps if c.is_ascii_digit() => self.reps.push(c),
+            _ => {}
+        }
+    }
+
+    fn pop(&mut self) {
+        match self.field {
+            InputField::Exercise => self.exercise.pop(),
+            InputField::Sets => self.sets.pop(),
+            InputField::Reps => self.reps.pop(),
+        };
+    }
+
+    /// Training from the form, or a message for the first invalid field;
+    /// sets and reps use the same bounds as the CLI and the bot
+    fn to_training(&self) -> std::result::Result<Training, String> {
+        let exercise = self.exercise.trim();
+        if exercise.is_empty() {
+            return Err("Exercise is required".to_string());
+        }
+        let sets = validate_sets(self.sets.parse().unwrap_or(0))?;
+        let reps = validate_reps(self.reps.parse().unwrap_or(0))?;
+        Ok(Training {
+            id: None,
+            date: Utc::now(),
+            exercise: exercise.to_string(),
+            sets,
+            reps,
+            duration_secs: None,
+            pulse_before: None,
+            pulse_after: None,
+            rpe: None,
+            notes: None,
+            user_id: None,
+            context: None,
+            category: None,
+            is_timed: None,
+        })
+    }
+}
+
 /// App state for TUI
 pub struct App {
     db: Database,
     trainings: Vec<Training>,
+    /// Weekly muscle load for the balance panel, rebuilt on refresh
+    tracker: MuscleTracker,
     should_quit: bool,
+    /// Case-insensitive substring filter on exercise name
+    filter: String,
+    /// Typing into the filter (after `/`)
+    editing_filter: bool,
+    /// Catalog category filter, cycled with Tab
+    category_filter: Option<Category>,
+    /// Reload trainings after this long without a keypress (None = only on `r`)
+    refresh_interval: Option<Duration>,
+    last_refresh: Instant,
+    last_key: Instant,
+    /// Add-training form, when open
+    input: Option<InputState>,
+    /// Highlighted row among the visible trainings
+    selected: Option<usize>,
+    /// One-off message shown in the footer (e.g. after a delete)
+    status: Option<String>,
+    /// Show the daily volume chart instead of the training table
+    show_volume: bool,
 }
 
 impl App {
     pub fn new(db: Database) -> Result<Self> {
         let trainings = db.get_trainings()?;
+        let tracker = MuscleTracker::from_trainings(&trainings);
         Ok(Self {
             db,
             trainings,
+            tracker,
             should_quit: false,
+            filter: String::new(),
+            editing_filter: false,
+            category_filter: None,
+            refresh_interval: None,
+            last_refresh: Instant::now(),
+            last_key: Instant::now(),
+            input: None,
+            selected: None,
+            status: None,
+            show_volume: false,
         })
     }
 
+    /// Reload trainings periodically, e.g. while the bot writes to the same DB
+    pub fn with_auto_refresh(mut self, interval: Option<Duration>) -> Self {
+        self.refresh_interval = interval.filter(|i| !i.is_zero());
+        self
+    }
+
+    /// Reload trainings; the active filters apply to the new data and the
+    /// highlight stays within the matching rows
+    fn refresh(&mut self) -> Result<()> {
+        self.trainings = self.db.get_trainings()?;
+        self.tracker = MuscleTracker::from_trainings(&self.trainings);
+        self.last_refresh = Instant::now();
+        let len = self.visible_trainings().len();
+        self.selected = self.selected.filter(|_| len > 0).map(|i| i.min(len - 1));
+        Ok(())
+    }
+
+    /// Whether the auto-refresh interval has elapsed since both the last
+    /// reload and the last keypress, so typing never triggers a re-query
+    fn refresh_due(&self) -> bool {
+        self.refresh_interval.is_some_and(|interval| {
+            self.last_refresh.elapsed() >= interval && self.last_key.elapsed() >= interval
+        })
+    }
+
+    /// Trainings matching the active text and category filters
+    fn visible_trainings(&self) -> Vec<&Training> {
+        let query = self.filter.to_lowercase();
+        self.trainings
+            .iter()
+            .filter(|t| t.exercise.to_lowercase().contains(&query))
+            .filter(|t| match self.category_filter {
+                Some(category) => t.exercise_category() == Some(category),
+                None => true,
+            })
+            .collect()
+    }
+
+    /// Total reps (sets x reps) per Moscow-local day over the last `VOLUME_DAYS`, oldest first;
+    /// days without trainings are zero
+    fn daily_volume(&self, now: DateTime<Utc>) -> Vec<(NaiveDate, i64)> {
+        let tz = user_tz(DEFAULT_TZ_OFFSET_MINUTES);
+        let today = now.with_timezone(&tz).date_naive();
+        let mut days: Vec<(NaiveDate, i64)> = (0..VOLUME_DAYS)
+            .rev()
+            .map(|ago| (today - ChronoDuration::days(ago), 0))
+            .collect();
+        for training in &self.trainings {
+            let date = training.date.with_timezone(&tz).date_naive();
+            if let Some((_, total)) = days.iter_mut().find(|(day, _)| *day == date) {
+                *total += i64::from(training.sets) * i64::from(training.reps);
+            }
+        }
+        days
+    }
+
+    /// Next category filter: none -> each category in order -> none
+    fn next_category_filter(&self) -> Option<Category> {
+        let all = Category::all();
+        match self.category_filter {
+            None => all.first().copied(),
+            Some(current) => all.iter()
+                .position(|c| *c == current)
+                .and_then(|i| all.get(i + 1))
+                .copied(),
+        }
+    }
+
     /// Run the TUI application
     pub fn run(&mut self) -> Result<()> {
         let mut terminal = init_terminal()?;
+        // Leave raw mode and the alternate screen even when the loop fails
+        let result = self.event_loop(&mut terminal);
+        restore_terminal()?;
+        result
+    }
 
+    fn event_loop(&mut self, terminal: &mut Tui) -> Result<()> {
         while !self.should_quit {
             terminal.draw(|frame| self.render(frame))?;
             self.handle_events()?;
+            if self.refresh_due() {
+                self.auto_refresh();
+            }
         }
-
-        restore_terminal()?;
         Ok(())
     }
 
+    /// Timed reload; a failure (e.g. the bot holding a write lock) goes to the
+    /// status line and is retried after the next interval
+    fn auto_refresh(&mut self) {
+        if let Err(e) = self.refresh() {
+            self.status = Some(format!("refresh failed: {}", e));
+            self.last_refresh = Instant::now();
+        }
+    }
+
     fn render(&self, frame: &mut Frame) {
         let area = frame.area();
 
@@ -64,8 +276,15 @@ impl App {
             .block(Block::default().borders(Borders::ALL));
         frame.render_widget(header, chunks[0]);
 
+        // Main area: trainings on the left, weekly muscle balance on the right
+        let main = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(40), Constraint::Length(36)])
+            .split(chunks[1]);
+
         // Training table
-        let rows: Vec<Row> = self.trainings.iter().map(|t| {
+        let visible = self.visible_trainings();
+        let rows: Vec<Row> = visible.iter().map(|t| {
             Row::new(vec![
                 Cell::from(t.date.format("%Y-%m-%d").to_string()),
                 Cell::from(t.exercise.clone()),
@@ -74,6 +293,8 @@ impl App {
             ])
         }).collect();
 
+        let mut state = TableState::default()
+            .with_selected(self.selected.filter(|i| *i < visible.len()));
         let table = Table::new(
             rows,
             [
@@ -85,31 +306,252 @@ impl App {
         )
         .header(Row::new(vec!["Date", "Exercise", "Sets x Reps", "Notes"])
             .style(Style::default().bold()))
-        .block(Block::default().borders(Borders::ALL).title("Trainings"));
+        .block(Block::default().borders(Borders::ALL).title(self.table_title(visible.len())))
+        .row_highlight_style(Style::default().reversed());
 
-        frame.render_widget(table, chunks[1]);
+        if self.show_volume {
+            let volume = self.daily_volume(Utc::now());
+            let total: i64 = volume.iter().map(|(_, reps)| reps).sum();
+            let bars: Vec<(String, u64)> = volume
+                .iter()
+                .map(|(day, reps)| (day.format("%d.%m").to_string(), *reps as u64))
+                .collect();
+            let data: Vec<(&str, u64)> = bars.iter().map(|(label, reps)| (label.as_str(), *reps)).collect();
+            let chart = BarChart::default()
+                .block(Block::default().borders(Borders::ALL).title(format!("Reps, last {} days: {}", VOLUME_DAYS, total)))
+                .data(&data)
+                .bar_width(6)
+                .bar_gap(2)
+                .bar_style(Style::default().fg(Color::Cyan))
+                .value_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+            frame.render_widget(chart, main[0]);
+        } else {
+            frame.render_stateful_widget(table, main[0], &mut state);
+        }
+        frame.render_widget(self.balance_table(), main[1]);
 
         // Footer
-        let footer = Paragraph::new("q: quit | a: add | r: refresh")
+        let footer_text = if self.input.is_some() {
+            "Tab/↑↓: field | Enter: next / save | Esc: cancel".to_string()
+        } else if self.editing_filter {
+            format!("/{}_  (Enter: apply | Esc: clear)", self.filter)
+        } else if let Some(status) = &self.status {
+            status.clone()
+        } else {
+            "q: quit | a: add | ↑↓: select | d: delete | r: refresh | v: volume | /: filter | Tab: category | Esc: clear".to_string()
+        };
+        let footer = Paragraph::new(footer_text)
             .style(Style::default().fg(Color::DarkGray))
             .block(Block::default().borders(Borders::ALL));
         frame.render_widget(footer, chunks[2]);
+
+        if let Some(input) = &self.input {
+            render_input_popup(frame, input, main[0]);
+        }
+    }
+
+    /// Weekly volume per muscle group with the overall balance score in the title
+    fn balance_table(&self) -> Table<'static> {
+        let rows: Vec<Row> = self.tracker.get_weekly_report()
+            .into_iter()
+            .map(|(group, volume, bar)| {
+                Row::new(vec![
+                    Cell::from(group.name_ru()),
+                    Cell::from(volume.to_string()),
+                    Cell::from(bar),
+                ])
+            })
+            .collect();
+
+        Table::new(
+            rows,
+            [Constraint::Length(15), Constraint::Length(6), Constraint::Length(8)],
+        )
+        .header(Row::new(vec!["Muscle", "Week", "Load"]).style(Style::default().bold()))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Balance {:.0}%", self.tracker.get_balance_score())),
+        )
+    }
+
+    /// Table title with active filters and match count
+    fn table_title(&self, shown: usize) -> String {
+        let mut filters = Vec::new();
+        if !self.filter.is_empty() {
+            filters.push(format!("\"{}\"", self.filter));
+        }
+        if let Some(category) = self.category_filter {
+            filters.push(format!("{} {}", category.emoji(), category.name_ru()));
+        }
+        if filters.is_empty() {
+            "Trainings".to_string()
+        } else {
+            format!("Trainings [{}] {}/{}", filters.join(", "), shown, self.trainings.len())
+        }
     }
 
     fn handle_events(&mut self) -> Result<()> {
-        if event::poll(std::time::Duration::from_millis(100))?
+        if event::poll(Duration::from_millis(100))?
             && let Event::Key(key) = event::read()?
                 && key.kind == KeyEventKind::Press {
+                    self.last_key = Instant::now();
+                    if self.input.is_some() {
+                        return self.handle_input_key(key.code);
+                    }
+                    self.status = None;
+
+                    if self.editing_filter {
+                        match key.code {
+                            KeyCode::Char(c) => self.filter.push(c),
+                            KeyCode::Backspace => {
+                                self.filter.pop();
+                            }
+                            KeyCode::Enter => self.editing_filter = false,
+                            KeyCode::Esc => self.clear_filters(),
+                            _ => {}
+                        }
+                        return Ok(());
+                    }
+
                     match key.code {
                         KeyCode::Char('q') => self.should_quit = true,
-                        KeyCode::Char('r') => {
-                            self.trainings = self.db.get_trainings()?;
-                        }
+                        KeyCode::Char('r') => self.refresh()?,
+                        KeyCode::Char('a') => self.input = Some(InputState::new()),
+                        KeyCode::Char('d') if !self.show_volume => self.delete_selected()?,
+                        KeyCode::Char('v') => self.show_volume = !self.show_volume,
+                        KeyCode::Down => self.move_selection(1),
+                        KeyCode::Up => self.move_selection(-1),
+                        KeyCode::Char('/') => self.editing_filter = true,
+                        KeyCode::Tab => self.category_filter = self.next_category_filter(),
+                        KeyCode::Esc => self.clear_filters(),
                         _ => {}
                     }
                 }
         Ok(())
     }
+
+    /// Move the highlight by `delta` rows within the visible trainings
+    fn move_selection(&mut self, delta: isize) {
+        let len = self.visible_trainings().len();
+        if len == 0 {
+            self.selected = None;
+            return;
+        }
+        let next = match self.selected {
+            Some(i) => i.saturating_add_signed(delta).min(len - 1),
+            None => 0,
+        };
+        self.selected = Some(next);
+    }
+
+    /// Delete the highlighted training: CLI records, or the owner's own
+    fn delete_selected(&mut self) -> Result<()> {
+        let Some(training) = self.selected.and_then(|i| self.visible_trainings().get(i).copied().cloned()) else {
+            self.status = Some("nothing selected (↑↓ to pick a row)".to_string());
+            return Ok(());
+        };
+        let Some(id) = training.id else {
+            self.status = Some("selected training has no id".to_string());
+            return Ok(());
+        };
+
+        let deleted = match training.user_id {
+            None => self.db.delete_training_cli(id)?,
+            Some(user_id) => match self.db.get_owner()? {
+                Some(owner) if owner.id == user_id => self.db.delete_training(id, owner.id)?,
+                _ => {
+                    self.status = Some(format!("id {} belongs to another user", id));
+                    return Ok(());
+                }
+            },
+        };
+        self.status = Some(if deleted {
+            format!("deleted id {}", id)
+        } else {
+            format!("id {} not found", id)
+        });
+
+        self.refresh()?;
+        Ok(())
+    }
+
+    /// Keys while the add-training form is open
+    fn handle_input_key(&mut self, code: KeyCode) -> Result<()> {
+        let Some(input) = self.input.as_mut() else {
+            return Ok(());
+        };
+        match code {
+            KeyCode::Esc => self.input = None,
+            KeyCode::Char(c) => input.push(c),
+            KeyCode::Backspace => input.pop(),
+            KeyCode::Tab | KeyCode::Down => input.field = input.field.next(),
+            KeyCode::BackTab | KeyCode::Up => input.field = input.field.prev(),
+            KeyCode::Enter if input.field != InputField::Reps => input.field = input.field.next(),
+            KeyCode::Enter => match input.to_training() {
+                Ok(training) => {
+                    self.db.add_training_cli(&training)?;
+                    self.input = None;
+                    self.refresh()?;
+                }
+                Err(message) => input.error = Some(message),
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn clear_filters(&mut self) {
+        self.filter.clear();
+        self.editing_filter = false;
+        self.category_filter = None;
+        self.selected = None;
+    }
+}
+
+/// Add-training form drawn over the table
+fn render_input_popup(frame: &mut Frame, input: &InputState, area: Rect) {
+    let popup = centered_rect(area, 44, 8);
+
+    let field_line = |label: &str, value: &str, field: InputField| {
+        let active = input.field == field;
+        let cursor = if active { "_" } else { "" };
+        let style = if active {
+            Style::default().fg(Color::Yellow).bold()
+        } else {
+            Style::default()
+        };
+        Line::from(vec![
+            Span::styled(format!("{:<10}", label), style),
+            Span::raw(format!("{}{}", value, cursor)),
+        ])
+    };
+    let mut lines = vec![
+        field_line("Exercise", &input.exercise, InputField::Exercise),
+        field_line("Sets", &input.sets, InputField::Sets),
+        field_line("Reps", &input.reps, InputField::Reps),
+    ];
+    if let Some(error) = &input.error {
+        lines.push(Line::from(""));
+        lines.push(Line::styled(error.clone(), Style::default().fg(Color::Red)));
+    }
+
+    let form = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("Add training"));
+    frame.render_widget(Clear, popup);
+    frame.render_widget(form, popup);
+}
+
+/// Rect of at most `width` x `height` centered in `area`
+fn centered_rect(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
 }
 
 fn init_terminal() -> Result<Tui> {
@@ -124,3 +566,143 @@ fn restore_terminal() -> Result<()> {
     stdout().execute(LeaveAlternateScreen)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn training_at(exercise: &str, date: &str, sets: i32, reps: i32) -> Training {
+        Training {
+            id: None,
+            date: DateTime::parse_from_rfc3339(date).unwrap().with_timezone(&Utc),
+            exercise: exercise.to_string(),
+            sets,
+            reps,
+            duration_secs: None,
+            pulse_before: None,
+            pulse_after: None,
+            rpe: None,
+            notes: None,
+            user_id: None,
+            context: None,
+            category: None,
+            is_timed: None,
+        }
+    }
+
+    fn app_with(trainings: Vec<Training>) -> App {
+        let mut app = App::new(Database::open(":memory:").unwrap()).unwrap();
+        app.trainings = trainings;
+        app
+    }
+
+    fn day(d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2026, 1, d).unwrap()
+    }
+
+    #[test]
+    fn test_daily_volume_rest_days_are_zero() {
+        let app = app_with(vec![
+            training_at("пловец", "2026-01-07T09:00:00+00:00", 3, 10),
+            training_at("пловец", "2026-01-07T10:00:00+00:00", 1, 5),
+            training_at("джекнайф", "2026-01-04T09:00:00+00:00", 2, 8),
+            // Outside the window
+            training_at("пловец", "2025-12-30T09:00:00+00:00", 9, 9),
+        ]);
+        let now = DateTime::parse_from_rfc3339("2026-01-07T12:00:00+00:00").unwrap().with_timezone(&Utc);
+
+        let volume = app.daily_volume(now);
+        assert_eq!(volume.len(), VOLUME_DAYS as usize);
+        assert_eq!(volume, vec![
+            (day(1), 0), (day(2), 0), (day(3), 0), (day(4), 16), (day(5), 0), (day(6), 0), (day(7), 35),
+        ]);
+    }
+
+    #[test]
+    fn test_daily_volume_buckets_by_local_midnight() {
+        // Moscow midnight of Jan 7 is 21:00 UTC on Jan 6
+        let app = app_with(vec![
+            training_at("пловец", "2026-01-06T20:59:59+00:00", 1, 10),
+            training_at("пловец", "2026-01-06T21:00:00+00:00", 1, 20),
+        ]);
+        let now = DateTime::parse_from_rfc3339("2026-01-07T12:00:00+00:00").unwrap().with_timezone(&Utc);
+
+        let volume = app.daily_volume(now);
+        assert_eq!(volume[VOLUME_DAYS as usize - 2], (day(6), 10));
+        assert_eq!(volume[VOLUME_DAYS as usize - 1], (day(7), 20));
+    }
+
+    #[test]
+    fn test_visible_trainings_combined_filters() {
+        let with_category = |exercise: &str, category| Training {
+            category: Some(category),
+            ..training_at(exercise, "2026-01-07T09:00:00+00:00", 1, 10)
+        };
+        let mut app = app_with(vec![
+            with_category("Отжимания узко", Category::Push),
+            with_category("отжимания широко", Category::Push),
+            with_category("Тяга отжиманий", Category::Pull),
+            with_category("планка", Category::Core),
+        ]);
+        let names = |app: &App| -> Vec<String> {
+            app.visible_trainings().iter().map(|t| t.exercise.clone()).collect()
+        };
+        assert_eq!(names(&app).len(), 4);
+
+        app.filter = "ОТЖИМ".to_string();
+        assert_eq!(names(&app), vec!["Отжимания узко", "отжимания широко", "Тяга отжиманий"]);
+
+        app.category_filter = Some(Category::Push);
+        assert_eq!(names(&app), vec!["Отжимания узко", "отжимания широко"]);
+
+        app.filter = "планка".to_string();
+        assert!(names(&app).is_empty());
+
+        app.filter.clear();
+        app.category_filter = Some(Category::Core);
+        assert_eq!(names(&app), vec!["планка"]);
+    }
+
+    #[test]
+    fn test_refresh_due_waits_for_idle_interval() {
+        let interval = Duration::from_millis(100);
+        assert!(!app_with(Vec::new()).refresh_due(), "no interval: only on `r`");
+        assert!(!app_with(Vec::new()).with_auto_refresh(Some(Duration::ZERO)).refresh_due());
+
+        let mut app = app_with(Vec::new()).with_auto_refresh(Some(interval));
+        assert!(!app.refresh_due(), "just loaded");
+
+        std::thread::sleep(interval * 2);
+        assert!(app.refresh_due());
+
+        // A recent keypress postpones the reload
+        app.last_key = Instant::now();
+        assert!(!app.refresh_due());
+
+        std::thread::sleep(interval * 2);
+        app.refresh().unwrap();
+        assert!(!app.refresh_due(), "reload restarts the interval");
+    }
+
+    fn form(sets: &str, reps: &str) -> InputState {
+        InputState {
+            exercise: "пловец".to_string(),
+            sets: sets.to_string(),
+            reps: reps.to_string(),
+            ..InputState::default()
+        }
+    }
+
+    #[test]
+    fn test_to_training_bounds() {
+        let training = form("3", "1000").to_training().unwrap();
+        assert_eq!((training.sets, training.reps), (3, 1000));
+
+        assert_eq!(form("0", "10").to_training().unwrap_err(), validate_sets(0).unwrap_err());
+        assert_eq!(form("1000000", "10").to_training().unwrap_err(), validate_sets(1_000_000).unwrap_err());
+        assert_eq!(form("1", "1001").to_training().unwrap_err(), validate_reps(1001).unwrap_err());
+        // Too many digits for i32 is out of range too, not a panic
+        assert!(form("1", "99999999999").to_training().is_err());
+        assert!(InputState { exercise: " ".to_string(), ..form("1", "10") }.to_training().is_err());
+    }
+}